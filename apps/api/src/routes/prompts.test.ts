@@ -69,6 +69,28 @@ describe('Prompts Route', () => {
     expect(data.prompts.every((p: any) => p.visibility === 'private')).toBe(true);
   });
 
+  it('should only return prompts updated since the given time', async () => {
+    const all = await (await app.request('/', { headers: getAuthHeaders() })).json();
+    const newest = Math.max(...all.prompts.map((p: any) => new Date(p.updatedAt).getTime()));
+
+    const res = await app.request(`/?updatedSince=${encodeURIComponent(new Date(newest).toISOString())}`, {
+      headers: getAuthHeaders()
+    });
+    const data = await res.json();
+
+    expect(res.status).toBe(200);
+    expect(data.prompts.length).toBeGreaterThan(0);
+    expect(data.prompts.every((p: any) => new Date(p.updatedAt).getTime() >= newest)).toBe(true);
+  });
+
+  it('should reject an invalid updatedSince', async () => {
+    const res = await app.request('/?updatedSince=yesterday', {
+      headers: getAuthHeaders()
+    });
+
+    expect(res.status).toBe(400);
+  });
+
   it('should create a new prompt', async () => {
     const promptData = {
       title: 'Test Prompt',
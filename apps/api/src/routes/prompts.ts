@@ -22,23 +22,41 @@ prompts.get('/', async (c) => {
     const visibility = c.req.query('visibility');
     const tagId = c.req.query('tagId');
     const workspaceId = c.req.query('workspaceId');
+    const updatedSince = c.req.query('updatedSince');
+
+    // Incremental sync clients pass the newest updatedAt they have seen
+    let since: Date | undefined;
+    if (updatedSince) {
+      since = new Date(updatedSince);
+      if (Number.isNaN(since.getTime())) {
+        return c.json(
+          createErrorResponse(
+            ERROR_CODES.INVALID_REQUEST_DATA,
+            'updatedSince must be an ISO 8601 date',
+            { requestId }
+          ),
+          400
+        );
+      }
+    }
 
     const results = await fileStorage.getPrompts({
       status,
       visibility,
       tagId,
       workspaceId,
+      updatedSince: since,
     });
 
     await fileStorage.logAccess('*', auth?.user?.id || 'anonymous', 'list', {
-      filters: { status, visibility, tagId, workspaceId },
+      filters: { status, visibility, tagId, workspaceId, updatedSince },
       resultCount: results.length
     });
 
     logger.debug({ 
       requestId,
       userId: auth?.user?.id,
-      filters: { status, visibility, tagId, workspaceId },
+      filters: { status, visibility, tagId, workspaceId, updatedSince },
       count: results.length 
     }, 'Retrieved prompts');
 
@@ -381,6 +381,7 @@ export class FileStorage {
     status?: string;
     visibility?: string;
     tagId?: string;
+    updatedSince?: Date;
   }): Promise<Prompt[]> {
     await this.initialize();
     
@@ -402,6 +403,12 @@ export class FileStorage {
       results = results.filter(prompt => prompt.tagIds.includes(filters.tagId!));
     }
 
+    if (filters?.updatedSince) {
+      // Inclusive so prompts sharing the cursor timestamp are not skipped
+      const since = filters.updatedSince.getTime();
+      results = results.filter(prompt => prompt.updatedAt.getTime() >= since);
+    }
+
     return results.sort((a, b) => b.updatedAt.getTime() - a.updatedAt.getTime());
   }
 
@@ -0,0 +1,1432 @@
+/*!
+ * PromPalette APIサーバーとの同期クライアント
+ *
+ * `apps/api` の `/api/v1/prompts` に対してプロンプトをpush/pullする
+ *
+ * 同期方式:
+ * - ローカルID⇔リモートIDの対応を `api_sync_links` に保存
+ * - ローカル削除は `api_sync_tombstones` の墓標としてサーバーに伝播
+ * - リモート側の変更は `updatedSince` のカーソルで差分取得し、リンクに記録した `updatedAt` との比較で検出
+ * - カーソルがない場合と一定間隔ごとに全件を取得する（サーバー側の削除は全件取得でのみ検出できる）
+ * - 一覧から消えたプロンプトは個別に取得し直し、サーバーが404を返した場合のみ削除とみなす
+ * - サーバー側の削除は墓標からのみ行い、ローカルで別のワークスペースに移動したプロンプトは移動として反映
+ * - 接続先やワークスペースを変更した場合は対応関係と墓標を破棄する
+ * - 双方で変更された場合は新しい方を採用（last-writer-wins）し、競合として報告
+ *
+ * HTTP通信は `PromptApi` トレイトで抽象化しているため、
+ * ローカルで起動したAPIサーバーやモックサーバーに対してテストできる
+ */
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::{Row, SqlitePool};
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::OnceLock;
+use tauri::{AppHandle, Emitter};
+
+use crate::database::{is_valid_workspace_id, Prompt, PromptStatus, PromptVisibility};
+use crate::environment::Environment;
+
+/// 同期設定ファイル名（アプリデータディレクトリに保存）
+const CONFIG_FILENAME: &str = "api_sync.json";
+
+/// APIサーバー側の本文の最大長（@prompalette/core の MAX_PROMPT_LENGTH）
+const REMOTE_MAX_CONTENT_LENGTH: usize = 10_000;
+
+/// APIサーバー側のタイトルの最大長（@prompalette/core の MAX_TITLE_LENGTH）
+const REMOTE_MAX_TITLE_LENGTH: usize = 100;
+
+/// APIサーバー側のタグ数上限
+const REMOTE_MAX_TAGS: usize = 20;
+
+/// デフォルトのワークスペースID（APIサーバーのデフォルトと同じ）
+const DEFAULT_WORKSPACE_ID: &str = "wsp_demo";
+
+/// 全件取得の間隔（差分取得ではサーバー側の削除を検出できないため）
+const FULL_SYNC_INTERVAL: chrono::Duration = chrono::Duration::hours(24);
+
+/// HTTPリクエストのタイムアウト
+const REQUEST_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// 同期処理の排他ロック
+static SYNC_LOCK: OnceLock<tokio::sync::Mutex<()>> = OnceLock::new();
+
+/// API同期エラー
+#[derive(Debug, Clone, Serialize)]
+pub enum ApiSyncError {
+    /// 接続失敗・タイムアウト
+    Network(String),
+    /// サーバーがエラーステータスを返した
+    Http { status: u16, message: String },
+    /// ローカルデータベースのエラー
+    Database(String),
+    /// 設定不備
+    Config(String),
+}
+
+impl std::fmt::Display for ApiSyncError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ApiSyncError::Network(msg) => write!(f, "Network error: {}", msg),
+            ApiSyncError::Http { status, message } => write!(f, "Server returned {}: {}", status, message),
+            ApiSyncError::Database(msg) => write!(f, "Database error: {}", msg),
+            ApiSyncError::Config(msg) => write!(f, "Configuration error: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for ApiSyncError {}
+
+impl From<sqlx::Error> for ApiSyncError {
+    fn from(err: sqlx::Error) -> Self {
+        ApiSyncError::Database(err.to_string())
+    }
+}
+
+// TauriコマンドでStringエラーとして返すための変換
+impl From<ApiSyncError> for String {
+    fn from(err: ApiSyncError) -> Self {
+        err.to_string()
+    }
+}
+
+/// API同期設定（永続化）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ApiSyncConfig {
+    /// APIサーバーのベースURL（例: `http://localhost:3000`）
+    pub base_url: Option<String>,
+    /// APIキー（Bearerトークン）。他のユーザーから読めないよう0600で保存する
+    pub api_key: Option<String>,
+    /// 同期対象のワークスペースID
+    pub workspace_id: Option<String>,
+    /// リモート変更のカーソル（取得済みの最大 `updatedAt`）
+    pub cursor: Option<chrono::DateTime<chrono::Utc>>,
+    /// 最後に全件を取得した日時
+    pub full_synced_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// 最終同期日時
+    pub last_synced_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl ApiSyncConfig {
+    fn config_path() -> Result<PathBuf, ApiSyncError> {
+        let env = Environment::current();
+        let data_dir = env.data_dir().map_err(|e| ApiSyncError::Config(e.to_string()))?;
+        Ok(data_dir.join(CONFIG_FILENAME))
+    }
+
+    /// 設定ファイルから読み込み（存在しない場合はデフォルト）
+    pub fn load() -> Result<Self, ApiSyncError> {
+        let path = Self::config_path()?;
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(&path).map_err(|e| ApiSyncError::Config(e.to_string()))?;
+        serde_json::from_str(&content).map_err(|e| ApiSyncError::Config(format!("Invalid sync config: {e}")))
+    }
+
+    /// 設定ファイルへ保存（APIキーを含むため所有者のみ読み書きできる権限で作成）
+    pub fn save(&self) -> Result<(), ApiSyncError> {
+        self.save_to(&Self::config_path()?)
+    }
+
+    fn save_to(&self, path: &std::path::Path) -> Result<(), ApiSyncError> {
+        use std::io::Write;
+
+        let content = serde_json::to_string_pretty(self).map_err(|e| ApiSyncError::Config(e.to_string()))?;
+        let to_error = |e: std::io::Error| ApiSyncError::Config(format!("Failed to save sync config: {e}"));
+
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+            options.mode(0o600);
+            // 以前のバージョンで作成されたファイルの権限も絞る
+            if path.exists() {
+                std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600)).map_err(to_error)?;
+            }
+        }
+        let mut file = options.open(path).map_err(to_error)?;
+        file.write_all(content.as_bytes()).map_err(to_error)
+    }
+
+    fn workspace_id(&self) -> &str {
+        self.workspace_id.as_deref().unwrap_or(DEFAULT_WORKSPACE_ID)
+    }
+
+    /// 差分取得に使うカーソル（全件取得が必要な場合は `None`）
+    fn updated_since(&self, now: chrono::DateTime<chrono::Utc>) -> Option<chrono::DateTime<chrono::Utc>> {
+        match self.full_synced_at {
+            Some(full_synced_at) if now - full_synced_at < FULL_SYNC_INTERVAL => self.cursor,
+            _ => None,
+        }
+    }
+}
+
+/// APIサーバー上のプロンプト
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RemotePrompt {
+    pub id: String,
+    pub title: Option<String>,
+    pub content: String,
+    #[serde(default)]
+    pub tag_ids: Vec<String>,
+    pub quick_access_key: Option<String>,
+    pub workspace_id: Option<String>,
+    #[serde(default)]
+    pub status: PromptStatus,
+    #[serde(default)]
+    pub visibility: PromptVisibility,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// APIサーバーへの作成・更新リクエスト
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RemotePromptInput {
+    pub title: Option<String>,
+    pub content: String,
+    pub tag_ids: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quick_access_key: Option<String>,
+    pub workspace_id: String,
+    pub status: PromptStatus,
+    pub visibility: PromptVisibility,
+}
+
+impl RemotePromptInput {
+    /// ローカルのプロンプトからAPIの制約に合わせたリクエストを生成
+    fn from_prompt(prompt: &Prompt, workspace_id: &str) -> Result<Self, String> {
+        if prompt.content.trim().is_empty() || prompt.content.chars().count() > REMOTE_MAX_CONTENT_LENGTH {
+            return Err(format!(
+                "Content must be 1-{REMOTE_MAX_CONTENT_LENGTH} characters to sync with the server"
+            ));
+        }
+
+        // タグIDは英数字・`_`・`-` のみ許可されている
+        let tag_ids = local_tags(prompt)
+            .iter()
+            .map(|tag| {
+                tag.chars()
+                    .map(|c| if c.is_ascii_alphanumeric() || c == '_' || c == '-' { c } else { '-' })
+                    .collect::<String>()
+            })
+            .filter(|tag| !tag.trim_matches('-').is_empty())
+            .take(REMOTE_MAX_TAGS)
+            .collect();
+
+        // クイックアクセスキーは英数字2-20文字のみ受け付けられる
+        let quick_access_key = prompt.quick_access_key.clone().filter(|key| {
+            (2..=20).contains(&key.len()) && key.chars().all(|c| c.is_ascii_alphanumeric())
+        });
+
+        Ok(Self {
+            title: prompt
+                .title
+                .as_ref()
+                .map(|title| title.chars().take(REMOTE_MAX_TITLE_LENGTH).collect()),
+            content: prompt.content.clone(),
+            tag_ids,
+            quick_access_key,
+            workspace_id: workspace_id.to_string(),
+            status: prompt.status,
+            visibility: prompt.visibility,
+        })
+    }
+}
+
+/// プロンプトAPIの抽象化（HTTPクライアント／テスト用モック）
+pub(crate) trait PromptApi {
+    /// `updated_since` を指定した場合はそれ以降に更新されたプロンプトのみ
+    async fn list_prompts(
+        &self,
+        workspace_id: &str,
+        updated_since: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> Result<Vec<RemotePrompt>, ApiSyncError>;
+    /// 存在しない場合は `Ok(None)`
+    async fn get_prompt(&self, id: &str) -> Result<Option<RemotePrompt>, ApiSyncError>;
+    async fn create_prompt(&self, input: &RemotePromptInput) -> Result<RemotePrompt, ApiSyncError>;
+    /// 存在しない場合は `Ok(None)`
+    async fn update_prompt(
+        &self,
+        id: &str,
+        input: &RemotePromptInput,
+    ) -> Result<Option<RemotePrompt>, ApiSyncError>;
+    /// 存在しない場合は `Ok(false)`
+    async fn delete_prompt(&self, id: &str) -> Result<bool, ApiSyncError>;
+}
+
+/// HTTPによるAPIクライアント
+pub struct HttpPromptApi {
+    base_url: String,
+    api_key: String,
+    client: reqwest::Client,
+}
+
+#[derive(Deserialize)]
+struct PromptListResponse {
+    prompts: Vec<RemotePrompt>,
+}
+
+#[derive(Deserialize)]
+struct PromptResponse {
+    prompt: RemotePrompt,
+}
+
+impl HttpPromptApi {
+    pub fn new(base_url: &str, api_key: &str) -> Result<Self, ApiSyncError> {
+        let base_url = base_url.trim_end_matches('/');
+        if !(base_url.starts_with("http://") || base_url.starts_with("https://")) {
+            return Err(ApiSyncError::Config("Server URL must start with http:// or https://".to_string()));
+        }
+        let client = reqwest::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .build()
+            .map_err(|e| ApiSyncError::Network(e.to_string()))?;
+
+        Ok(Self {
+            base_url: base_url.to_string(),
+            api_key: api_key.to_string(),
+            client,
+        })
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}/api/v1/prompts{}", self.base_url, path)
+    }
+
+    async fn send(&self, request: reqwest::RequestBuilder) -> Result<reqwest::Response, ApiSyncError> {
+        request
+            .bearer_auth(&self.api_key)
+            .send()
+            .await
+            .map_err(|e| ApiSyncError::Network(e.to_string()))
+    }
+
+    /// エラーレスポンスを `ApiSyncError` に変換
+    async fn error_from(response: reqwest::Response) -> ApiSyncError {
+        let status = response.status().as_u16();
+        let body = response.text().await.unwrap_or_default();
+        let message = serde_json::from_str::<serde_json::Value>(&body)
+            .ok()
+            .and_then(|json| {
+                json.pointer("/error/message")
+                    .or_else(|| json.get("message"))
+                    .and_then(|message| message.as_str().map(str::to_string))
+            })
+            .unwrap_or(body);
+        ApiSyncError::Http { status, message }
+    }
+
+    async fn parse<T: serde::de::DeserializeOwned>(response: reqwest::Response) -> Result<T, ApiSyncError> {
+        response
+            .json::<T>()
+            .await
+            .map_err(|e| ApiSyncError::Network(format!("Invalid response body: {e}")))
+    }
+}
+
+impl PromptApi for HttpPromptApi {
+    async fn list_prompts(
+        &self,
+        workspace_id: &str,
+        updated_since: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> Result<Vec<RemotePrompt>, ApiSyncError> {
+        let mut request = self.client.get(self.url("")).query(&[("workspaceId", workspace_id)]);
+        if let Some(updated_since) = updated_since {
+            request = request.query(&[(
+                "updatedSince",
+                updated_since.to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+            )]);
+        }
+        let response = self.send(request).await?;
+        if !response.status().is_success() {
+            return Err(Self::error_from(response).await);
+        }
+        Ok(Self::parse::<PromptListResponse>(response).await?.prompts)
+    }
+
+    async fn get_prompt(&self, id: &str) -> Result<Option<RemotePrompt>, ApiSyncError> {
+        let response = self.send(self.client.get(self.url(&format!("/{id}")))).await?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !response.status().is_success() {
+            return Err(Self::error_from(response).await);
+        }
+        Ok(Some(Self::parse::<PromptResponse>(response).await?.prompt))
+    }
+
+    async fn create_prompt(&self, input: &RemotePromptInput) -> Result<RemotePrompt, ApiSyncError> {
+        let response = self.send(self.client.post(self.url("")).json(input)).await?;
+        if !response.status().is_success() {
+            return Err(Self::error_from(response).await);
+        }
+        Ok(Self::parse::<PromptResponse>(response).await?.prompt)
+    }
+
+    async fn update_prompt(
+        &self,
+        id: &str,
+        input: &RemotePromptInput,
+    ) -> Result<Option<RemotePrompt>, ApiSyncError> {
+        let response = self.send(self.client.put(self.url(&format!("/{id}"))).json(input)).await?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !response.status().is_success() {
+            return Err(Self::error_from(response).await);
+        }
+        Ok(Some(Self::parse::<PromptResponse>(response).await?.prompt))
+    }
+
+    async fn delete_prompt(&self, id: &str) -> Result<bool, ApiSyncError> {
+        let response = self.send(self.client.delete(self.url(&format!("/{id}")))).await?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(false);
+        }
+        if !response.status().is_success() {
+            return Err(Self::error_from(response).await);
+        }
+        Ok(true)
+    }
+}
+
+/// 競合時に採用した側
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConflictWinner {
+    Local,
+    Remote,
+}
+
+/// 同期競合（last-writer-winsで解決済み）
+#[derive(Debug, Clone, Serialize)]
+pub struct ApiSyncConflict {
+    pub local_id: String,
+    pub remote_id: String,
+    pub winner: ConflictWinner,
+    pub local_updated_at: Option<String>,
+    pub remote_updated_at: Option<String>,
+}
+
+/// 同期結果
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ApiSyncReport {
+    pub pushed: usize,
+    pub pulled: usize,
+    pub deleted_remote: usize,
+    pub deleted_local: usize,
+    pub conflicts: Vec<ApiSyncConflict>,
+    /// 同期できなかったプロンプト（ローカルID, 理由）
+    pub skipped: Vec<(String, String)>,
+    /// 次回の差分取得に使うカーソル
+    pub cursor: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// 同期済みの対応関係
+struct SyncLink {
+    local_id: String,
+    remote_id: String,
+    local_hash: String,
+    remote_updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// リンク先のリモートプロンプトの状態
+enum RemoteState<'r> {
+    /// 今回の一覧に含まれていた
+    Listed(&'r RemotePrompt),
+    /// 差分取得の一覧に含まれていない（前回の同期から変更なし）
+    Unchanged,
+    /// 全件取得の一覧に含まれていない（移動または削除）
+    Missing,
+}
+
+/// ローカルプロンプトのタグ一覧
+fn local_tags(prompt: &Prompt) -> Vec<String> {
+    prompt
+        .tags
+        .as_deref()
+        .and_then(|tags| serde_json::from_str(tags).ok())
+        .unwrap_or_default()
+}
+
+/// 同期対象フィールドのハッシュ（ローカル変更の検出用）
+fn local_hash(prompt: &Prompt) -> String {
+    let canonical = serde_json::json!([
+        prompt.title,
+        prompt.content,
+        local_tags(prompt),
+        prompt.quick_access_key,
+        prompt.status,
+        prompt.visibility,
+    ]);
+    let digest = Sha256::digest(canonical.to_string().as_bytes());
+    digest.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// 同期エンジン
+///
+/// テストのためにデータベースプールとAPIクライアントを外部から受け取る
+pub(crate) struct ApiSyncEngine<'a, A: PromptApi> {
+    pool: &'a SqlitePool,
+    api: &'a A,
+    workspace_id: &'a str,
+}
+
+impl<'a, A: PromptApi> ApiSyncEngine<'a, A> {
+    pub(crate) fn new(pool: &'a SqlitePool, api: &'a A, workspace_id: &'a str) -> Self {
+        Self { pool, api, workspace_id }
+    }
+
+    /// 1回分の同期を実行
+    ///
+    /// updated_since: 前回のカーソル（`None` の場合は全件取得）
+    pub(crate) async fn run(
+        &self,
+        updated_since: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> Result<ApiSyncReport, ApiSyncError> {
+        let mut report = ApiSyncReport::default();
+
+        // 1. ローカル削除（墓標）をサーバーに伝播
+        self.push_tombstones(&mut report).await?;
+
+        // 2. 現在の状態を取得
+        let remote: HashMap<String, RemotePrompt> = self
+            .api
+            .list_prompts(self.workspace_id, updated_since)
+            .await?
+            .into_iter()
+            .map(|prompt| (prompt.id.clone(), prompt))
+            .collect();
+        // 別のワークスペースに割り当てられたプロンプトは対象外
+        let local: HashMap<String, Prompt> =
+            sqlx::query_as::<_, Prompt>("SELECT * FROM prompts WHERE workspace_id IS NULL OR workspace_id = $1")
+                .bind(self.workspace_id)
+                .fetch_all(self.pool)
+                .await?
+                .into_iter()
+                .map(|prompt| (prompt.id.clone(), prompt))
+                .collect();
+        let links = self.load_links().await?;
+
+        let linked_local: HashSet<String> = links.iter().map(|link| link.local_id.clone()).collect();
+        let linked_remote: HashSet<String> = links.iter().map(|link| link.remote_id.clone()).collect();
+
+        // 3. 同期済みプロンプトの差分を反映（シークレットにしたプロンプトは対象外）
+        for link in &links {
+            if local.get(&link.local_id).is_some_and(|prompt| prompt.is_secret) {
+                report.skipped.push((link.local_id.clone(), "Secret prompts are not synced".to_string()));
+                continue;
+            }
+            let remote_state = match remote.get(&link.remote_id) {
+                Some(remote) => RemoteState::Listed(remote),
+                None if updated_since.is_some() => RemoteState::Unchanged,
+                None => RemoteState::Missing,
+            };
+            self.sync_linked(link, local.get(&link.local_id), remote_state, &mut report)
+                .await?;
+        }
+
+        // 4. 未同期のローカルプロンプトをサーバーに作成（シークレットは送らない）
+        for prompt in local
+            .values()
+            .filter(|prompt| !prompt.is_secret && !linked_local.contains(&prompt.id))
+        {
+            self.push_new(prompt, &mut report).await?;
+        }
+
+        // 5. 未同期のリモートプロンプトをローカルに作成
+        for remote_prompt in remote.values().filter(|prompt| !linked_remote.contains(&prompt.id)) {
+            let local_id = uuid::Uuid::new_v4().to_string();
+            self.pull(&local_id, remote_prompt).await?;
+            report.pulled += 1;
+        }
+
+        // カーソルを取得済みの最大updatedAtまで進める
+        report.cursor = remote.values().map(|prompt| prompt.updated_at).chain(updated_since).max();
+        Ok(report)
+    }
+
+    async fn push_tombstones(&self, report: &mut ApiSyncReport) -> Result<(), ApiSyncError> {
+        let tombstones: Vec<(String,)> = sqlx::query_as("SELECT remote_id FROM api_sync_tombstones")
+            .fetch_all(self.pool)
+            .await?;
+
+        for (remote_id,) in tombstones {
+            // 既にサーバー側で削除されている場合も成功扱い
+            if self.api.delete_prompt(&remote_id).await? {
+                report.deleted_remote += 1;
+            }
+            sqlx::query("DELETE FROM api_sync_tombstones WHERE remote_id = $1")
+                .bind(&remote_id)
+                .execute(self.pool)
+                .await?;
+        }
+        Ok(())
+    }
+
+    async fn load_links(&self) -> Result<Vec<SyncLink>, ApiSyncError> {
+        let rows = sqlx::query("SELECT local_id, remote_id, local_hash, remote_updated_at FROM api_sync_links")
+            .fetch_all(self.pool)
+            .await?;
+        Ok(rows
+            .into_iter()
+            .map(|row| SyncLink {
+                local_id: row.get("local_id"),
+                remote_id: row.get("remote_id"),
+                local_hash: row.get("local_hash"),
+                remote_updated_at: row.get("remote_updated_at"),
+            })
+            .collect())
+    }
+
+    async fn save_link(&self, local: &Prompt, remote: &RemotePrompt) -> Result<(), ApiSyncError> {
+        sqlx::query(
+            r"
+            INSERT OR REPLACE INTO api_sync_links (local_id, remote_id, local_hash, remote_updated_at, synced_at)
+            VALUES ($1, $2, $3, $4, $5)
+            ",
+        )
+        .bind(&local.id)
+        .bind(&remote.id)
+        .bind(local_hash(local))
+        .bind(remote.updated_at)
+        .bind(chrono::Utc::now())
+        .execute(self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn remove_link(&self, local_id: &str) -> Result<(), ApiSyncError> {
+        sqlx::query("DELETE FROM api_sync_links WHERE local_id = $1")
+            .bind(local_id)
+            .execute(self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// 同期済みプロンプト1件の差分を反映
+    async fn sync_linked(
+        &self,
+        link: &SyncLink,
+        local: Option<&Prompt>,
+        remote: RemoteState<'_>,
+        report: &mut ApiSyncReport,
+    ) -> Result<(), ApiSyncError> {
+        let Some(local) = local else {
+            return self.sync_out_of_scope(link, remote, report).await;
+        };
+        let local_changed = local_hash(local) != link.local_hash;
+
+        match remote {
+            RemoteState::Listed(remote) => match (local_changed, remote.updated_at > link.remote_updated_at) {
+                (false, false) => {}
+                (true, false) => self.push_update(local, &link.remote_id, report).await?,
+                (false, true) => {
+                    self.pull(&local.id, remote).await?;
+                    report.pulled += 1;
+                }
+                (true, true) => {
+                    // 両方で変更: 新しい方を採用
+                    let winner = if local.updated_at > remote.updated_at {
+                        self.push_update(local, &link.remote_id, report).await?;
+                        ConflictWinner::Local
+                    } else {
+                        self.pull(&local.id, remote).await?;
+                        report.pulled += 1;
+                        ConflictWinner::Remote
+                    };
+                    report.conflicts.push(ApiSyncConflict {
+                        local_id: local.id.clone(),
+                        remote_id: remote.id.clone(),
+                        winner,
+                        local_updated_at: Some(local.updated_at.to_rfc3339()),
+                        remote_updated_at: Some(remote.updated_at.to_rfc3339()),
+                    });
+                }
+            },
+            RemoteState::Unchanged => {
+                if local_changed {
+                    self.push_update(local, &link.remote_id, report).await?;
+                }
+            }
+            RemoteState::Missing => {
+                // 一覧にないだけでは削除とみなさない（別のワークスペースへの移動など）
+                if let Some(moved) = self.api.get_prompt(&link.remote_id).await? {
+                    return self.sync_moved(link, local, &moved, local_changed, report).await;
+                }
+
+                self.remove_link(&local.id).await?;
+                if local_changed {
+                    // サーバーで削除されたがローカルで編集されている: ローカルを残して再作成
+                    self.push_new(local, report).await?;
+                    report.conflicts.push(ApiSyncConflict {
+                        local_id: local.id.clone(),
+                        remote_id: link.remote_id.clone(),
+                        winner: ConflictWinner::Local,
+                        local_updated_at: Some(local.updated_at.to_rfc3339()),
+                        remote_updated_at: None,
+                    });
+                } else {
+                    // サーバーが404を返した（削除済み）
+                    // 墓標を残さないよう、database::delete_promptを経由せずに削除
+                    sqlx::query("DELETE FROM prompts WHERE id = $1")
+                        .bind(&local.id)
+                        .execute(self.pool)
+                        .await?;
+                    report.deleted_local += 1;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// 同期対象のワークスペースのローカル一覧にない同期済みプロンプト
+    ///
+    /// ローカルでの削除は墓標として `push_tombstones` で処理済みのため、ここではサーバー側を削除しない。
+    /// 別のワークスペースに移動されていればその移動をサーバーに反映し（以降は同期対象外）、
+    /// 墓標なしで消えている場合はサーバーの内容から復元する
+    async fn sync_out_of_scope(
+        &self,
+        link: &SyncLink,
+        remote: RemoteState<'_>,
+        report: &mut ApiSyncReport,
+    ) -> Result<(), ApiSyncError> {
+        let local = sqlx::query_as::<_, Prompt>("SELECT * FROM prompts WHERE id = $1")
+            .bind(&link.local_id)
+            .fetch_optional(self.pool)
+            .await?;
+
+        match local {
+            Some(local) if local.is_secret => {
+                report.skipped.push((local.id.clone(), "Secret prompts are not synced".to_string()));
+            }
+            Some(local) => {
+                // ローカルで別のワークスペースに移動された
+                let workspace_id = local.workspace_id.as_deref().unwrap_or(self.workspace_id);
+                match remote {
+                    RemoteState::Listed(remote) if remote.updated_at > link.remote_updated_at => {
+                        // 移動後にサーバー側でも変更された: 新しい方を採用
+                        let winner = if local.updated_at > remote.updated_at {
+                            if self.push_update_in(&local, &link.remote_id, workspace_id, report).await? {
+                                self.remove_link(&local.id).await?;
+                            }
+                            ConflictWinner::Local
+                        } else {
+                            self.pull(&local.id, remote).await?;
+                            report.pulled += 1;
+                            ConflictWinner::Remote
+                        };
+                        report.conflicts.push(ApiSyncConflict {
+                            local_id: local.id.clone(),
+                            remote_id: remote.id.clone(),
+                            winner,
+                            local_updated_at: Some(local.updated_at.to_rfc3339()),
+                            remote_updated_at: Some(remote.updated_at.to_rfc3339()),
+                        });
+                    }
+                    _ => {
+                        if self.push_update_in(&local, &link.remote_id, workspace_id, report).await? {
+                            self.remove_link(&local.id).await?;
+                        }
+                    }
+                }
+            }
+            None => {
+                // 墓標なしでローカルから消えている（同期前のデータ移行など）
+                let restored = match remote {
+                    RemoteState::Listed(remote) => Some(remote.clone()),
+                    RemoteState::Unchanged | RemoteState::Missing => self.api.get_prompt(&link.remote_id).await?,
+                };
+                match restored {
+                    Some(remote)
+                        if remote.workspace_id.as_deref().is_none_or(|id| id == self.workspace_id) =>
+                    {
+                        self.pull(&link.local_id, &remote).await?;
+                        report.pulled += 1;
+                    }
+                    _ => self.remove_link(&link.local_id).await?,
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// 同期対象のワークスペースの一覧から外れたが、サーバーには残っているプロンプト
+    ///
+    /// ローカルが未変更ならサーバー側の移動を取り込み（以降は同期対象外）、
+    /// 変更されていれば競合としてローカルの内容でこのワークスペースに戻す
+    async fn sync_moved(
+        &self,
+        link: &SyncLink,
+        local: &Prompt,
+        remote: &RemotePrompt,
+        local_changed: bool,
+        report: &mut ApiSyncReport,
+    ) -> Result<(), ApiSyncError> {
+        if local_changed {
+            self.push_update(local, &link.remote_id, report).await?;
+            report.conflicts.push(ApiSyncConflict {
+                local_id: local.id.clone(),
+                remote_id: remote.id.clone(),
+                winner: ConflictWinner::Local,
+                local_updated_at: Some(local.updated_at.to_rfc3339()),
+                remote_updated_at: Some(remote.updated_at.to_rfc3339()),
+            });
+        } else {
+            self.pull(&local.id, remote).await?;
+            self.remove_link(&local.id).await?;
+            report.pulled += 1;
+        }
+        Ok(())
+    }
+
+    /// ローカルの新規プロンプトをサーバーに作成
+    async fn push_new(&self, local: &Prompt, report: &mut ApiSyncReport) -> Result<(), ApiSyncError> {
+        let input = match RemotePromptInput::from_prompt(local, self.workspace_id) {
+            Ok(input) => input,
+            Err(reason) => {
+                report.skipped.push((local.id.clone(), reason));
+                return Ok(());
+            }
+        };
+        let remote = self.api.create_prompt(&input).await?;
+        self.save_link(local, &remote).await?;
+        report.pushed += 1;
+        Ok(())
+    }
+
+    /// ローカルの変更をサーバーに反映
+    async fn push_update(
+        &self,
+        local: &Prompt,
+        remote_id: &str,
+        report: &mut ApiSyncReport,
+    ) -> Result<(), ApiSyncError> {
+        self.push_update_in(local, remote_id, self.workspace_id, report).await?;
+        Ok(())
+    }
+
+    /// ローカルの変更を指定したワークスペースのプロンプトとしてサーバーに反映
+    ///
+    /// APIの制約を満たさず送れなかった場合は `Ok(false)`
+    async fn push_update_in(
+        &self,
+        local: &Prompt,
+        remote_id: &str,
+        workspace_id: &str,
+        report: &mut ApiSyncReport,
+    ) -> Result<bool, ApiSyncError> {
+        let input = match RemotePromptInput::from_prompt(local, workspace_id) {
+            Ok(input) => input,
+            Err(reason) => {
+                report.skipped.push((local.id.clone(), reason));
+                return Ok(false);
+            }
+        };
+        match self.api.update_prompt(remote_id, &input).await? {
+            Some(remote) => self.save_link(local, &remote).await?,
+            // 同期中にサーバー側で削除された場合は作成し直す
+            None => {
+                let remote = self.api.create_prompt(&input).await?;
+                self.save_link(local, &remote).await?;
+            }
+        }
+        report.pushed += 1;
+        Ok(true)
+    }
+
+    /// サーバーの内容をローカルに反映（存在しない場合は作成）
+    async fn pull(&self, local_id: &str, remote: &RemotePrompt) -> Result<(), ApiSyncError> {
+        let now = chrono::Utc::now();
+        let tags_json = serde_json::to_string(&remote.tag_ids).map_err(|e| ApiSyncError::Database(e.to_string()))?;
+
+        let prompt = sqlx::query_as::<_, Prompt>(
+            r"
+            INSERT INTO prompts (id, title, content, tags, quick_access_key, created_at, updated_at, pinned_position, pinned_at, status, visibility, workspace_id)
+            VALUES ($1, $2, $3, $4, $5, $6, $6, NULL, NULL, $7, $8, $9)
+            ON CONFLICT(id) DO UPDATE SET
+                title = excluded.title,
+                content = excluded.content,
+                tags = excluded.tags,
+                quick_access_key = excluded.quick_access_key,
+                updated_at = excluded.updated_at,
+                status = excluded.status,
+                visibility = excluded.visibility,
+                workspace_id = excluded.workspace_id
+            RETURNING *
+            ",
+        )
+        .bind(local_id)
+        .bind(&remote.title)
+        .bind(&remote.content)
+        .bind(tags_json)
+        .bind(&remote.quick_access_key)
+        .bind(now)
+        .bind(remote.status)
+        .bind(remote.visibility)
+        .bind(remote.workspace_id.as_deref().unwrap_or(self.workspace_id))
+        .fetch_one(self.pool)
+        .await?;
+
+        self.save_link(&prompt, remote).await
+    }
+}
+
+/// 保存済み設定でAPIサーバーと同期
+pub async fn sync_with_config() -> Result<ApiSyncReport, ApiSyncError> {
+    let _guard = SYNC_LOCK.get_or_init(|| tokio::sync::Mutex::new(())).lock().await;
+
+    let mut config = ApiSyncConfig::load()?;
+    let base_url = config
+        .base_url
+        .clone()
+        .ok_or_else(|| ApiSyncError::Config("Server URL is not configured".to_string()))?;
+    let api_key = config
+        .api_key
+        .clone()
+        .ok_or_else(|| ApiSyncError::Config("API key is not configured".to_string()))?;
+
+    let api = HttpPromptApi::new(&base_url, &api_key)?;
+    let pool = crate::database::try_get_db_pool().map_err(ApiSyncError::Database)?;
+    let started_at = chrono::Utc::now();
+    let updated_since = config.updated_since(started_at);
+    let report = ApiSyncEngine::new(&pool, &api, config.workspace_id()).run(updated_since).await?;
+
+    config.cursor = report.cursor;
+    if updated_since.is_none() {
+        config.full_synced_at = Some(started_at);
+    }
+    config.last_synced_at = Some(chrono::Utc::now());
+    config.save()?;
+
+    Ok(report)
+}
+
+/// APIサーバー同期の設定
+///
+/// base_url: APIサーバーのURL
+/// api_key: APIキー
+/// workspace_id: 同期対象のワークスペース（省略時はサーバーのデフォルト）
+#[tauri::command]
+pub async fn configure_api_sync(
+    base_url: String,
+    api_key: String,
+    workspace_id: Option<String>,
+) -> Result<(), String> {
+    // URLの形式チェック
+    HttpPromptApi::new(&base_url, &api_key)?;
+
+    if workspace_id.as_deref().is_some_and(|workspace_id| !is_valid_workspace_id(workspace_id)) {
+        return Err("Invalid workspace ID format".to_string());
+    }
+
+    let _guard = SYNC_LOCK.get_or_init(|| tokio::sync::Mutex::new(())).lock().await;
+
+    let mut config = ApiSyncConfig::load()?;
+    // 接続先が変わった場合、以前の対応関係と墓標は新しい接続先では意味を持たない
+    // （残すと新しいサーバーにないプロンプトが削除扱いになる）
+    if config.base_url.as_deref() != Some(base_url.as_str()) || config.workspace_id != workspace_id {
+        let pool = crate::database::try_get_db_pool()?;
+        reset_sync_state(&pool).await?;
+        config.cursor = None;
+        config.full_synced_at = None;
+        config.last_synced_at = None;
+    }
+    config.base_url = Some(base_url);
+    config.api_key = Some(api_key);
+    config.workspace_id = workspace_id;
+    config.save()?;
+    Ok(())
+}
+
+/// 同期の対応関係と墓標を破棄
+async fn reset_sync_state(pool: &SqlitePool) -> Result<(), ApiSyncError> {
+    let mut tx = pool.begin().await?;
+    sqlx::query("DELETE FROM api_sync_links").execute(&mut *tx).await?;
+    sqlx::query("DELETE FROM api_sync_tombstones").execute(&mut *tx).await?;
+    tx.commit().await?;
+    Ok(())
+}
+
+/// APIサーバーとの同期を実行
+#[tauri::command]
+pub async fn sync_with_api(app_handle: AppHandle) -> Result<ApiSyncReport, String> {
+    let report = sync_with_config().await?;
+    crate::tray::refresh_tray(&app_handle);
+
+    if !report.conflicts.is_empty() {
+        if let Err(e) = app_handle.emit("api-sync-conflict", &report.conflicts) {
+            eprintln!("Failed to emit api sync conflict event: {e}");
+        }
+    }
+    if let Err(e) = app_handle.emit("api-sync-completed", &report) {
+        eprintln!("Failed to emit api sync completed event: {e}");
+    }
+    Ok(report)
+}
+
+/// APIサーバー同期の状態を取得（APIキーは返さない）
+#[tauri::command]
+pub async fn get_api_sync_status() -> Result<serde_json::Value, String> {
+    let config = ApiSyncConfig::load()?;
+    let pending_deletes: i64 = match crate::database::try_get_db_pool() {
+        Ok(pool) => sqlx::query_scalar("SELECT COUNT(*) FROM api_sync_tombstones")
+            .fetch_one(&pool)
+            .await
+            .map_err(|e| e.to_string())?,
+        Err(_) => 0,
+    };
+
+    Ok(serde_json::json!({
+        "configured": config.base_url.is_some() && config.api_key.is_some(),
+        "base_url": config.base_url,
+        "workspace_id": config.workspace_id(),
+        "last_synced_at": config.last_synced_at.map(|at| at.to_rfc3339()),
+        "pending_deletes": pending_deletes,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    /// インメモリのAPIモック
+    #[derive(Default)]
+    struct MockApi {
+        prompts: Mutex<HashMap<String, RemotePrompt>>,
+        next_id: Mutex<u32>,
+        /// list_promptsが返した件数（呼び出しごと）
+        listed: Mutex<Vec<usize>>,
+    }
+
+    impl MockApi {
+        fn insert(&self, id: &str, content: &str, updated_at: chrono::DateTime<chrono::Utc>) {
+            self.prompts.lock().unwrap().insert(
+                id.to_string(),
+                RemotePrompt {
+                    id: id.to_string(),
+                    title: None,
+                    content: content.to_string(),
+                    tag_ids: vec![],
+                    quick_access_key: None,
+                    workspace_id: Some(DEFAULT_WORKSPACE_ID.to_string()),
+                    status: PromptStatus::Active,
+                    visibility: PromptVisibility::Private,
+                    updated_at,
+                },
+            );
+        }
+
+        fn content(&self, id: &str) -> Option<String> {
+            self.prompts.lock().unwrap().get(id).map(|prompt| prompt.content.clone())
+        }
+    }
+
+    impl PromptApi for MockApi {
+        async fn list_prompts(
+            &self,
+            workspace_id: &str,
+            updated_since: Option<chrono::DateTime<chrono::Utc>>,
+        ) -> Result<Vec<RemotePrompt>, ApiSyncError> {
+            let prompts: Vec<RemotePrompt> = self
+                .prompts
+                .lock()
+                .unwrap()
+                .values()
+                .filter(|prompt| prompt.workspace_id.as_deref() == Some(workspace_id))
+                .filter(|prompt| updated_since.is_none_or(|since| prompt.updated_at >= since))
+                .cloned()
+                .collect();
+            self.listed.lock().unwrap().push(prompts.len());
+            Ok(prompts)
+        }
+
+        async fn get_prompt(&self, id: &str) -> Result<Option<RemotePrompt>, ApiSyncError> {
+            Ok(self.prompts.lock().unwrap().get(id).cloned())
+        }
+
+        async fn create_prompt(&self, input: &RemotePromptInput) -> Result<RemotePrompt, ApiSyncError> {
+            let mut next_id = self.next_id.lock().unwrap();
+            *next_id += 1;
+            let id = format!("prm_mock{}", *next_id);
+            self.insert(&id, &input.content, chrono::Utc::now());
+            Ok(self.prompts.lock().unwrap()[&id].clone())
+        }
+
+        async fn update_prompt(
+            &self,
+            id: &str,
+            input: &RemotePromptInput,
+        ) -> Result<Option<RemotePrompt>, ApiSyncError> {
+            let mut prompts = self.prompts.lock().unwrap();
+            Ok(prompts.get_mut(id).map(|prompt| {
+                prompt.content = input.content.clone();
+                prompt.workspace_id = Some(input.workspace_id.clone());
+                prompt.updated_at = chrono::Utc::now();
+                prompt.clone()
+            }))
+        }
+
+        async fn delete_prompt(&self, id: &str) -> Result<bool, ApiSyncError> {
+            Ok(self.prompts.lock().unwrap().remove(id).is_some())
+        }
+    }
+
+    async fn create_test_pool() -> SqlitePool {
+        let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+        crate::database::init_database_schema(&pool).await.unwrap();
+        pool
+    }
+
+    async fn insert_local(pool: &SqlitePool, id: &str, content: &str) {
+        sqlx::query("INSERT INTO prompts (id, content, tags, created_at, updated_at) VALUES ($1, $2, '[]', $3, $3)")
+            .bind(id)
+            .bind(content)
+            .bind(chrono::Utc::now())
+            .execute(pool)
+            .await
+            .unwrap();
+    }
+
+    async fn local_content(pool: &SqlitePool, id: &str) -> Option<String> {
+        sqlx::query_scalar("SELECT content FROM prompts WHERE id = $1")
+            .bind(id)
+            .fetch_optional(pool)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_push_and_pull_new_prompts() {
+        let pool = create_test_pool().await;
+        let api = MockApi::default();
+        insert_local(&pool, "local-1", "local prompt").await;
+        api.insert("prm_remote1", "remote prompt", chrono::Utc::now());
+
+        let report = ApiSyncEngine::new(&pool, &api, DEFAULT_WORKSPACE_ID).run(None).await.unwrap();
+
+        assert_eq!(report.pushed, 1);
+        assert_eq!(report.pulled, 1);
+        assert_eq!(api.prompts.lock().unwrap().len(), 2);
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM prompts").fetch_one(&pool).await.unwrap();
+        assert_eq!(count, 2);
+
+        // 2回目は差分なし
+        let report = ApiSyncEngine::new(&pool, &api, DEFAULT_WORKSPACE_ID).run(None).await.unwrap();
+        assert_eq!(report.pushed + report.pulled, 0);
+    }
+
+    #[tokio::test]
+    async fn test_tombstone_deletes_remote_prompt() {
+        let pool = create_test_pool().await;
+        let api = MockApi::default();
+        insert_local(&pool, "local-1", "to be deleted").await;
+        ApiSyncEngine::new(&pool, &api, DEFAULT_WORKSPACE_ID).run(None).await.unwrap();
+
+        // database::delete_promptと同じ墓標を記録
+        sqlx::query("INSERT INTO api_sync_tombstones SELECT remote_id, local_id, $1 FROM api_sync_links")
+            .bind(chrono::Utc::now())
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("DELETE FROM prompts").execute(&pool).await.unwrap();
+        sqlx::query("DELETE FROM api_sync_links").execute(&pool).await.unwrap();
+
+        let report = ApiSyncEngine::new(&pool, &api, DEFAULT_WORKSPACE_ID).run(None).await.unwrap();
+        assert_eq!(report.deleted_remote, 1);
+        assert!(api.prompts.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_remote_delete_removes_unchanged_local_prompt() {
+        let pool = create_test_pool().await;
+        let api = MockApi::default();
+        api.insert("prm_remote1", "remote prompt", chrono::Utc::now());
+        ApiSyncEngine::new(&pool, &api, DEFAULT_WORKSPACE_ID).run(None).await.unwrap();
+
+        api.prompts.lock().unwrap().clear();
+        let report = ApiSyncEngine::new(&pool, &api, DEFAULT_WORKSPACE_ID).run(None).await.unwrap();
+
+        assert_eq!(report.deleted_local, 1);
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM prompts").fetch_one(&pool).await.unwrap();
+        assert_eq!(count, 0);
+    }
+
+    #[tokio::test]
+    async fn test_prompt_moved_to_other_workspace_is_not_deleted() {
+        let pool = create_test_pool().await;
+        let api = MockApi::default();
+        api.insert("prm_remote1", "remote prompt", chrono::Utc::now());
+        ApiSyncEngine::new(&pool, &api, DEFAULT_WORKSPACE_ID).run(None).await.unwrap();
+
+        // 一覧からは消えるが、サーバーには残っている
+        api.prompts.lock().unwrap().get_mut("prm_remote1").unwrap().workspace_id = Some("wsp_other".to_string());
+        let report = ApiSyncEngine::new(&pool, &api, DEFAULT_WORKSPACE_ID).run(None).await.unwrap();
+
+        assert_eq!(report.deleted_local, 0);
+        let workspace_id: Option<String> = sqlx::query_scalar("SELECT workspace_id FROM prompts")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(workspace_id.as_deref(), Some("wsp_other"));
+        let links: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM api_sync_links").fetch_one(&pool).await.unwrap();
+        assert_eq!(links, 0);
+
+        // 以降はこのワークスペースの同期対象にならない
+        let report = ApiSyncEngine::new(&pool, &api, DEFAULT_WORKSPACE_ID).run(None).await.unwrap();
+        assert_eq!(report.pushed + report.pulled, 0);
+        assert_eq!(api.prompts.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_prompt_moved_locally_is_moved_on_server() {
+        let pool = create_test_pool().await;
+        let api = MockApi::default();
+        insert_local(&pool, "local-1", "local prompt").await;
+        ApiSyncEngine::new(&pool, &api, DEFAULT_WORKSPACE_ID).run(None).await.unwrap();
+
+        // ローカルで別のワークスペースに移動
+        sqlx::query("UPDATE prompts SET workspace_id = 'wsp_other'")
+            .execute(&pool)
+            .await
+            .unwrap();
+        let report = ApiSyncEngine::new(&pool, &api, DEFAULT_WORKSPACE_ID).run(None).await.unwrap();
+
+        assert_eq!(report.deleted_remote, 0);
+        let prompts = api.prompts.lock().unwrap().values().cloned().collect::<Vec<_>>();
+        assert_eq!(prompts.len(), 1);
+        assert_eq!(prompts[0].workspace_id.as_deref(), Some("wsp_other"));
+        assert_eq!(prompts[0].content, "local prompt");
+        let links: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM api_sync_links").fetch_one(&pool).await.unwrap();
+        assert_eq!(links, 0);
+        assert_eq!(local_content(&pool, "local-1").await.as_deref(), Some("local prompt"));
+    }
+
+    #[tokio::test]
+    async fn test_incremental_sync_only_fetches_changes() {
+        let pool = create_test_pool().await;
+        let api = MockApi::default();
+        let base = chrono::Utc::now() - chrono::Duration::hours(1);
+        for i in 0..5 {
+            api.insert(&format!("prm_remote{i}"), "remote prompt", base + chrono::Duration::minutes(i));
+        }
+
+        let report = ApiSyncEngine::new(&pool, &api, DEFAULT_WORKSPACE_ID).run(None).await.unwrap();
+        assert_eq!(report.pulled, 5);
+        assert_eq!(report.cursor, Some(base + chrono::Duration::minutes(4)));
+
+        // 変更がなければカーソル位置の1件しか取得しない
+        let report = ApiSyncEngine::new(&pool, &api, DEFAULT_WORKSPACE_ID)
+            .run(report.cursor)
+            .await
+            .unwrap();
+        assert_eq!(report.pushed + report.pulled + report.deleted_local + report.deleted_remote, 0);
+        assert_eq!(*api.listed.lock().unwrap(), vec![5, 1]);
+
+        // 差分に含まれないプロンプトもローカルの変更は送る
+        sqlx::query("UPDATE prompts SET content = 'edited' WHERE id = (SELECT local_id FROM api_sync_links WHERE remote_id = 'prm_remote0')")
+            .execute(&pool)
+            .await
+            .unwrap();
+        let report = ApiSyncEngine::new(&pool, &api, DEFAULT_WORKSPACE_ID)
+            .run(report.cursor)
+            .await
+            .unwrap();
+        assert_eq!(report.pushed, 1);
+        assert_eq!(report.deleted_local, 0);
+        assert_eq!(api.content("prm_remote0").as_deref(), Some("edited"));
+    }
+
+    #[test]
+    fn test_full_listing_without_cursor_or_when_stale() {
+        let now = chrono::Utc::now();
+        let cursor = now - chrono::Duration::minutes(5);
+        let mut config = ApiSyncConfig {
+            cursor: Some(cursor),
+            ..Default::default()
+        };
+        assert_eq!(config.updated_since(now), None);
+
+        config.full_synced_at = Some(now - chrono::Duration::hours(1));
+        assert_eq!(config.updated_since(now), Some(cursor));
+
+        config.full_synced_at = Some(now - FULL_SYNC_INTERVAL);
+        assert_eq!(config.updated_since(now), None);
+
+        config.full_synced_at = Some(now);
+        config.cursor = None;
+        assert_eq!(config.updated_since(now), None);
+    }
+
+    #[tokio::test]
+    async fn test_reset_sync_state_keeps_local_prompts() {
+        let pool = create_test_pool().await;
+        let api = MockApi::default();
+        insert_local(&pool, "local-1", "local prompt").await;
+        ApiSyncEngine::new(&pool, &api, DEFAULT_WORKSPACE_ID).run(None).await.unwrap();
+
+        // 接続先の変更: 新しいサーバーには何もない
+        reset_sync_state(&pool).await.unwrap();
+        let other_server = MockApi::default();
+        let report = ApiSyncEngine::new(&pool, &other_server, DEFAULT_WORKSPACE_ID).run(None).await.unwrap();
+
+        assert_eq!(report.deleted_local, 0);
+        assert_eq!(report.pushed, 1);
+        assert_eq!(local_content(&pool, "local-1").await.as_deref(), Some("local prompt"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_config_is_saved_owner_only() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = std::env::temp_dir().join(format!("prompalette-api-sync-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(CONFIG_FILENAME);
+        std::fs::write(&path, "{}").unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o644)).unwrap();
+
+        let config = ApiSyncConfig { api_key: Some("secret".to_string()), ..ApiSyncConfig::default() };
+        config.save_to(&path).unwrap();
+
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        assert!(std::fs::read_to_string(&path).unwrap().contains("secret"));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_secret_prompts_are_not_synced() {
+        let pool = create_test_pool().await;
+        let api = MockApi::default();
+        insert_local(&pool, "local-1", "shared prompt").await;
+        ApiSyncEngine::new(&pool, &api, DEFAULT_WORKSPACE_ID).run(None).await.unwrap();
+        let remote_id: String = sqlx::query_scalar("SELECT remote_id FROM api_sync_links")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+
+        // 同期済みのプロンプトをシークレットにし、新しいシークレットも追加
+        sqlx::query("UPDATE prompts SET content = 'vault:v1:AAAA', is_secret = 1")
+            .execute(&pool)
+            .await
+            .unwrap();
+        insert_local(&pool, "local-2", "vault:v1:BBBB").await;
+        sqlx::query("UPDATE prompts SET is_secret = 1 WHERE id = 'local-2'")
+            .execute(&pool)
+            .await
+            .unwrap();
+        api.insert(&remote_id, "remote edit", chrono::Utc::now() + chrono::Duration::seconds(5));
+
+        let report = ApiSyncEngine::new(&pool, &api, DEFAULT_WORKSPACE_ID).run(None).await.unwrap();
+
+        assert_eq!(report.pushed, 0);
+        assert_eq!(report.pulled, 0);
+        assert_eq!(report.skipped.len(), 1);
+        assert_eq!(api.prompts.lock().unwrap().len(), 1);
+        assert_eq!(api.content(&remote_id).as_deref(), Some("remote edit"));
+        assert_eq!(local_content(&pool, "local-1").await.as_deref(), Some("vault:v1:AAAA"));
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_edits_use_last_writer_wins() {
+        let pool = create_test_pool().await;
+        let api = MockApi::default();
+        insert_local(&pool, "local-1", "original").await;
+        ApiSyncEngine::new(&pool, &api, DEFAULT_WORKSPACE_ID).run(None).await.unwrap();
+        let remote_id: String = sqlx::query_scalar("SELECT remote_id FROM api_sync_links")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+
+        // ローカルを先に、リモートを後に編集
+        sqlx::query("UPDATE prompts SET content = 'local edit', updated_at = $1")
+            .bind(chrono::Utc::now())
+            .execute(&pool)
+            .await
+            .unwrap();
+        api.insert(&remote_id, "remote edit", chrono::Utc::now() + chrono::Duration::seconds(5));
+
+        let report = ApiSyncEngine::new(&pool, &api, DEFAULT_WORKSPACE_ID).run(None).await.unwrap();
+
+        assert_eq!(report.conflicts.len(), 1);
+        assert_eq!(report.conflicts[0].winner, ConflictWinner::Remote);
+        assert_eq!(local_content(&pool, "local-1").await.as_deref(), Some("remote edit"));
+        assert_eq!(api.content(&remote_id).as_deref(), Some("remote edit"));
+    }
+
+    #[test]
+    fn test_remote_input_respects_api_constraints() {
+        let prompt = Prompt {
+            id: "local-1".to_string(),
+            title: Some("t".repeat(150)),
+            content: "content".to_string(),
+            tags: Some(r#"["code review", "ok_tag", "日本語"]"#.to_string()),
+            quick_access_key: Some("a".to_string()),
+            created_at: chrono::Utc::now(),
+            updated_at: chrono::Utc::now(),
+            pinned_position: None,
+            pinned_at: None,
+            status: PromptStatus::Draft,
+            visibility: PromptVisibility::Shared,
+            workspace_id: None,
+            is_secret: false,
+            is_sensitive: false,
+            paste_confirmation: Default::default(),
+        };
+
+        let input = RemotePromptInput::from_prompt(&prompt, "wsp_test").unwrap();
+        assert_eq!(input.title.as_ref().unwrap().chars().count(), REMOTE_MAX_TITLE_LENGTH);
+        assert_eq!(input.tag_ids, vec!["code-review", "ok_tag"]);
+        assert_eq!(input.quick_access_key, None);
+        assert_eq!(input.status, PromptStatus::Draft);
+        assert_eq!(input.visibility, PromptVisibility::Shared);
+
+        let too_long = Prompt {
+            content: "x".repeat(REMOTE_MAX_CONTENT_LENGTH + 1),
+            ..prompt
+        };
+        assert!(RemotePromptInput::from_prompt(&too_long, "wsp_test").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_http_client_against_mock_server() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let server = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buffer = vec![0u8; 4096];
+            let read = socket.read(&mut buffer).await.unwrap();
+            let request = String::from_utf8_lossy(&buffer[..read]).to_string();
+
+            let body = r#"{"prompts":[{"id":"prm_1","title":null,"content":"hello","tagIds":["a"],"workspaceId":"wsp_demo","updatedAt":"2025-01-01T00:00:00.000Z"}],"total":1}"#;
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            socket.write_all(response.as_bytes()).await.unwrap();
+            request
+        });
+
+        let api = HttpPromptApi::new(&format!("http://{address}"), "test-key").unwrap();
+        let prompts = api
+            .list_prompts("wsp_demo", Some("2025-01-01T00:00:00Z".parse().unwrap()))
+            .await.unwrap();
+        let request = server.await.unwrap();
+
+        assert!(request.starts_with(
+            "GET /api/v1/prompts?workspaceId=wsp_demo&updatedSince=2025-01-01T00%3A00%3A00.000Z"
+        ));
+        assert!(request.to_lowercase().contains("authorization: bearer test-key"));
+        assert_eq!(prompts.len(), 1);
+        assert_eq!(prompts[0].content, "hello");
+        assert_eq!(prompts[0].tag_ids, vec!["a"]);
+    }
+}
@@ -1,31 +1,224 @@
 /*!
  * アプリケーションの状態管理
  * システムトレイとの連携、初回起動時の動作などを管理
+ * 
+ * 起動・オンボーディングの状態（初回起動の処理済み、トレイの案内の表示済み、前回起動したバージョン）は
+ * app_state.jsonに保存し、再起動しても初回起動として扱わないようにする。
+ * バージョンが変わった場合は`first-launch`の代わりに`app-updated`を通知する
+ * 
+ * 状態ファイルがなく、データベースがロック中でプロンプトの有無を確認できない場合は、
+ * 空のデータベースとはみなさずにロック解除まで判定を保留する
  */
 
-use tauri::{AppHandle, Emitter, Manager};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
 use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::environment::Environment;
+
+/// 起動状態ファイル名（アプリデータディレクトリに保存）
+const STATE_FILENAME: &str = "app_state.json";
+
+/// 実行中のアプリのバージョン
+const CURRENT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// 今回の起動の種類（`handle_first_launch`で判定）
+static LAUNCH_KIND: Mutex<Option<LaunchKind>> = Mutex::new(None);
+
+/// 起動の種類の判定をロック解除まで保留しているか
+static LAUNCH_DEFERRED: AtomicBool = AtomicBool::new(false);
+
+/// 状態ファイルの読み書きを直列化するロック
+static STATE_LOCK: Mutex<()> = Mutex::new(());
+
+/// 起動・オンボーディングの状態（永続化）
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OnboardingState {
+    /// 初回起動の処理が済んだかどうか
+    pub first_run_completed: bool,
+    /// トレイに常駐することの案内を表示したかどうか
+    pub tray_tip_shown: bool,
+    /// 前回起動したアプリのバージョン
+    pub last_run_version: Option<String>,
+    /// スターターライブラリを導入したかどうか（削除された後も再度導入しない）
+    pub starter_library_installed: bool,
+}
+
+impl OnboardingState {
+    fn state_path() -> Result<PathBuf, String> {
+        let data_dir = Environment::current().data_dir().map_err(|e| e.to_string())?;
+        Ok(data_dir.join(STATE_FILENAME))
+    }
+
+    /// 状態ファイルから読み込み（存在しない場合は`None`）
+    fn load() -> Result<Option<Self>, String> {
+        let path = Self::state_path()?;
+        if !path.exists() {
+            return Ok(None);
+        }
+        let content = std::fs::read_to_string(&path).map_err(|e| e.to_string())?;
+        serde_json::from_str(&content)
+            .map(Some)
+            .map_err(|e| format!("Invalid app state file: {e}"))
+    }
 
-/// アプリケーションの初回起動フラグ
-static FIRST_LAUNCH: AtomicBool = AtomicBool::new(true);
+    /// 状態ファイルへ保存
+    fn save(&self) -> Result<(), String> {
+        let path = Self::state_path()?;
+        let content = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        // 書き込み途中で終了しても壊れないよう、一時ファイルに書いてから置き換える
+        let tmp_path = path.with_extension("json.tmp");
+        std::fs::write(&tmp_path, content).map_err(|e| format!("Failed to save app state: {e}"))?;
+        std::fs::rename(&tmp_path, &path).map_err(|e| format!("Failed to save app state: {e}"))
+    }
+}
 
-/// アプリケーションの初回起動時の処理
+/// 起動の種類
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum LaunchKind {
+    /// 初回起動
+    FirstRun,
+    /// 前回と異なるバージョンでの起動（前回のバージョンが不明な場合は`None`）
+    Updated { previous: Option<String> },
+    /// 通常の起動
+    Normal,
+}
+
+/// 保存済みの状態から起動の種類を判定
+///
+/// 状態ファイルがなくてもプロンプトがある場合は、状態を保存する前のバージョンからの更新とみなす。
+/// has_prompts: プロンプトの有無（確認できなかった場合は`None`）。判定できない場合は`None`を返す
+fn launch_kind(
+    state: Option<&OnboardingState>,
+    has_prompts: Option<bool>,
+    current_version: &str,
+) -> Option<LaunchKind> {
+    let kind = match state {
+        None => match has_prompts? {
+            true => LaunchKind::Updated { previous: None },
+            false => LaunchKind::FirstRun,
+        },
+        Some(state) if !state.first_run_completed => LaunchKind::FirstRun,
+        Some(state) if state.last_run_version.as_deref() != Some(current_version) => LaunchKind::Updated {
+            previous: state.last_run_version.clone(),
+        },
+        Some(_) => LaunchKind::Normal,
+    };
+    Some(kind)
+}
+
+/**
+ * 起動時に初回起動・更新を判定して処理する
+ * 
+ * - 初回起動: スターターライブラリを導入し、`first-launch`を通知してメインウィンドウを表示
+ * - 更新後の初回起動: `app-updated`を通知（ウィンドウは表示しない）
+ * 
+ * データベースの初期化後に呼ぶ（状態ファイルがない場合にプロンプトの有無を確認するため）。
+ * ロック中で確認できない場合は`resume_deferred_launch`でロック解除後に判定する
+ */
 pub fn handle_first_launch(app_handle: &AppHandle) {
-    if FIRST_LAUNCH.swap(false, Ordering::SeqCst) {
-        println!("First launch detected");
-        
-        // 初回起動時にシステムトレイについての通知を送信
-        let _ = app_handle.emit("first-launch", ());
-        
-        // メインウィンドウを表示（初回はウィンドウを表示）
-        if let Some(window) = app_handle.get_webview_window("main") {
-            let _ = window.show();
-            let _ = window.set_focus();
-            println!("Main window shown on first launch");
+    let _guard = STATE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let saved = OnboardingState::load().unwrap_or_else(|e| {
+        // 壊れた状態ファイルで初回起動を繰り返さないよう、初回起動は済んだものとして扱う
+        eprintln!("Failed to load app state: {e}");
+        Some(OnboardingState { first_run_completed: true, ..OnboardingState::default() })
+    });
+    let has_prompts = match saved {
+        Some(_) => None,
+        None => tauri::async_runtime::block_on(crate::database::has_prompts())
+            .map_err(|e| eprintln!("Failed to check existing prompts: {e}"))
+            .ok(),
+    };
+    let Some(kind) = launch_kind(saved.as_ref(), has_prompts, CURRENT_VERSION) else {
+        println!("Deferring launch detection until the database is unlocked");
+        LAUNCH_DEFERRED.store(true, Ordering::SeqCst);
+        return;
+    };
+    if let Ok(mut launch) = LAUNCH_KIND.lock() {
+        *launch = Some(kind.clone());
+    }
+
+    let mut state = saved.unwrap_or_default();
+    if kind == LaunchKind::FirstRun && !state.starter_library_installed {
+        match tauri::async_runtime::block_on(crate::starter::install_starter_library()) {
+            Ok(count) => {
+                println!("Installed {count} starter prompts");
+                state.starter_library_installed = true;
+                crate::tray::refresh_tray(app_handle);
+            }
+            Err(e) => eprintln!("{e}. Retrying on next launch"),
+        }
+    }
+    // スターターライブラリを導入できなかった場合は、次回の起動も初回起動として再試行する
+    if kind != LaunchKind::FirstRun || state.starter_library_installed {
+        state.first_run_completed = true;
+    }
+    state.last_run_version = Some(CURRENT_VERSION.to_string());
+    if let Err(e) = state.save() {
+        eprintln!("Failed to save app state: {e}");
+    }
+
+    match kind {
+        LaunchKind::FirstRun => {
+            println!("First launch detected");
+            
+            // 初回起動時にシステムトレイについての通知を送信
+            let _ = app_handle.emit("first-launch", ());
+            
+            // メインウィンドウを表示（初回はウィンドウを表示）
+            if let Some(window) = app_handle.get_webview_window("main") {
+                let _ = window.show();
+                let _ = window.set_focus();
+                println!("Main window shown on first launch");
+            }
+        }
+        LaunchKind::Updated { previous } => {
+            println!("App updated from {} to {CURRENT_VERSION}", previous.as_deref().unwrap_or("an unknown version"));
+            let _ = app_handle.emit("app-updated", serde_json::json!({
+                "previous_version": previous,
+                "current_version": CURRENT_VERSION
+            }));
         }
+        LaunchKind::Normal => {}
     }
 }
 
+/// 保留していた起動の種類の判定を実行（データベースのロック解除後に呼ぶ）
+pub fn resume_deferred_launch(app_handle: &AppHandle) {
+    if LAUNCH_DEFERRED.swap(false, Ordering::SeqCst) {
+        let app_handle = app_handle.clone();
+        // スターターライブラリの導入でblock_onを使うため、非同期ランタイムの外で実行
+        tauri::async_runtime::spawn_blocking(move || handle_first_launch(&app_handle));
+    }
+}
+
+/**
+ * トレイに常駐することの案内を表示するかどうか（まだ表示していない場合のみtrue）
+ * 
+ * trueを返した時点で表示済みとして保存する
+ */
+pub fn take_tray_tip() -> bool {
+    let _guard = STATE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut state = match OnboardingState::load() {
+        Ok(state) => state.unwrap_or_default(),
+        Err(e) => {
+            eprintln!("Failed to load app state: {e}");
+            return false;
+        }
+    };
+    if state.tray_tip_shown {
+        return false;
+    }
+    state.tray_tip_shown = true;
+    if let Err(e) = state.save() {
+        eprintln!("Failed to save app state: {e}");
+    }
+    true
+}
+
 /// アプリケーションがフォーカスを得た時の処理
 #[tauri::command]
 pub fn on_app_focus(app_handle: AppHandle) -> Result<(), String> {
@@ -49,11 +242,17 @@ pub fn get_app_state(app_handle: AppHandle) -> Result<AppState, String> {
     } else {
         false
     };
+    let launch = LAUNCH_KIND.lock().ok().and_then(|launch| launch.clone());
 
     Ok(AppState {
         main_window_visible,
         system_tray_enabled: true, // システムトレイは常に有効
-        first_launch: FIRST_LAUNCH.load(Ordering::SeqCst),
+        first_launch: matches!(launch, Some(LaunchKind::FirstRun)),
+        app_updated: matches!(launch, Some(LaunchKind::Updated { .. })),
+        previous_version: match launch {
+            Some(LaunchKind::Updated { previous }) => previous,
+            _ => None,
+        },
     })
 }
 
@@ -77,6 +276,10 @@ pub struct AppState {
     pub main_window_visible: bool,
     pub system_tray_enabled: bool,
     pub first_launch: bool,
+    /// 今回の起動が更新後の初回起動かどうか
+    pub app_updated: bool,
+    /// 更新前のバージョン（不明な場合は`None`）
+    pub previous_version: Option<String>,
 }
 
 #[derive(serde::Serialize, serde::Deserialize)]
@@ -84,4 +287,40 @@ pub struct TrayHelpMessage {
     pub title: String,
     pub description: String,
     pub actions: Vec<String>,
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_launch_kind() {
+        assert_eq!(launch_kind(None, Some(false), "1.2.0"), Some(LaunchKind::FirstRun));
+        // 状態を保存する前のバージョンから更新した場合
+        assert_eq!(launch_kind(None, Some(true), "1.2.0"), Some(LaunchKind::Updated { previous: None }));
+        // ロック中でプロンプトの有無を確認できない場合は空とみなさない
+        assert_eq!(launch_kind(None, None, "1.2.0"), None);
+
+        let state = OnboardingState {
+            first_run_completed: true,
+            tray_tip_shown: false,
+            last_run_version: Some("1.1.0".to_string()),
+            starter_library_installed: true,
+        };
+        assert_eq!(
+            launch_kind(Some(&state), None, "1.2.0"),
+            Some(LaunchKind::Updated { previous: Some("1.1.0".to_string()) })
+        );
+        assert_eq!(launch_kind(Some(&state), None, "1.1.0"), Some(LaunchKind::Normal));
+
+        let unfinished = OnboardingState::default();
+        assert_eq!(launch_kind(Some(&unfinished), None, "1.2.0"), Some(LaunchKind::FirstRun));
+    }
+
+    #[test]
+    fn test_onboarding_state_defaults_missing_fields() {
+        let state: OnboardingState = serde_json::from_str(r#"{"first_run_completed": true}"#).unwrap();
+        assert!(state.first_run_completed);
+        assert!(!state.tray_tip_shown);
+        assert_eq!(state.last_run_version, None);
+    }
+}
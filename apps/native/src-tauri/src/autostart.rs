@@ -0,0 +1,305 @@
+/*!
+ * ログイン時の自動起動
+ *
+ * ログイン時にトレイに常駐した状態（メインウィンドウを表示しない`--hidden`付き）で起動する
+ *
+ * - Linux: XDGの自動起動エントリ（`~/.config/autostart/<識別子>.desktop`）
+ * - macOS: LaunchAgent（`~/Library/LaunchAgents/<識別子>.plist`）
+ * - Windows: レジストリの`HKCU\Software\Microsoft\Windows\CurrentVersion\Run`
+ *
+ * 有効かどうかはOS側の登録の有無で判断する（アプリ側には保存しない）。
+ * そのため設定（settingsモジュールの`AppSettings`）には含めず、専用のコマンドで変更する。
+ * アプリの場所が変わっても起動できるよう、有効な場合は起動時に登録内容を書き直す
+ */
+use serde::Serialize;
+use std::path::PathBuf;
+
+use crate::environment::Environment;
+
+/// メインウィンドウを表示せずに起動する引数
+pub const HIDDEN_FLAG: &str = "--hidden";
+
+/// 自動起動の状態
+#[derive(Debug, Clone, Serialize)]
+pub struct AutostartStatus {
+    pub enabled: bool,
+    /// 登録先（ファイルのパスまたはレジストリキー）
+    pub location: String,
+}
+
+/// `--hidden`付きで起動したかどうか
+pub fn started_hidden() -> bool {
+    std::env::args().skip(1).any(|arg| arg == HIDDEN_FLAG)
+}
+
+/// 自動起動で実行するファイル（AppImageの場合はAppImage自体）
+fn launch_executable() -> Result<PathBuf, String> {
+    if cfg!(target_os = "linux") {
+        if let Some(appimage) = std::env::var_os("APPIMAGE") {
+            return Ok(PathBuf::from(appimage));
+        }
+    }
+    std::env::current_exe().map_err(|e| format!("Failed to get the app location: {e}"))
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use super::*;
+
+    /// 自動起動エントリのパス（`XDG_CONFIG_HOME`が未設定の場合は`~/.config`）
+    pub(super) fn location() -> Result<String, String> {
+        let config_dir = match std::env::var_os("XDG_CONFIG_HOME").filter(|dir| !dir.is_empty()) {
+            Some(dir) => PathBuf::from(dir),
+            None => std::env::var_os("HOME")
+                .map(|home| PathBuf::from(home).join(".config"))
+                .ok_or("Cannot find HOME directory on Linux")?,
+        };
+        let file_name = format!("{}.desktop", Environment::current().app_identifier());
+        Ok(config_dir.join("autostart").join(file_name).to_string_lossy().into_owned())
+    }
+
+    pub(super) fn is_enabled() -> Result<bool, String> {
+        Ok(std::path::Path::new(&location()?).exists())
+    }
+
+    pub(super) fn enable(executable: &std::path::Path) -> Result<(), String> {
+        let path = PathBuf::from(location()?);
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create autostart directory: {e}"))?;
+        }
+        let entry = desktop_entry(Environment::current().app_name(), &executable.to_string_lossy());
+        std::fs::write(&path, entry).map_err(|e| format!("Failed to write autostart entry: {e}"))
+    }
+
+    pub(super) fn disable() -> Result<(), String> {
+        match std::fs::remove_file(location()?) {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(format!("Failed to remove autostart entry: {e}")),
+        }
+    }
+
+    /// XDG自動起動エントリの内容
+    pub(super) fn desktop_entry(name: &str, executable: &str) -> String {
+        format!(
+            "[Desktop Entry]\nType=Application\nName={name}\nExec={} {HIDDEN_FLAG}\nTerminal=false\nX-GNOME-Autostart-enabled=true\n",
+            quote_exec_arg(executable)
+        )
+    }
+
+    /// `Exec`キーの引数として引用（Desktop Entry仕様の予約文字をエスケープ）
+    ///
+    /// 値は引数として解釈される前に文字列としてエスケープが解除されるため、引用後に`\`を二重にする
+    fn quote_exec_arg(arg: &str) -> String {
+        let mut quoted = String::from("\"");
+        for c in arg.chars() {
+            match c {
+                '"' | '`' | '$' | '\\' => {
+                    quoted.push('\\');
+                    quoted.push(c);
+                }
+                '%' => quoted.push_str("%%"),
+                _ => quoted.push(c),
+            }
+        }
+        quoted.push('"');
+        quoted.replace('\\', "\\\\")
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use super::*;
+
+    /// LaunchAgentのパス
+    pub(super) fn location() -> Result<String, String> {
+        let home = std::env::var_os("HOME").ok_or("Cannot find HOME directory on macOS")?;
+        let file_name = format!("{}.plist", Environment::current().app_identifier());
+        Ok(PathBuf::from(home)
+            .join("Library/LaunchAgents")
+            .join(file_name)
+            .to_string_lossy()
+            .into_owned())
+    }
+
+    pub(super) fn is_enabled() -> Result<bool, String> {
+        Ok(std::path::Path::new(&location()?).exists())
+    }
+
+    pub(super) fn enable(executable: &std::path::Path) -> Result<(), String> {
+        let path = PathBuf::from(location()?);
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create LaunchAgents directory: {e}"))?;
+        }
+        let plist = launch_agent(Environment::current().app_identifier(), &executable.to_string_lossy());
+        std::fs::write(&path, plist).map_err(|e| format!("Failed to write launch agent: {e}"))
+    }
+
+    pub(super) fn disable() -> Result<(), String> {
+        match std::fs::remove_file(location()?) {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(format!("Failed to remove launch agent: {e}")),
+        }
+    }
+
+    /// LaunchAgentのplist
+    fn launch_agent(label: &str, executable: &str) -> String {
+        let escape = |value: &str| value.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;");
+        format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+    <key>Label</key>
+    <string>{}</string>
+    <key>ProgramArguments</key>
+    <array>
+        <string>{}</string>
+        <string>{HIDDEN_FLAG}</string>
+    </array>
+    <key>RunAtLoad</key>
+    <true/>
+</dict>
+</plist>
+"#,
+            escape(label),
+            escape(executable)
+        )
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use super::*;
+    use std::os::windows::process::CommandExt;
+    use std::process::Command;
+
+    /// ログイン時に実行するプログラムのレジストリキー
+    const RUN_KEY: &str = r"HKCU\Software\Microsoft\Windows\CurrentVersion\Run";
+
+    /// コンソールウィンドウを表示しない
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+
+    pub(super) fn location() -> Result<String, String> {
+        Ok(format!(r"{RUN_KEY}\{}", Environment::current().app_name()))
+    }
+
+    fn reg(args: &[&str]) -> Result<bool, String> {
+        let status = Command::new("reg")
+            .args(args)
+            .creation_flags(CREATE_NO_WINDOW)
+            .output()
+            .map_err(|e| format!("Failed to run reg: {e}"))?
+            .status;
+        Ok(status.success())
+    }
+
+    pub(super) fn is_enabled() -> Result<bool, String> {
+        reg(&["query", RUN_KEY, "/v", Environment::current().app_name()])
+    }
+
+    pub(super) fn enable(executable: &std::path::Path) -> Result<(), String> {
+        let command = format!("\"{}\" {HIDDEN_FLAG}", executable.display());
+        let name = Environment::current().app_name();
+        if reg(&["add", RUN_KEY, "/v", name, "/t", "REG_SZ", "/d", &command, "/f"])? {
+            Ok(())
+        } else {
+            Err("Failed to add the autostart registry value".to_string())
+        }
+    }
+
+    pub(super) fn disable() -> Result<(), String> {
+        if !is_enabled()? {
+            return Ok(());
+        }
+        if reg(&["delete", RUN_KEY, "/v", Environment::current().app_name(), "/f"])? {
+            Ok(())
+        } else {
+            Err("Failed to remove the autostart registry value".to_string())
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+mod platform {
+    const UNSUPPORTED: &str = "Launch at login is not supported on this platform";
+
+    pub(super) fn location() -> Result<String, String> {
+        Err(UNSUPPORTED.to_string())
+    }
+
+    pub(super) fn is_enabled() -> Result<bool, String> {
+        Ok(false)
+    }
+
+    pub(super) fn enable(_executable: &std::path::Path) -> Result<(), String> {
+        Err(UNSUPPORTED.to_string())
+    }
+
+    pub(super) fn disable() -> Result<(), String> {
+        Ok(())
+    }
+}
+
+fn status() -> Result<AutostartStatus, String> {
+    Ok(AutostartStatus {
+        enabled: platform::is_enabled()?,
+        location: platform::location()?,
+    })
+}
+
+/// 有効な場合は登録内容を現在のアプリの場所で書き直す（起動時に呼ぶ）
+pub fn refresh_from_config() {
+    if !platform::is_enabled().unwrap_or(false) {
+        return;
+    }
+    if let Err(e) = launch_executable().and_then(|executable| platform::enable(&executable)) {
+        eprintln!("Failed to refresh autostart entry: {e}");
+    }
+}
+
+/**
+ * ログイン時の自動起動を有効化（`--hidden`付きで起動する）
+ *
+ * 設定の変更とは別のコマンドのため、`update_settings`では変更できず`settings-changed`も通知しない
+ */
+#[tauri::command]
+pub async fn enable_autostart() -> Result<AutostartStatus, String> {
+    platform::enable(&launch_executable()?)?;
+    status()
+}
+
+/**
+ * ログイン時の自動起動を無効化
+ */
+#[tauri::command]
+pub async fn disable_autostart() -> Result<AutostartStatus, String> {
+    platform::disable()?;
+    status()
+}
+
+/**
+ * ログイン時の自動起動の状態を取得
+ */
+#[tauri::command]
+pub async fn get_autostart_status() -> Result<AutostartStatus, String> {
+    status()
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_desktop_entry_quotes_exec() {
+        let entry = platform::desktop_entry("PromPalette", "/opt/Prom Palette/$app\"100%");
+        assert!(entry.starts_with("[Desktop Entry]\n"));
+        assert!(entry.contains("Name=PromPalette\n"));
+        // 引用のエスケープ（`\$`、`\"`）の`\`は、文字列のエスケープとして二重にする
+        assert!(entry.contains(r#"Exec="/opt/Prom Palette/\\$app\\"100%%" --hidden"#));
+
+        let entry = platform::desktop_entry("PromPalette", r"/opt/a\b");
+        assert!(entry.contains(r#"Exec="/opt/a\\\\b" --hidden"#));
+    }
+}
@@ -0,0 +1,252 @@
+/*!
+ * 選択範囲・クリップボードの取り込み
+ *
+ * 取り込みホットキー（既定: Ctrl+Alt+Shift+C、macOSはCmd+Option+Shift+C）で、
+ * アクティブアプリケーションで選択中のテキストを新しいプロンプトとして保存する
+ *
+ * 1. ホットキーのキーを離すまで少し待ってから、コピーのキーストロークを送る（pasteモジュールの送信手段を使用）
+ * 2. クリップボードに入ったテキストを読み取り、元のクリップボードに戻す
+ * 3. 本文の最初の行からタイトルを作り、`inbox`タグを付けて保存
+ * 4. `prompt-captured`を通知（フロントエンドで編集を促す）。失敗した場合は`capture-failed`
+ *
+ * トレイメニューの「New Prompt from Clipboard」（`create_prompt_from_clipboard`コマンド）は
+ * 現在のクリップボードのテキストを同じ形で保存する（同じ本文のプロンプトが既にある場合は保存しない）。
+ * 通知するイベントの`source`は`selection`または`clipboard`
+ */
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter};
+use tauri_plugin_clipboard_manager::ClipboardExt;
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, ShortcutState};
+
+use crate::commands::MAX_TITLE_LENGTH;
+use crate::config;
+use crate::database::{CreatePromptRequest, Prompt};
+
+/// 取り込んだプロンプトに付けるタグ
+pub const INBOX_TAG: &str = "inbox";
+
+/// 登録状況の所有者ID
+pub const CAPTURE_OWNER: &str = "capture";
+
+/// タイトルの最大文字数（本文の最初の行から作る。`create_prompt`コマンドのバイト数の制限も守る）
+const MAX_TITLE_CHARS: usize = 60;
+
+/// 本文の最大サイズ（`create_prompt`コマンドと同じ）
+const MAX_CONTENT_LEN: usize = 100_000;
+
+/// 選択範囲の取り込み（イベントの`source`）
+const SOURCE_SELECTION: &str = "selection";
+
+/// クリップボードの取り込み（イベントの`source`）
+const SOURCE_CLIPBOARD: &str = "clipboard";
+
+/// 取り込みの設定（アプリケーション設定の`capture`セクション）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CaptureConfig {
+    /// 取り込みホットキーを登録するかどうか
+    pub enabled: bool,
+    pub shortcut: String,
+}
+
+impl Default for CaptureConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            shortcut: config::shortcuts::CAPTURE_SELECTION.to_string(),
+        }
+    }
+}
+
+impl CaptureConfig {
+    pub(crate) fn validate(&self) -> Result<(), String> {
+        crate::shortcuts::validate_global_shortcut(&self.shortcut)
+    }
+
+    /// 登録するショートカット（無効の場合は`None`）
+    pub(crate) fn binding(&self) -> Option<&str> {
+        self.enabled.then_some(self.shortcut.as_str())
+    }
+}
+
+/// 現在登録中の取り込みホットキー
+static ACTIVE_CAPTURE: Mutex<Option<String>> = Mutex::new(None);
+
+/// 取り込み処理中フラグ（キーリピートでの二重取り込み防止）
+static IS_CAPTURING: AtomicBool = AtomicBool::new(false);
+
+/// 設定に従って取り込みホットキーを登録（無効の場合は解除のみ）
+pub(crate) fn apply_capture_shortcut(app_handle: &AppHandle, config: &CaptureConfig) -> Result<(), String> {
+    crate::shortcuts::replace_shortcut(app_handle, &ACTIVE_CAPTURE, config.binding(), register_capture_shortcut)
+}
+
+/// 取り込みホットキーを解除（ホットキーの一時停止用）
+pub(crate) fn unregister_capture_shortcut(app_handle: &AppHandle) -> Result<(), String> {
+    crate::shortcuts::replace_shortcut(app_handle, &ACTIVE_CAPTURE, None, register_capture_shortcut)
+}
+
+fn register_capture_shortcut(app_handle: &AppHandle, shortcut: &str) -> Result<(), String> {
+    let result = app_handle
+        .global_shortcut()
+        .on_shortcut(shortcut, |app_handle, _shortcut, event| {
+            if event.state == ShortcutState::Released {
+                return;
+            }
+            // パレットのペースト中はキー送信が重なるため取り込まない
+            if crate::global_hotkey::is_pasting() || IS_CAPTURING.swap(true, Ordering::SeqCst) {
+                return;
+            }
+            let app_handle = app_handle.clone();
+            tauri::async_runtime::spawn(async move {
+                let result = capture_selection().await;
+                IS_CAPTURING.store(false, Ordering::SeqCst);
+                emit_capture_result(&app_handle, SOURCE_SELECTION, &result);
+            });
+        })
+        .map_err(|e| format!("取り込みのショートカットキー '{shortcut}' の登録に失敗しました: {e}"));
+    crate::shortcuts::record_registration(CAPTURE_OWNER, shortcut, &result);
+    result
+}
+
+/// 起動時に取り込みホットキーを登録
+pub async fn register_capture_hotkey(app_handle: AppHandle) -> Result<(), String> {
+    if crate::shortcuts::is_paused() {
+        return Ok(());
+    }
+    let config = crate::settings::load_or_default().capture;
+    apply_capture_shortcut(&app_handle, &config)
+}
+
+/// 選択範囲をコピーして新しいプロンプトとして保存
+async fn capture_selection() -> Result<Prompt, String> {
+    // 修飾キーを押したままだとコピーのキーと組み合わさる
+    tokio::time::sleep(crate::paste::KEY_RELEASE_DELAY).await;
+    let copied = crate::clipboard::capture_copied_text(|| crate::paste::send_copy_keystroke().map(|_| ())).await?;
+    let text = copied.ok_or_else(|| "No text is selected".to_string())?;
+    let request = capture_request(text)?;
+    crate::database::create_prompt(request)
+        .await
+        .map_err(|e| format!("Failed to save captured prompt: {e}"))
+}
+
+/// クリップボードのテキストを新しいプロンプトとして保存
+async fn capture_clipboard(app_handle: &AppHandle) -> Result<Prompt, String> {
+    let text = app_handle
+        .clipboard()
+        .read_text()
+        .map_err(|_| "Clipboard does not contain text".to_string())?;
+    let request = capture_request(text)?;
+    let existing = crate::database::find_prompt_by_content(&request.content)
+        .await
+        .map_err(|e| format!("Failed to check existing prompts: {e}"))?;
+    if existing.is_some() {
+        return Err("A prompt with the same content already exists".to_string());
+    }
+    crate::database::create_prompt(request)
+        .await
+        .map_err(|e| format!("Failed to save clipboard prompt: {e}"))
+}
+
+/// 取り込みの結果を通知
+fn emit_capture_result(app_handle: &AppHandle, source: &str, result: &Result<Prompt, String>) {
+    let _ = match result {
+        Ok(prompt) => app_handle.emit("prompt-captured", serde_json::json!({ "prompt": prompt, "source": source })),
+        Err(e) => app_handle.emit("capture-failed", serde_json::json!({ "error": e, "source": source })),
+    };
+}
+
+/// クリップボードのテキストから新しいプロンプトを作成（`prompt-captured`または`capture-failed`も通知）
+#[tauri::command]
+pub async fn create_prompt_from_clipboard(app_handle: AppHandle) -> Result<Prompt, String> {
+    let result = capture_clipboard(&app_handle).await;
+    emit_capture_result(&app_handle, SOURCE_CLIPBOARD, &result);
+    result
+}
+
+/// 取り込んだテキストから作成リクエストを作る（`create_prompt`コマンドと同じ制限）
+fn capture_request(content: String) -> Result<CreatePromptRequest, String> {
+    if content.trim().is_empty() {
+        return Err("Content cannot be empty".to_string());
+    }
+    if content.len() > MAX_CONTENT_LEN {
+        return Err("Content too long (max 100,000 characters)".to_string());
+    }
+    Ok(CreatePromptRequest {
+        title: Some(capture_title(&content)),
+        content,
+        tags: Some(vec![INBOX_TAG.to_string()]),
+        quick_access_key: None,
+        status: None,
+        visibility: None,
+        workspace_id: None,
+        is_sensitive: None,
+        paste_confirmation: None,
+    })
+}
+
+/// 本文の最初の空でない行からタイトルを作る（長い場合は省略）
+fn capture_title(content: &str) -> String {
+    const ELLIPSIS: char = '…';
+    let line = content
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .unwrap_or_default();
+    if line.chars().count() <= MAX_TITLE_CHARS && line.len() <= MAX_TITLE_LENGTH {
+        return line.to_string();
+    }
+    // 省略記号を含めて文字数・バイト数の制限に収まるところまで（文字の途中では切らない）
+    let budget = MAX_TITLE_LENGTH - ELLIPSIS.len_utf8();
+    let mut title = String::new();
+    for c in line.chars().take(MAX_TITLE_CHARS - 1) {
+        if title.len() + c.len_utf8() > budget {
+            break;
+        }
+        title.push(c);
+    }
+    title.push(ELLIPSIS);
+    title
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_capture_title() {
+        assert_eq!(capture_title("\n\n  Summarize this  \nsecond line"), "Summarize this");
+        let long = "あ".repeat(100);
+        let title = capture_title(&long);
+        assert_eq!(title.chars().count(), MAX_TITLE_CHARS);
+        assert!(title.len() <= MAX_TITLE_LENGTH);
+        assert!(title.ends_with('…'));
+
+        // 4バイト文字はバイト数の制限で省略する
+        let title = capture_title(&"😀".repeat(60));
+        assert!(title.len() <= MAX_TITLE_LENGTH);
+        assert!(title.ends_with('…'));
+    }
+
+    #[test]
+    fn test_capture_request() {
+        let request = capture_request("Review this diff\n```\n+1\n```".to_string()).unwrap();
+        assert_eq!(request.title.as_deref(), Some("Review this diff"));
+        assert_eq!(request.tags, Some(vec![INBOX_TAG.to_string()]));
+
+        assert!(capture_request(" \n\t".to_string()).is_err());
+        assert!(capture_request("x".repeat(MAX_CONTENT_LEN + 1)).is_err());
+    }
+
+    #[test]
+    fn test_capture_config() {
+        let config = CaptureConfig::default();
+        assert!(config.validate().is_ok());
+        assert_eq!(config.binding(), Some(config::shortcuts::CAPTURE_SELECTION));
+
+        let disabled = CaptureConfig { enabled: false, ..CaptureConfig::default() };
+        assert_eq!(disabled.binding(), None);
+        assert!(CaptureConfig { shortcut: "C".to_string(), ..CaptureConfig::default() }.validate().is_err());
+    }
+}
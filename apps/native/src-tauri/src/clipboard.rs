@@ -0,0 +1,444 @@
+/*!
+ * プロンプトのクリップボード書き込み
+ *
+ * - パレットからのペースト: 書き込み前のクリップボード（テキスト・HTML・画像・ファイル）を保存し、
+ *   ペースト後に一定時間（既定500ミリ秒）で元に戻す
+ * - 機密（またはシークレット）プロンプト: ペースト・コピー後、一定時間（既定30秒）で
+ *   クリップボードを元の内容に戻すか消去する
+ *
+ * - 戻す・消去するのはクリップボードの内容がまだこちらで書き込んだものである場合のみ
+ *   （その間にユーザーが別の内容をコピーしていれば何もしない）
+ * - 書き込んだ内容は比較用のハッシュのみ保持する
+ * - 機密プロンプトはクリップボード履歴の除外ヒントを付けて書き込む
+ *   （Linux: `x-kde-passwordManagerHint`、macOS: `org.nspasteboard.ConcealedType`、Windows: 履歴・クラウド同期の除外）
+ * - 選択範囲の取り込み: コピーのキーストロークでクリップボードに入ったテキストを読み取り、
+ *   すぐに元の内容に戻す
+ */
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+use zeroize::Zeroizing;
+
+/// 機密プロンプトを消去するまでの時間（秒）
+const DEFAULT_CLEAR_DELAY_SECS: u64 = 30;
+const MIN_CLEAR_DELAY_SECS: u64 = 5;
+const MAX_CLEAR_DELAY_SECS: u64 = 600;
+
+/// ペースト後に元の内容に戻すまでの時間（ミリ秒）
+const DEFAULT_RESTORE_DELAY_MS: u64 = 500;
+const MIN_RESTORE_DELAY_MS: u64 = 100;
+const MAX_RESTORE_DELAY_MS: u64 = 10_000;
+
+/// 選択範囲の取り込みでクリップボードの変化を確認する間隔と待ち時間の上限
+const COPY_POLL_INTERVAL: Duration = Duration::from_millis(50);
+const COPY_TIMEOUT: Duration = Duration::from_millis(1000);
+
+/// クリップボード設定（アプリケーション設定の`clipboard`セクション）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClipboardConfig {
+    /// 機密プロンプトをクリップボードから消去するまでの時間（秒）
+    #[serde(default = "default_clear_delay_secs")]
+    pub sensitive_clear_delay_secs: u64,
+    /// パレットからのペースト後に元のクリップボードに戻す
+    #[serde(default = "default_restore_after_paste")]
+    pub restore_after_paste: bool,
+    /// ペースト後に元に戻すまでの時間（ミリ秒）
+    #[serde(default = "default_restore_delay_ms")]
+    pub restore_delay_ms: u64,
+}
+
+fn default_clear_delay_secs() -> u64 {
+    DEFAULT_CLEAR_DELAY_SECS
+}
+
+fn default_restore_after_paste() -> bool {
+    true
+}
+
+fn default_restore_delay_ms() -> u64 {
+    DEFAULT_RESTORE_DELAY_MS
+}
+
+impl Default for ClipboardConfig {
+    fn default() -> Self {
+        Self {
+            sensitive_clear_delay_secs: DEFAULT_CLEAR_DELAY_SECS,
+            restore_after_paste: true,
+            restore_delay_ms: DEFAULT_RESTORE_DELAY_MS,
+        }
+    }
+}
+
+impl ClipboardConfig {
+    /// アプリケーション設定から読み込み（失敗した場合はデフォルト）
+    fn load_or_default() -> Self {
+        crate::settings::load_or_default().clipboard
+    }
+
+    pub(crate) fn validate(&self) -> Result<(), String> {
+        if !(MIN_CLEAR_DELAY_SECS..=MAX_CLEAR_DELAY_SECS).contains(&self.sensitive_clear_delay_secs) {
+            return Err(format!(
+                "Clear delay must be between {MIN_CLEAR_DELAY_SECS} and {MAX_CLEAR_DELAY_SECS} seconds"
+            ));
+        }
+        if !(MIN_RESTORE_DELAY_MS..=MAX_RESTORE_DELAY_MS).contains(&self.restore_delay_ms) {
+            return Err(format!(
+                "Restore delay must be between {MIN_RESTORE_DELAY_MS} and {MAX_RESTORE_DELAY_MS} milliseconds"
+            ));
+        }
+        Ok(())
+    }
+
+    fn clear_delay(&self) -> Duration {
+        Duration::from_secs(
+            self.sensitive_clear_delay_secs
+                .clamp(MIN_CLEAR_DELAY_SECS, MAX_CLEAR_DELAY_SECS),
+        )
+    }
+
+    fn restore_delay(&self) -> Duration {
+        Duration::from_millis(self.restore_delay_ms.clamp(MIN_RESTORE_DELAY_MS, MAX_RESTORE_DELAY_MS))
+    }
+}
+
+/// 書き込み前のクリップボードの内容
+enum ClipboardSnapshot {
+    Empty,
+    Text(Zeroizing<String>),
+    Html {
+        html: Zeroizing<String>,
+        alt_text: Option<Zeroizing<String>>,
+    },
+    Image(arboard::ImageData<'static>),
+    Files(Vec<PathBuf>),
+}
+
+impl ClipboardSnapshot {
+    /// 現在のクリップボードの内容を保存（情報量の多い形式を優先）
+    fn capture(clipboard: &mut arboard::Clipboard) -> Self {
+        if let Ok(files) = clipboard.get().file_list() {
+            if !files.is_empty() {
+                return Self::Files(files);
+            }
+        }
+        let text = clipboard.get_text().ok().map(Zeroizing::new);
+        if let Ok(html) = clipboard.get().html() {
+            return Self::Html {
+                html: Zeroizing::new(html),
+                alt_text: text,
+            };
+        }
+        if let Ok(image) = clipboard.get_image() {
+            return Self::Image(image);
+        }
+        match text {
+            Some(text) => Self::Text(text),
+            None => Self::Empty,
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        match self {
+            Self::Empty => true,
+            Self::Text(text) => text.is_empty(),
+            _ => false,
+        }
+    }
+
+    /// 保存した内容をクリップボードに戻す
+    fn restore(&self, clipboard: &mut arboard::Clipboard) -> Result<(), arboard::Error> {
+        match self {
+            Self::Empty => clipboard.clear(),
+            Self::Text(text) => clipboard.set_text(text.as_str()),
+            Self::Html { html, alt_text } => clipboard.set_html(html.as_str(), alt_text.as_deref().map(String::as_str)),
+            Self::Image(image) => clipboard.set_image(image.clone()),
+            Self::Files(files) => clipboard.set().file_list(files),
+        }
+    }
+}
+
+/// 予約中の復元・消去処理
+struct PendingRestore {
+    /// 書き込んだ内容のハッシュ
+    written_hash: [u8; 32],
+    /// 書き込み前のクリップボードの内容（戻す対象）
+    previous: ClipboardSnapshot,
+    /// 機密プロンプトを書き込んだかどうか
+    sensitive: bool,
+    /// 復元を待つタスク（ペースト前は`None`）
+    task: Option<tauri::async_runtime::JoinHandle<()>>,
+}
+
+/// 予約中の復元・消去（新しく書き込むと置き換わる）
+static PENDING_RESTORE: Mutex<Option<PendingRestore>> = Mutex::new(None);
+
+/// プロンプトの書き込み・復元に使うクリップボード
+///
+/// X11・Waylandではクリップボードの所有者が内容を提供するため、アプリ終了まで保持する
+static CLIPBOARD: Mutex<Option<arboard::Clipboard>> = Mutex::new(None);
+
+fn with_clipboard<T>(f: impl FnOnce(&mut arboard::Clipboard) -> Result<T, arboard::Error>) -> Result<T, String> {
+    let mut clipboard = CLIPBOARD.lock().unwrap();
+    if clipboard.is_none() {
+        *clipboard = Some(arboard::Clipboard::new().map_err(|e| format!("Failed to open clipboard: {e}"))?);
+    }
+    f(clipboard.as_mut().unwrap()).map_err(|e| format!("Clipboard error: {e}"))
+}
+
+fn content_hash(text: &str) -> [u8; 32] {
+    Sha256::digest(text.as_bytes()).into()
+}
+
+/// 復元時の処理
+#[derive(Debug, PartialEq, Eq)]
+enum RestoreAction {
+    /// 書き込み前の内容に戻す
+    Restore,
+    /// 空にする
+    Clear,
+    /// ユーザーが別の内容をコピーしたため何もしない
+    Skip,
+}
+
+fn plan_restore(current: Option<&str>, written_hash: &[u8; 32], previous_is_empty: bool) -> RestoreAction {
+    match current {
+        Some(current) if content_hash(current) == *written_hash => {
+            if previous_is_empty {
+                RestoreAction::Clear
+            } else {
+                RestoreAction::Restore
+            }
+        }
+        _ => RestoreAction::Skip,
+    }
+}
+
+/// テキストを書き込む（機密プロンプトは履歴除外ヒントを付ける）
+fn write_text(text: &str, sensitive: bool) -> Result<(), String> {
+    with_clipboard(|clipboard| {
+        let set = clipboard.set();
+        if !sensitive {
+            return set.text(text);
+        }
+        #[cfg(target_os = "linux")]
+        let set = {
+            use arboard::SetExtLinux;
+            set.exclude_from_history()
+        };
+        #[cfg(target_os = "macos")]
+        let set = {
+            use arboard::SetExtApple;
+            set.exclude_from_history()
+        };
+        #[cfg(target_os = "windows")]
+        let set = {
+            use arboard::SetExtWindows;
+            set.exclude_from_history().exclude_from_cloud()
+        };
+        set.text(text)
+    })
+}
+
+/// 書き込み前の内容を保存してからテキストを書き込み、復元を予約する
+///
+/// 予約中の復元がある場合、戻す対象はその時の書き込み前の内容を引き継ぐ
+fn write_with_snapshot(text: &str, sensitive: bool) -> Result<(), String> {
+    let mut pending = PENDING_RESTORE.lock().unwrap();
+    let previous = match pending.take() {
+        Some(PendingRestore { previous, task, .. }) => {
+            if let Some(task) = task {
+                task.abort();
+            }
+            previous
+        }
+        None => with_clipboard(|clipboard| Ok(ClipboardSnapshot::capture(clipboard)))?,
+    };
+
+    write_text(text, sensitive)?;
+    *pending = Some(PendingRestore {
+        written_hash: content_hash(text),
+        previous,
+        sensitive,
+        task: None,
+    });
+    Ok(())
+}
+
+/// 予約した復元を指定時間後に実行
+fn schedule_restore(app_handle: &AppHandle, delay: Duration) {
+    let mut pending = PENDING_RESTORE.lock().unwrap();
+    let Some(pending) = pending.as_mut() else {
+        return;
+    };
+    let app_handle = app_handle.clone();
+    let task = tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(delay).await;
+        restore_if_unchanged(&app_handle);
+    });
+    if let Some(previous) = pending.task.replace(task) {
+        previous.abort();
+    }
+}
+
+/// プロンプトをクリップボードにコピーする
+///
+/// 機密プロンプトは履歴除外ヒントを付けて書き込み、設定した時間の後に元の内容に戻すか消去する
+pub fn write_prompt_text(app_handle: &AppHandle, text: &str, sensitive: bool) -> Result<(), String> {
+    if !sensitive {
+        use tauri_plugin_clipboard_manager::ClipboardExt;
+        return app_handle
+            .clipboard()
+            .write_text(text.to_string())
+            .map_err(|e| e.to_string());
+    }
+
+    write_with_snapshot(text, true)?;
+    schedule_restore(app_handle, ClipboardConfig::load_or_default().clear_delay());
+    Ok(())
+}
+
+/// ペーストするプロンプトをクリップボードに書き込む
+///
+/// ペーストの送信後に`finish_paste`を呼ぶこと
+pub fn begin_paste(app_handle: &AppHandle, text: &str, sensitive: bool) -> Result<(), String> {
+    if ClipboardConfig::load_or_default().restore_after_paste {
+        write_with_snapshot(text, sensitive)
+    } else {
+        write_prompt_text(app_handle, text, sensitive)
+    }
+}
+
+/// ペーストの送信後、設定した時間の後に元のクリップボードに戻す
+///
+/// ペーストを送信できなかった場合は手動でペーストできるようプロンプトを残す
+/// （機密プロンプトは消去までの時間の後に戻す）
+pub fn finish_paste(app_handle: &AppHandle, pasted: bool) {
+    let config = ClipboardConfig::load_or_default();
+    if !config.restore_after_paste {
+        return;
+    }
+    if pasted {
+        schedule_restore(app_handle, config.restore_delay());
+        return;
+    }
+    let sensitive = PENDING_RESTORE.lock().unwrap().as_ref().is_some_and(|pending| pending.sensitive);
+    if sensitive {
+        schedule_restore(app_handle, config.clear_delay());
+    } else {
+        PENDING_RESTORE.lock().unwrap().take();
+    }
+}
+
+/// 予約した復元を実行（クリップボードがこちらの書き込みのままの場合のみ）
+fn restore_if_unchanged(app_handle: &AppHandle) {
+    let Some(pending) = PENDING_RESTORE.lock().unwrap().take() else {
+        return;
+    };
+    let current = with_clipboard(|clipboard| clipboard.get_text()).ok().map(Zeroizing::new);
+    let action = plan_restore(
+        current.as_deref().map(String::as_str),
+        &pending.written_hash,
+        pending.previous.is_empty(),
+    );
+
+    let result = match action {
+        RestoreAction::Restore => with_clipboard(|clipboard| pending.previous.restore(clipboard)),
+        RestoreAction::Clear => with_clipboard(|clipboard| clipboard.clear()),
+        RestoreAction::Skip => return,
+    };
+    match result {
+        Ok(()) => {
+            let event = if pending.sensitive { "clipboard-cleared" } else { "clipboard-restored" };
+            let _ = app_handle.emit(event, serde_json::json!({ "restored": action == RestoreAction::Restore }));
+        }
+        Err(e) => eprintln!("Failed to restore clipboard: {e}"),
+    }
+}
+
+/// コピーのキーストロークで選択範囲をクリップボードに入れ、そのテキストを読み取る
+///
+/// `copy`でキーを送った後、クリップボードのテキストが変わるまで待つ。
+/// 変わらなかった場合（何も選択されていない、または選択範囲がコピー前と同じ）は`None`。
+/// 読み取った後はコピー前の内容に戻す
+pub async fn capture_copied_text(copy: impl FnOnce() -> Result<(), String>) -> Result<Option<String>, String> {
+    let (previous, before) = with_clipboard(|clipboard| {
+        let before = clipboard.get_text().ok().map(|text| content_hash(&text));
+        Ok((ClipboardSnapshot::capture(clipboard), before))
+    })?;
+    copy()?;
+
+    let started = Instant::now();
+    let mut copied = None;
+    while copied.is_none() && started.elapsed() < COPY_TIMEOUT {
+        tokio::time::sleep(COPY_POLL_INTERVAL).await;
+        copied = with_clipboard(|clipboard| clipboard.get_text())
+            .ok()
+            .filter(|text| before != Some(content_hash(text)));
+    }
+
+    if copied.is_some() {
+        if let Err(e) = with_clipboard(|clipboard| previous.restore(clipboard)) {
+            eprintln!("Failed to restore clipboard after capture: {e}");
+        }
+    }
+    Ok(copied)
+}
+
+/// クリップボード設定を取得
+#[tauri::command]
+pub async fn get_clipboard_config() -> Result<ClipboardConfig, String> {
+    crate::settings::load().map(|settings| settings.clipboard)
+}
+
+/// クリップボード設定を保存
+#[tauri::command]
+pub async fn set_clipboard_config(app_handle: AppHandle, config: ClipboardConfig) -> Result<(), String> {
+    crate::settings::update_with(&app_handle, |settings| {
+        settings.clipboard = config;
+        Ok(())
+    })
+    .map(|_| ())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_restore_only_when_clipboard_still_ours() {
+        let written = content_hash("sk-live-123");
+
+        assert_eq!(plan_restore(Some("sk-live-123"), &written, false), RestoreAction::Restore);
+        assert_eq!(plan_restore(Some("sk-live-123"), &written, true), RestoreAction::Clear);
+        // ユーザーが別の内容をコピーした
+        assert_eq!(plan_restore(Some("copied later"), &written, false), RestoreAction::Skip);
+        // テキスト以外がコピーされている
+        assert_eq!(plan_restore(None, &written, false), RestoreAction::Skip);
+    }
+
+    #[test]
+    fn test_snapshot_emptiness() {
+        assert!(ClipboardSnapshot::Empty.is_empty());
+        assert!(ClipboardSnapshot::Text(Zeroizing::new(String::new())).is_empty());
+        assert!(!ClipboardSnapshot::Text(Zeroizing::new("x".to_string())).is_empty());
+        assert!(!ClipboardSnapshot::Files(vec![PathBuf::from("/tmp/a.txt")]).is_empty());
+    }
+
+    #[test]
+    fn test_config_validation_and_defaults() {
+        let config: ClipboardConfig = serde_json::from_str(r#"{"sensitive_clear_delay_secs":30}"#).unwrap();
+        assert_eq!(config, ClipboardConfig::default());
+        assert!(config.restore_after_paste);
+        assert!(config.validate().is_ok());
+
+        let too_short = ClipboardConfig { sensitive_clear_delay_secs: 1, ..ClipboardConfig::default() };
+        assert!(too_short.validate().is_err());
+        assert_eq!(too_short.clear_delay(), Duration::from_secs(MIN_CLEAR_DELAY_SECS));
+        let too_long = ClipboardConfig { restore_delay_ms: MAX_RESTORE_DELAY_MS + 1, ..ClipboardConfig::default() };
+        assert!(too_long.validate().is_err());
+        assert_eq!(too_long.restore_delay(), Duration::from_millis(MAX_RESTORE_DELAY_MS));
+    }
+}
@@ -7,21 +7,28 @@ pub mod environment;
 use crate::database::{
     create_prompt as db_create_prompt,
     delete_prompt as db_delete_prompt,
-    get_all_prompts as db_get_all_prompts,
+    list_prompts as db_list_prompts,
     get_prompt as db_get_prompt,
     search_prompts as db_search_prompts,
     search_prompts_fast as db_search_prompts_fast,
     update_prompt as db_update_prompt,
+    set_prompt_secret as db_set_prompt_secret,
     pin_prompt as db_pin_prompt,
     unpin_prompt as db_unpin_prompt,
     get_pinned_prompts as db_get_pinned_prompts,
     get_pinned_prompt_content as db_get_pinned_prompt_content,
+    get_prompt_paste_content as db_get_prompt_paste_content,
+    get_changes_since as db_get_changes_since,
+    is_valid_workspace_id,
+    ChangeSet,
+    MAX_PIN_POSITION,
     CreatePromptRequest,
+    PinnedContent,
     Prompt,
+    PromptFilter,
     UpdatePromptRequest,
 };
 use tauri::AppHandle;
-use tauri_plugin_clipboard_manager::ClipboardExt;
 
 /// エラーレスポンス構造
 #[derive(Debug, serde::Serialize)]
@@ -36,6 +43,31 @@ pub struct SuccessResponse<T> {
     pub data: T,
 }
 
+/// タイトルの最大長（バイト数）
+pub(crate) const MAX_TITLE_LENGTH: usize = 200;
+
+/// タイトルを最大長以内に切り詰める（文字の途中では切らない）
+pub(crate) fn truncate_title(title: &str) -> &str {
+    if title.len() <= MAX_TITLE_LENGTH {
+        return title;
+    }
+    let mut end = MAX_TITLE_LENGTH;
+    while !title.is_char_boundary(end) {
+        end -= 1;
+    }
+    &title[..end]
+}
+
+/// ワークスペースIDの検証（指定がある場合のみ）
+fn validate_workspace_id(workspace_id: Option<&str>) -> Result<(), ErrorResponse> {
+    match workspace_id {
+        Some(workspace_id) if !is_valid_workspace_id(workspace_id) => Err(ErrorResponse {
+            error: "Invalid workspace ID format".to_string(),
+        }),
+        _ => Ok(()),
+    }
+}
+
 /// プロンプト作成コマンド
 #[tauri::command]
 pub async fn create_prompt(request: CreatePromptRequest) -> Result<SuccessResponse<Prompt>, ErrorResponse> {
@@ -48,7 +80,7 @@ pub async fn create_prompt(request: CreatePromptRequest) -> Result<SuccessRespon
     
     // サイズ制限チェック（タイトルがある場合のみ）
     if let Some(ref title) = request.title {
-        if title.len() > 200 {
+        if title.len() > MAX_TITLE_LENGTH {
             return Err(ErrorResponse {
                 error: "Title too long (max 200 characters)".to_string(),
             });
@@ -61,6 +93,8 @@ pub async fn create_prompt(request: CreatePromptRequest) -> Result<SuccessRespon
         });
     }
     
+    validate_workspace_id(request.workspace_id.as_deref())?;
+    
     match db_create_prompt(request).await {
         Ok(prompt) => Ok(SuccessResponse {
             success: true,
@@ -87,9 +121,14 @@ pub async fn get_prompt(id: String) -> Result<SuccessResponse<Option<Prompt>>, E
 }
 
 /// 全プロンプト取得コマンド
+/// 
+/// filter: 状態・公開範囲・タグ・ワークスペースによる絞り込み（省略時は全件）
 #[tauri::command]
-pub async fn get_all_prompts() -> Result<SuccessResponse<Vec<Prompt>>, ErrorResponse> {
-    match db_get_all_prompts().await {
+pub async fn get_all_prompts(filter: Option<PromptFilter>) -> Result<SuccessResponse<Vec<Prompt>>, ErrorResponse> {
+    let filter = filter.unwrap_or_default();
+    validate_workspace_id(filter.workspace_id.as_deref())?;
+    
+    match db_list_prompts(&filter).await {
         Ok(prompts) => Ok(SuccessResponse {
             success: true,
             data: prompts,
@@ -101,8 +140,10 @@ pub async fn get_all_prompts() -> Result<SuccessResponse<Vec<Prompt>>, ErrorResp
 }
 
 /// プロンプト検索コマンド
+/// 
+/// filter: 状態・公開範囲・タグ・ワークスペースによる絞り込み（省略時は全件）
 #[tauri::command]
-pub async fn search_prompts(query: String) -> Result<SuccessResponse<Vec<Prompt>>, ErrorResponse> {
+pub async fn search_prompts(query: String, filter: Option<PromptFilter>) -> Result<SuccessResponse<Vec<Prompt>>, ErrorResponse> {
     // 入力値検証
     if query.len() > 1000 {
         return Err(ErrorResponse {
@@ -110,7 +151,10 @@ pub async fn search_prompts(query: String) -> Result<SuccessResponse<Vec<Prompt>
         });
     }
     
-    match db_search_prompts(&query).await {
+    let filter = filter.unwrap_or_default();
+    validate_workspace_id(filter.workspace_id.as_deref())?;
+    
+    match db_search_prompts(&query, &filter).await {
         Ok(prompts) => Ok(SuccessResponse {
             success: true,
             data: prompts,
@@ -128,7 +172,7 @@ pub async fn search_prompts(query: String) -> Result<SuccessResponse<Vec<Prompt>
 /// - 結果数制限（最大20件）
 /// - <50ms レスポンス目標
 #[tauri::command]
-pub async fn search_prompts_fast(query: String) -> Result<SuccessResponse<Vec<Prompt>>, ErrorResponse> {
+pub async fn search_prompts_fast(query: String, filter: Option<PromptFilter>) -> Result<SuccessResponse<Vec<Prompt>>, ErrorResponse> {
     // 入力値検証
     if query.len() > 1000 {
         return Err(ErrorResponse {
@@ -144,7 +188,10 @@ pub async fn search_prompts_fast(query: String) -> Result<SuccessResponse<Vec<Pr
         });
     }
     
-    match db_search_prompts_fast(&query).await {
+    let filter = filter.unwrap_or_default();
+    validate_workspace_id(filter.workspace_id.as_deref())?;
+    
+    match db_search_prompts_fast(&query, &filter).await {
         Ok(prompts) => Ok(SuccessResponse {
             success: true,
             data: prompts,
@@ -158,6 +205,7 @@ pub async fn search_prompts_fast(query: String) -> Result<SuccessResponse<Vec<Pr
 /// プロンプト更新コマンド
 #[tauri::command]
 pub async fn update_prompt(
+    app_handle: AppHandle,
     id: String,
     request: UpdatePromptRequest,
 ) -> Result<SuccessResponse<Option<Prompt>>, ErrorResponse> {
@@ -168,25 +216,56 @@ pub async fn update_prompt(
         });
     }
     
+    validate_workspace_id(request.workspace_id.as_ref().and_then(Option::as_deref))?;
+    
     match db_update_prompt(&id, request).await {
+        Ok(prompt) => {
+            // タイトル・本文はトレイメニューにも表示している
+            crate::tray::refresh_tray(&app_handle);
+            Ok(SuccessResponse {
+                success: true,
+                data: prompt,
+            })
+        }
+        Err(_e) => Err(ErrorResponse {
+            error: "Failed to update prompt. Please check your input and try again.".to_string(),
+        }),
+    }
+}
+
+/// プロンプトをシークレットにする・解除するコマンド
+/// 
+/// 保管庫のロック解除が必要
+#[tauri::command]
+pub async fn set_prompt_secret(id: String, secret: bool) -> Result<SuccessResponse<Option<Prompt>>, ErrorResponse> {
+    if id.trim().is_empty() {
+        return Err(ErrorResponse {
+            error: "Prompt ID cannot be empty".to_string(),
+        });
+    }
+    
+    match db_set_prompt_secret(&id, secret).await {
         Ok(prompt) => Ok(SuccessResponse {
             success: true,
             data: prompt,
         }),
-        Err(_e) => Err(ErrorResponse {
-            error: "Failed to update prompt. Please check your input and try again.".to_string(),
+        Err(e) => Err(ErrorResponse {
+            error: format!("Failed to update secret flag: {e}"),
         }),
     }
 }
 
 /// プロンプト削除コマンド
 #[tauri::command]
-pub async fn delete_prompt(id: String) -> Result<SuccessResponse<bool>, ErrorResponse> {
+pub async fn delete_prompt(app_handle: AppHandle, id: String) -> Result<SuccessResponse<bool>, ErrorResponse> {
     match db_delete_prompt(&id).await {
-        Ok(deleted) => Ok(SuccessResponse {
-            success: true,
-            data: deleted,
-        }),
+        Ok(deleted) => {
+            crate::tray::refresh_tray(&app_handle);
+            Ok(SuccessResponse {
+                success: true,
+                data: deleted,
+            })
+        }
         Err(e) => Err(ErrorResponse {
             error: format!("Failed to delete prompt: {e}"),
         }),
@@ -225,9 +304,9 @@ pub async fn get_app_info() -> Result<SuccessResponse<serde_json::Value>, ErrorR
 /// プロンプトをピン留めするコマンド
 /// 
 /// prompt_id: ピン留めするプロンプトのID
-/// position: ピン留め位置（1-10）
+/// position: ピン留め位置（1-36）
 #[tauri::command]
-pub async fn pin_prompt(prompt_id: String, position: u8) -> Result<SuccessResponse<String>, ErrorResponse> {
+pub async fn pin_prompt(app_handle: AppHandle, prompt_id: String, position: u8) -> Result<SuccessResponse<String>, ErrorResponse> {
     // 入力値検証
     if prompt_id.trim().is_empty() {
         return Err(ErrorResponse {
@@ -235,17 +314,20 @@ pub async fn pin_prompt(prompt_id: String, position: u8) -> Result<SuccessRespon
         });
     }
     
-    if !(1..=10).contains(&position) {
+    if !(1..=MAX_PIN_POSITION).contains(&position) {
         return Err(ErrorResponse {
-            error: "Pin position must be between 1 and 10".to_string(),
+            error: format!("Pin position must be between 1 and {MAX_PIN_POSITION}"),
         });
     }
     
     match db_pin_prompt(&prompt_id, position).await {
-        Ok(()) => Ok(SuccessResponse {
-            success: true,
-            data: format!("Prompt pinned to position {position}"),
-        }),
+        Ok(()) => {
+            crate::tray::refresh_tray(&app_handle);
+            Ok(SuccessResponse {
+                success: true,
+                data: format!("Prompt pinned to position {position}"),
+            })
+        }
         Err(e) => Err(ErrorResponse {
             error: format!("Failed to pin prompt: {e}"),
         }),
@@ -254,21 +336,24 @@ pub async fn pin_prompt(prompt_id: String, position: u8) -> Result<SuccessRespon
 
 /// プロンプトのピン留めを解除するコマンド
 /// 
-/// position: 解除するピン留め位置（1-10）
+/// position: 解除するピン留め位置（1-36）
 #[tauri::command]
-pub async fn unpin_prompt(position: u8) -> Result<SuccessResponse<String>, ErrorResponse> {
+pub async fn unpin_prompt(app_handle: AppHandle, position: u8) -> Result<SuccessResponse<String>, ErrorResponse> {
     // 入力値検証
-    if !(1..=10).contains(&position) {
+    if !(1..=MAX_PIN_POSITION).contains(&position) {
         return Err(ErrorResponse {
-            error: "Pin position must be between 1 and 10".to_string(),
+            error: format!("Pin position must be between 1 and {MAX_PIN_POSITION}"),
         });
     }
     
     match db_unpin_prompt(position).await {
-        Ok(()) => Ok(SuccessResponse {
-            success: true,
-            data: format!("Prompt unpinned from position {position}"),
-        }),
+        Ok(()) => {
+            crate::tray::refresh_tray(&app_handle);
+            Ok(SuccessResponse {
+                success: true,
+                data: format!("Prompt unpinned from position {position}"),
+            })
+        }
         Err(e) => Err(ErrorResponse {
             error: format!("Failed to unpin prompt: {e}"),
         }),
@@ -291,38 +376,26 @@ pub async fn get_pinned_prompts() -> Result<SuccessResponse<Vec<Prompt>>, ErrorR
 
 /// 指定されたピン留め位置のプロンプトをクリップボードにコピーするコマンド
 /// 
-/// position: コピーするピン留め位置（1-10）
+/// position: コピーするピン留め位置（1-36）
 #[tauri::command]
 pub async fn copy_pinned_prompt(
     app_handle: AppHandle,
     position: u8
 ) -> Result<SuccessResponse<String>, ErrorResponse> {
     // 入力値検証
-    if !(1..=10).contains(&position) {
+    if !(1..=MAX_PIN_POSITION).contains(&position) {
         return Err(ErrorResponse {
-            error: "Pin position must be between 1 and 10".to_string(),
+            error: format!("Pin position must be between 1 and {MAX_PIN_POSITION}"),
         });
     }
     
     match db_get_pinned_prompt_content(position).await {
-        Ok(Some(content)) => {
-            // サイズ制限チェック（DoS攻撃防止）
-            if content.len() > 1_000_000 { // 1MB制限
-                return Err(ErrorResponse {
-                    error: "Prompt content too large for clipboard".to_string(),
-                });
-            }
-            
-            // Tauriプラグインを使用した安全なクリップボード操作
-            match app_handle.clipboard().write_text(content.clone()) {
-                Ok(_) => Ok(SuccessResponse {
-                    success: true,
-                    data: format!("Prompt from position {position} copied to clipboard"),
-                }),
-                Err(e) => Err(ErrorResponse {
-                    error: format!("Failed to copy to clipboard: {}", e),
-                })
-            }
+        Ok(Some(pinned)) => {
+            copy_to_clipboard(&app_handle, &pinned)?;
+            Ok(SuccessResponse {
+                success: true,
+                data: format!("Prompt from position {position} copied to clipboard"),
+            })
         }
         Ok(None) => Err(ErrorResponse {
             error: format!("No prompt found at pin position {position}"),
@@ -333,6 +406,57 @@ pub async fn copy_pinned_prompt(
     }
 }
 
+/// 指定IDのプロンプトをクリップボードにコピーする（トレイメニューの最近のプロンプト）
+pub(crate) async fn copy_prompt_by_id(app_handle: &AppHandle, id: &str) -> Result<(), ErrorResponse> {
+    match db_get_prompt_paste_content(id).await {
+        Ok(Some(content)) => copy_to_clipboard(app_handle, &content),
+        Ok(None) => Err(ErrorResponse {
+            error: "Prompt not found".to_string(),
+        }),
+        Err(e) => Err(ErrorResponse {
+            error: format!("Failed to get prompt: {e}"),
+        }),
+    }
+}
+
+/// ペースト用の内容をクリップボードにコピーする
+fn copy_to_clipboard(app_handle: &AppHandle, content: &PinnedContent) -> Result<(), ErrorResponse> {
+    // サイズ制限チェック（DoS攻撃防止）
+    if content.content.len() > 1_000_000 { // 1MB制限
+        return Err(ErrorResponse {
+            error: "Prompt content too large for clipboard".to_string(),
+        });
+    }
+    
+    // 機密プロンプトは一定時間後にクリップボードから消去
+    crate::clipboard::write_prompt_text(app_handle, &content.content, content.sensitive).map_err(|e| ErrorResponse {
+        error: format!("Failed to copy to clipboard: {e}"),
+    })
+}
+
+/// 変更ジャーナル取得コマンド
+/// 
+/// since: 前回取得した`next_seq`（初回は0）
+/// limit: 最大取得件数（省略時は1000）
+#[tauri::command]
+pub async fn get_changes_since(since: i64, limit: Option<u32>) -> Result<SuccessResponse<ChangeSet>, ErrorResponse> {
+    if since < 0 {
+        return Err(ErrorResponse {
+            error: "Sequence number must not be negative".to_string(),
+        });
+    }
+    
+    match db_get_changes_since(since, limit).await {
+        Ok(change_set) => Ok(SuccessResponse {
+            success: true,
+            data: change_set,
+        }),
+        Err(e) => Err(ErrorResponse {
+            error: format!("Failed to get changes: {e}"),
+        }),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -346,6 +470,11 @@ mod tests {
             content: "Test content".to_string(),
             tags: None,
             quick_access_key: None,
+            status: None,
+            visibility: None,
+            workspace_id: None,
+            is_sensitive: None,
+            paste_confirmation: None,
         };
         
         // 長すぎるタイトルはエラーとなるはず
@@ -353,16 +482,28 @@ mod tests {
         assert!(request.title.as_ref().unwrap().len() > 200);
     }
     
+    #[test]
+    fn test_truncate_title() {
+        assert_eq!(truncate_title("Short title"), "Short title");
+        assert_eq!(truncate_title(&"a".repeat(201)).len(), MAX_TITLE_LENGTH);
+        // マルチバイト文字は文字境界で切る（3バイト文字×67 = 201バイト → 66文字）
+        let japanese = "あ".repeat(67);
+        assert_eq!(truncate_title(&japanese), "あ".repeat(66));
+        let emoji = "😀".repeat(60);
+        assert!(truncate_title(&emoji).len() <= MAX_TITLE_LENGTH);
+        assert_eq!(truncate_title(&emoji).chars().count(), 50);
+    }
+    
     #[test]
     fn test_pin_position_validation() {
         // 有効な範囲のチェック
-        for pos in 1..=10 {
-            assert!((1..=10).contains(&pos));
+        for pos in 1..=MAX_PIN_POSITION {
+            assert!((1..=MAX_PIN_POSITION).contains(&pos));
         }
         
         // 無効な範囲のチェック
-        assert!(!(1..=10).contains(&0));
-        assert!(!(1..=10).contains(&11));
+        assert!(!(1..=MAX_PIN_POSITION).contains(&0));
+        assert!(!(1..=MAX_PIN_POSITION).contains(&37));
     }
     
     #[test]
@@ -3,6 +3,7 @@
  * 
  * 将来の拡張を考慮した最小限の設定構造
  * YAGNI原則に従い、現時点では定数定義のみ
+ * （ユーザーが変更できる設定は`settings`モジュールで管理）
  */
 
 pub mod update_config;
@@ -15,6 +16,13 @@ pub mod shortcuts {
     /// - Windows/Linux: Ctrl+Shift+P
     /// - macOS: Cmd+Shift+P
     pub const QUICK_LAUNCHER: &str = "CommandOrControl+Shift+P";
+
+    /// 選択範囲の取り込みのデフォルトショートカット
+    /// 
+    /// プラットフォーム依存:
+    /// - Windows/Linux: Ctrl+Alt+Shift+C
+    /// - macOS: Cmd+Option+Shift+C
+    pub const CAPTURE_SELECTION: &str = "CommandOrControl+Alt+Shift+C";
 }
 
 /// 検索設定
@@ -27,21 +35,3 @@ pub mod search {
     #[allow(dead_code)]
     pub const MAX_RESULTS: i32 = 20;
 }
-
-// 将来的な拡張例（コメントアウト）
-// 
-// #[derive(Debug, serde::Serialize, serde::Deserialize)]
-// pub struct AppConfig {
-//     pub shortcuts: ShortcutConfig,
-//     pub search: SearchConfig,
-// }
-// 
-// impl AppConfig {
-//     pub fn load() -> Result<Self, Box<dyn std::error::Error>> {
-//         // 設定ファイルからの読み込み
-//     }
-//     
-//     pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
-//         // 設定ファイルへの保存
-//     }
-// }
\ No newline at end of file
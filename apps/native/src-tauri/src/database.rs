@@ -4,11 +4,31 @@
  */
 use serde::{Deserialize, Serialize};
 use sqlx::{SqlitePool, Row};
-use std::sync::OnceLock;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
 use uuid::Uuid;
 
+/// データベース接続プールの状態
+enum PoolState {
+    /// `init_database()`が未実行
+    Uninitialized,
+    /// 利用可能
+    Open(SqlitePool),
+    /// 暗号化されたデータベースがロック中（パスフレーズによるロック解除待ち）
+    Locked,
+}
+
 /// グローバルデータベース接続プール
-static DB_POOL: OnceLock<SqlitePool> = OnceLock::new();
+static DB_POOL: RwLock<PoolState> = RwLock::new(PoolState::Uninitialized);
+
+/// 変更ジャーナルの最大エントリ数（超えた場合にコンパクション）
+const CHANGE_JOURNAL_MAX_ENTRIES: i64 = 10_000;
+
+/// `get_changes_since`で一度に返す最大件数
+const CHANGE_JOURNAL_MAX_PAGE: u32 = 1_000;
+
+/// ピン留め位置の最大値（1-10: 数字キー、11-36: リーダーキーモードの英字キー a-z）
+pub const MAX_PIN_POSITION: u8 = 36;
 
 /// プロンプトデータ構造
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
@@ -20,8 +40,69 @@ pub struct Prompt {
     pub quick_access_key: Option<String>, // クイックアクセスキー
     pub created_at: chrono::DateTime<chrono::Utc>,
     pub updated_at: chrono::DateTime<chrono::Utc>,
-    pub pinned_position: Option<u8>, // ピン留め位置 (1-10)
+    pub pinned_position: Option<u8>, // ピン留め位置 (1-36)
     pub pinned_at: Option<chrono::DateTime<chrono::Utc>>, // ピン留め日時
+    pub status: PromptStatus, // 公開状態（APIの`status`と同じ値）
+    pub visibility: PromptVisibility, // 公開範囲（APIの`visibility`と同じ値）
+    pub workspace_id: Option<String>, // 所属ワークスペース（未割り当てはNone）
+    #[serde(default)]
+    pub is_secret: bool, // シークレット（本文は保管庫の鍵で暗号化して保存）
+    #[serde(default)]
+    pub is_sensitive: bool, // 機密（ペースト後にクリップボードから消去する）
+    #[serde(default)]
+    pub paste_confirmation: PasteConfirmation, // 大きなプロンプトのペースト前の確認
+}
+
+/// プロンプトの状態（@prompalette/core の PROMPT_STATUS と対応）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(type_name = "TEXT", rename_all = "lowercase")]
+pub enum PromptStatus {
+    Draft,
+    #[default]
+    Active,
+    Archived,
+}
+
+/// プロンプトの公開範囲（@prompalette/core の PROMPT_VISIBILITY と対応）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(type_name = "TEXT", rename_all = "lowercase")]
+pub enum PromptVisibility {
+    #[default]
+    Private,
+    Public,
+    Shared,
+}
+
+/// ホットキーでペーストする前の確認（プロンプト単位の設定）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(type_name = "TEXT", rename_all = "lowercase")]
+pub enum PasteConfirmation {
+    /// ペースト設定のしきい値を超える場合のみ確認
+    #[default]
+    Default,
+    /// 大きさに関係なく確認
+    Always,
+    /// 確認しない
+    Never,
+}
+
+/// ワークスペースIDの形式チェック（`wsp_` + 英数字、APIと同じ規則）
+pub fn is_valid_workspace_id(workspace_id: &str) -> bool {
+    workspace_id
+        .strip_prefix("wsp_")
+        .is_some_and(|rest| !rest.is_empty() && rest.chars().all(|c| c.is_ascii_alphanumeric()))
+}
+
+/// キーがある場合は`Some`として読む（`null`は`Some(None)`、キーがない場合は`#[serde(default)]`で`None`）
+fn deserialize_present<'de, D, T>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
 }
 
 /// プロンプト作成リクエスト
@@ -32,6 +113,14 @@ pub struct CreatePromptRequest {
     pub tags: Option<Vec<String>>,
     #[serde(rename = "quickAccessKey")]
     pub quick_access_key: Option<String>,
+    pub status: Option<PromptStatus>,
+    pub visibility: Option<PromptVisibility>,
+    #[serde(rename = "workspaceId")]
+    pub workspace_id: Option<String>,
+    #[serde(default, rename = "isSensitive")]
+    pub is_sensitive: Option<bool>,
+    #[serde(default, rename = "pasteConfirmation")]
+    pub paste_confirmation: Option<PasteConfirmation>,
 }
 
 /// プロンプト更新リクエスト
@@ -42,8 +131,70 @@ pub struct UpdatePromptRequest {
     pub tags: Option<Vec<String>>,
     #[serde(rename = "quickAccessKey")]
     pub quick_access_key: Option<String>,
+    pub status: Option<PromptStatus>,
+    pub visibility: Option<PromptVisibility>,
+    /// 未指定は変更しない、`null`はワークスペースから外す
+    #[serde(default, rename = "workspaceId", deserialize_with = "deserialize_present")]
+    pub workspace_id: Option<Option<String>>,
+    #[serde(default, rename = "isSensitive")]
+    pub is_sensitive: Option<bool>,
+    #[serde(default, rename = "pasteConfirmation")]
+    pub paste_confirmation: Option<PasteConfirmation>,
 }
 
+/// ピン留めプロンプト（またはペースト履歴のプロンプト）のペースト用の内容
+#[derive(Debug, Clone)]
+pub struct PinnedContent {
+    pub id: String,
+    pub content: String,
+    /// 機密またはシークレット（ペースト後にクリップボードから消去する）
+    pub sensitive: bool,
+    pub confirmation: PasteConfirmation,
+}
+
+/// 変更ジャーナルのエントリ
+///
+/// op: `create` / `update` / `delete` / `pin` / `unpin`
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct Change {
+    pub seq: i64,
+    pub prompt_id: String,
+    pub op: String,
+    pub changed_at: String,
+}
+
+/// `get_changes_since`の結果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChangeSet {
+    pub changes: Vec<Change>,
+    /// 次回問い合わせに使うシーケンス番号
+    pub next_seq: i64,
+    /// 続きのエントリがある場合true
+    pub has_more: bool,
+    /// 指定したシーケンス番号がコンパクションで失われている場合true
+    /// （呼び出し側は全件を再取得してから`next_seq`以降を追うこと）
+    pub reset_required: bool,
+}
+
+/// 一覧・検索の絞り込み条件（APIの`GET /api/v1/prompts`のクエリと対応）
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct PromptFilter {
+    pub status: Option<PromptStatus>,
+    pub visibility: Option<PromptVisibility>,
+    #[serde(rename = "tagId")]
+    pub tag_id: Option<String>,
+    #[serde(rename = "workspaceId")]
+    pub workspace_id: Option<String>,
+}
+
+/// 絞り込み条件のSQL（`$1`-`$4`にstatus, visibility, tag_id, workspace_idをバインドする）
+const PROMPT_FILTER_SQL: &str = r"
+    ($1 IS NULL OR status = $1)
+    AND ($2 IS NULL OR visibility = $2)
+    AND ($3 IS NULL OR EXISTS (SELECT 1 FROM json_each(CASE WHEN json_valid(prompts.tags) THEN prompts.tags ELSE '[]' END) WHERE value = $3))
+    AND ($4 IS NULL OR workspace_id = $4)
+";
+
 /// アプリケーションデータディレクトリの取得
 /// プラットフォーム固有の適切なディレクトリを返す
 fn get_app_data_dir() -> Result<std::path::PathBuf, Box<dyn std::error::Error>> {
@@ -54,8 +205,17 @@ fn get_app_data_dir() -> Result<std::path::PathBuf, Box<dyn std::error::Error>>
 }
 
 
+/// データベースファイルのパス（環境固有のファイル名）
+pub(crate) fn database_path() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    use crate::environment::Environment;
+    Ok(get_app_data_dir()?.join(Environment::current().database_filename()))
+}
+
 /// データベース初期化
 /// アプリケーション起動時に確実にデータベースを初期化
+///
+/// データベースが暗号化されている場合は接続せずにロック状態とし、
+/// `unlock_database`コマンドでパスフレーズが入力されるまで待つ
 pub async fn init_database() -> Result<(), Box<dyn std::error::Error>> {
     // データディレクトリの確実な作成
     let app_dir = get_app_data_dir()?;
@@ -63,10 +223,7 @@ pub async fn init_database() -> Result<(), Box<dyn std::error::Error>> {
         format!("Failed to create app data directory {}: {}", app_dir.display(), e)
     })?;
     
-    // 環境固有のデータベースファイル名を使用
-    use crate::environment::Environment;
-    let env = Environment::current();
-    let db_path = app_dir.join(env.database_filename());
+    let db_path = database_path()?;
     
     // データベースファイルへの書き込み権限確認
     if db_path.exists() {
@@ -79,29 +236,147 @@ pub async fn init_database() -> Result<(), Box<dyn std::error::Error>> {
         }
     }
     
-    // SQLite接続プール作成（設定最適化）
-    let pool = SqlitePool::connect_with(
-        sqlx::sqlite::SqliteConnectOptions::new()
-            .filename(&db_path)
-            .create_if_missing(true)
-            .journal_mode(sqlx::sqlite::SqliteJournalMode::Wal)
-            .synchronous(sqlx::sqlite::SqliteSynchronous::Normal)
-    ).await.map_err(|e| {
+    if matches!(*DB_POOL.read().map_err(|_| "Database pool lock poisoned")?, PoolState::Open(_)) {
+        return Err("Database pool already initialized".into());
+    }
+    
+    // 設定ではなくファイルのヘッダーで判断する（設定はファイルに合わせて更新される）
+    if crate::encryption::detect_database_encryption(&db_path)? {
+        println!("Database is encrypted. Waiting for unlock.");
+        set_pool_state(PoolState::Locked)?;
+        return Ok(());
+    }
+    
+    let pool = open_database(&db_path, None).await.map_err(|e| {
         format!("Failed to connect to database {}: {}", db_path.display(), e)
     })?;
     
-    // データベーススキーマの初期化
-    init_database_schema(&pool).await?;
+    // グローバルプールに設定
+    set_pool_state(PoolState::Open(pool))?;
     
-    // グローバルプールに設定（一度だけ設定可能）
-    DB_POOL.set(pool).map_err(|_| "Database pool already initialized")?;
+    Ok(())
+}
+
+/// データベースを開いてスキーマを初期化
+///
+/// key: SQLCipherの`PRAGMA key`に渡す値（暗号化されていない場合は`None`）。
+/// 鍵が間違っている場合は`sqlx::Error`（SQLITE_NOTADB）を返す
+pub(crate) async fn open_database(db_path: &Path, key: Option<&str>) -> Result<SqlitePool, Box<dyn std::error::Error>> {
+    // SQLite接続プール作成（設定最適化）
+    let mut options = sqlx::sqlite::SqliteConnectOptions::new()
+        .filename(db_path)
+        .create_if_missing(true)
+        .journal_mode(sqlx::sqlite::SqliteJournalMode::Wal)
+        .synchronous(sqlx::sqlite::SqliteSynchronous::Normal);
+    if let Some(key) = key {
+        options = options.pragma("key", key.to_string());
+    }
+    let pool = SqlitePool::connect_with(options).await?;
     
+    // データベーススキーマの初期化
+    let schema_result = init_database_schema(&pool).await.map_err(|e| e.to_string());
+    if let Err(message) = schema_result {
+        pool.close().await;
+        return Err(message.into());
+    }
+    
+    Ok(pool)
+}
+
+fn set_pool_state(state: PoolState) -> Result<(), Box<dyn std::error::Error>> {
+    *DB_POOL.write().map_err(|_| "Database pool lock poisoned")? = state;
+    Ok(())
+}
+
+/// ロック中のデータベースに接続プールを設定
+pub(crate) fn install_unlocked_pool(pool: SqlitePool) -> Result<(), Box<dyn std::error::Error>> {
+    let mut state = DB_POOL.write().map_err(|_| "Database pool lock poisoned")?;
+    if !matches!(*state, PoolState::Locked) {
+        return Err("Database is not locked".into());
+    }
+    *state = PoolState::Open(pool);
     Ok(())
 }
 
+/// 接続プールを取り外してロック状態にする（呼び出し側でプールを閉じる）
+pub(crate) fn take_pool_for_lock() -> Result<SqlitePool, Box<dyn std::error::Error>> {
+    let mut state = DB_POOL.write().map_err(|_| "Database pool lock poisoned")?;
+    match std::mem::replace(&mut *state, PoolState::Locked) {
+        PoolState::Open(pool) => Ok(pool),
+        previous => {
+            *state = previous;
+            Err("Database is not open".into())
+        }
+    }
+}
+
+/// 接続プールを差し替え（暗号化の有効化・無効化で使用）
+pub(crate) fn replace_pool(pool: SqlitePool) -> Result<(), Box<dyn std::error::Error>> {
+    set_pool_state(PoolState::Open(pool))
+}
+
+/// データベースファイルを別のファイルで置き換えて接続プールを開き直す（バックアップからの復元で使用）
+///
+/// 接続を閉じてから置き換え、古いWAL/SHMは削除する（残すと復元したファイルに古い変更が適用される）。
+/// 暗号化中は閉じる前の接続設定（鍵を含む）で開き直す
+pub(crate) async fn replace_database_file(source: &Path) -> Result<(), String> {
+    let db_path = database_path().map_err(|e| e.to_string())?;
+    let temp_path = db_path.with_extension("db.restore");
+
+    let pool = take_pool_for_lock().map_err(|e| e.to_string())?;
+    let options = pool.connect_options();
+    pool.close().await;
+
+    let replaced = (|| {
+        std::fs::copy(source, &temp_path)?;
+        for suffix in ["-wal", "-shm"] {
+            let mut side_file = db_path.clone().into_os_string();
+            side_file.push(suffix);
+            match std::fs::remove_file(side_file) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e),
+                _ => {}
+            }
+        }
+        std::fs::rename(&temp_path, &db_path)
+    })()
+    .map_err(|e| format!("Failed to restore database: {e}"));
+    if replaced.is_err() {
+        let _ = std::fs::remove_file(&temp_path);
+    }
+
+    // 置き換えに失敗した場合も元のデータベースを開き直す
+    let pool = SqlitePool::connect_with((*options).clone())
+        .await
+        .map_err(|e| format!("Failed to reopen database: {e}"))?;
+    if let Err(e) = init_database_schema(&pool).await {
+        pool.close().await;
+        set_pool_state(PoolState::Uninitialized).map_err(|e| e.to_string())?;
+        return Err(format!("Failed to reopen database: {e}"));
+    }
+    replace_pool(pool).map_err(|e| e.to_string())?;
+    replaced
+}
+
+/// WALの内容をデータベースファイルに反映（ファイル単位のバックアップ前に使用）
+pub async fn checkpoint_wal() {
+    if let Ok(pool) = try_get_db_pool() {
+        if let Err(e) = sqlx::query("PRAGMA wal_checkpoint(TRUNCATE)").execute(&pool).await {
+            eprintln!("Failed to checkpoint database before backup: {e}");
+        }
+    }
+}
+
+/// データベースがロック中かどうか
+pub fn is_database_locked() -> bool {
+    DB_POOL
+        .read()
+        .map(|state| matches!(*state, PoolState::Locked))
+        .unwrap_or(false)
+}
+
 /// データベーススキーマの初期化
 /// テーブル作成とインデックス設定を実行
-async fn init_database_schema(pool: &SqlitePool) -> Result<(), Box<dyn std::error::Error>> {
+pub(crate) async fn init_database_schema(pool: &SqlitePool) -> Result<(), Box<dyn std::error::Error>> {
     // プロンプトテーブル作成
     sqlx::query(
         r"
@@ -138,7 +413,139 @@ async fn init_database_schema(pool: &SqlitePool) -> Result<(), Box<dyn std::erro
     sqlx::query("CREATE INDEX IF NOT EXISTS idx_prompts_pinned_at ON prompts(pinned_at) WHERE pinned_at IS NOT NULL;")
         .execute(pool)
         .await?;
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_prompts_workspace_status ON prompts(workspace_id, status);")
+        .execute(pool)
+        .await?;
     
+    // APIサーバー同期用テーブル（ローカルID⇔リモートIDの対応と削除の墓標）
+    sqlx::query(
+        r"
+        CREATE TABLE IF NOT EXISTS api_sync_links (
+            local_id TEXT PRIMARY KEY,
+            remote_id TEXT NOT NULL UNIQUE,
+            local_hash TEXT NOT NULL,
+            remote_updated_at DATETIME NOT NULL,
+            synced_at DATETIME NOT NULL
+        );
+        ",
+    )
+    .execute(pool)
+    .await
+    .map_err(|e| format!("Failed to create api_sync_links table: {e}"))?;
+    sqlx::query(
+        r"
+        CREATE TABLE IF NOT EXISTS api_sync_tombstones (
+            remote_id TEXT PRIMARY KEY,
+            local_id TEXT NOT NULL,
+            deleted_at DATETIME NOT NULL
+        );
+        ",
+    )
+    .execute(pool)
+    .await
+    .map_err(|e| format!("Failed to create api_sync_tombstones table: {e}"))?;
+
+    // 変更ジャーナル
+    init_change_journal(pool).await?;
+
+    Ok(())
+}
+
+/// 変更ジャーナルのテーブルとトリガーを作成
+///
+/// どの経路でプロンプトが書き換えられても（同期処理の直接SQLを含む）
+/// 漏れなく記録されるよう、アプリ側ではなくトリガーで記録する
+/// `AUTOINCREMENT`によりシーケンス番号はコンパクション後も再利用されない
+async fn init_change_journal(pool: &SqlitePool) -> Result<(), Box<dyn std::error::Error>> {
+    sqlx::query(
+        r"
+        CREATE TABLE IF NOT EXISTS changes (
+            seq INTEGER PRIMARY KEY AUTOINCREMENT,
+            prompt_id TEXT NOT NULL,
+            op TEXT NOT NULL,
+            changed_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))
+        );
+        ",
+    )
+    .execute(pool)
+    .await
+    .map_err(|e| format!("Failed to create changes table: {e}"))?;
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_changes_prompt_id ON changes(prompt_id);")
+        .execute(pool)
+        .await?;
+
+    // コンパクションで削除済みエントリを破棄した位置（これより古いカーソルは再同期が必要）
+    sqlx::query(
+        r"
+        CREATE TABLE IF NOT EXISTS change_journal_meta (
+            id INTEGER PRIMARY KEY CHECK (id = 1),
+            purged_through INTEGER NOT NULL DEFAULT 0
+        );
+        ",
+    )
+    .execute(pool)
+    .await
+    .map_err(|e| format!("Failed to create change_journal_meta table: {e}"))?;
+    sqlx::query("INSERT OR IGNORE INTO change_journal_meta (id, purged_through) VALUES (1, 0);")
+        .execute(pool)
+        .await?;
+
+    // 監視対象カラムが増えた場合に備えて更新トリガーは毎回作り直す
+    sqlx::query("DROP TRIGGER IF EXISTS trg_prompts_journal_update;")
+        .execute(pool)
+        .await?;
+
+    let triggers = [
+        r"
+        CREATE TRIGGER IF NOT EXISTS trg_prompts_journal_create AFTER INSERT ON prompts
+        BEGIN
+            INSERT INTO changes (prompt_id, op) VALUES (NEW.id, 'create');
+        END;
+        ",
+        r"
+        CREATE TRIGGER IF NOT EXISTS trg_prompts_journal_update
+        AFTER UPDATE OF title, content, tags, quick_access_key, status, visibility, workspace_id ON prompts
+        WHEN OLD.title IS NOT NEW.title
+            OR OLD.content IS NOT NEW.content
+            OR OLD.tags IS NOT NEW.tags
+            OR OLD.quick_access_key IS NOT NEW.quick_access_key
+            OR OLD.status IS NOT NEW.status
+            OR OLD.visibility IS NOT NEW.visibility
+            OR OLD.workspace_id IS NOT NEW.workspace_id
+        BEGIN
+            INSERT INTO changes (prompt_id, op) VALUES (NEW.id, 'update');
+        END;
+        ",
+        r"
+        CREATE TRIGGER IF NOT EXISTS trg_prompts_journal_pin AFTER UPDATE OF pinned_position ON prompts
+        WHEN NEW.pinned_position IS NOT NULL AND OLD.pinned_position IS NOT NEW.pinned_position
+        BEGIN
+            INSERT INTO changes (prompt_id, op) VALUES (NEW.id, 'pin');
+        END;
+        ",
+        r"
+        CREATE TRIGGER IF NOT EXISTS trg_prompts_journal_unpin AFTER UPDATE OF pinned_position ON prompts
+        WHEN NEW.pinned_position IS NULL AND OLD.pinned_position IS NOT NULL
+        BEGIN
+            INSERT INTO changes (prompt_id, op) VALUES (NEW.id, 'unpin');
+        END;
+        ",
+        r"
+        CREATE TRIGGER IF NOT EXISTS trg_prompts_journal_delete AFTER DELETE ON prompts
+        BEGIN
+            INSERT INTO changes (prompt_id, op) VALUES (OLD.id, 'delete');
+        END;
+        ",
+    ];
+    for trigger in triggers {
+        sqlx::query(trigger)
+            .execute(pool)
+            .await
+            .map_err(|e| format!("Failed to create change journal trigger: {e}"))?;
+    }
+
+    compact_changes_if_needed(pool, CHANGE_JOURNAL_MAX_ENTRIES).await?;
+
     Ok(())
 }
 
@@ -153,6 +560,21 @@ async fn run_migrations(pool: &SqlitePool) -> Result<(), Box<dyn std::error::Err
     // titleカラムのNOT NULL制約を削除するマイグレーション
     migrate_title_to_optional(pool).await?;
     
+    // APIと共通の状態・公開範囲・ワークスペース
+    // （titleのマイグレーションはテーブルを作り直すため、その後に追加する）
+    add_column_if_not_exists(pool, "status", "TEXT NOT NULL DEFAULT 'active'").await?;
+    add_column_if_not_exists(pool, "visibility", "TEXT NOT NULL DEFAULT 'private'").await?;
+    add_column_if_not_exists(pool, "workspace_id", "TEXT").await?;
+    
+    // シークレットプロンプト（本文は暗号化済み）
+    add_column_if_not_exists(pool, "is_secret", "INTEGER NOT NULL DEFAULT 0").await?;
+    
+    // 機密プロンプト（ペースト後にクリップボードから消去）
+    add_column_if_not_exists(pool, "is_sensitive", "INTEGER NOT NULL DEFAULT 0").await?;
+    
+    // ペースト前の確認（プロンプト単位の設定）
+    add_column_if_not_exists(pool, "paste_confirmation", "TEXT NOT NULL DEFAULT 'default'").await?;
+    
     Ok(())
 }
 
@@ -292,28 +714,95 @@ async fn migrate_title_to_optional(pool: &SqlitePool) -> Result<(), Box<dyn std:
 }
 
 /// データベース接続プール取得（内部使用用）
-/// データベースが初期化されていない場合はパニック
-/// 
-/// # Safety
-/// この関数は内部使用のみ。新規コードでは`try_get_db_pool()`を使用すること。
-fn get_db_pool() -> &'static SqlitePool {
-    DB_POOL.get().expect("Database not initialized. Call init_database() first.")
+/// データベースが初期化されていない、またはロック中の場合はエラー
+fn get_db_pool() -> Result<SqlitePool, Box<dyn std::error::Error>> {
+    Ok(try_get_db_pool()?)
 }
 
 /// データベース接続プール取得（安全版）
 /// 
 /// # Returns
-/// * `Ok(&SqlitePool)` - データベースプールが初期化済みの場合
-/// * `Err(String)` - データベースが初期化されていない場合
-#[allow(dead_code)]
-pub fn try_get_db_pool() -> Result<&'static SqlitePool, String> {
-    DB_POOL.get()
-        .ok_or_else(|| "Database not initialized. Call init_database() first.".to_string())
+/// * `Ok(SqlitePool)` - データベースプールが利用可能な場合（内部で共有されるため複製は軽量）
+/// * `Err(String)` - データベースが初期化されていない、またはロック中の場合
+pub fn try_get_db_pool() -> Result<SqlitePool, String> {
+    let state = DB_POOL
+        .read()
+        .map_err(|_| "Database pool lock poisoned".to_string())?;
+    match &*state {
+        PoolState::Open(pool) => Ok(pool.clone()),
+        PoolState::Locked => Err("Database is locked. Unlock it with your passphrase first.".to_string()),
+        PoolState::Uninitialized => Err("Database not initialized. Call init_database() first.".to_string()),
+    }
 }
 
 /// プロンプト作成
 pub async fn create_prompt(request: CreatePromptRequest) -> Result<Prompt, Box<dyn std::error::Error>> {
-    let pool = get_db_pool();
+    let pool = &get_db_pool()?;
+    let prompt = insert_prompt(pool, request).await?;
+    
+    compact_changes_after_write(pool).await;
+    
+    Ok(prompt)
+}
+
+/// まとめて作成した結果
+#[derive(Debug)]
+pub struct InstalledPrompts {
+    pub prompts: Vec<Prompt>,
+    /// 既に他のプロンプトがピン留めされていたため、ピン留めしなかった位置
+    pub skipped_pins: Vec<u8>,
+}
+
+/// プロンプトをまとめて作成する（スターターライブラリの導入用）
+///
+/// 1つのトランザクションで作成し、途中で失敗した場合は1件も作成しない
+/// ピン留め位置を指定したプロンプトは、その位置が空いている場合のみピン留めする（空いていなかった位置は結果で返す）
+pub async fn install_prompts(
+    prompts: Vec<(CreatePromptRequest, Option<u8>)>,
+) -> Result<InstalledPrompts, Box<dyn std::error::Error>> {
+    let pool = &get_db_pool()?;
+    let mut tx = pool.begin().await?;
+    let mut installed = Vec::with_capacity(prompts.len());
+    let mut skipped_pins = Vec::new();
+    
+    for (request, position) in prompts {
+        let mut prompt = insert_prompt(&mut *tx, request).await?;
+        if let Some(position) = position {
+            if !(1..=MAX_PIN_POSITION).contains(&position) {
+                return Err(format!("Pin position must be between 1 and {MAX_PIN_POSITION}").into());
+            }
+            let pinned: Option<Prompt> = sqlx::query_as(
+                r"
+                UPDATE prompts SET pinned_position = $1, pinned_at = $2
+                WHERE id = $3 AND NOT EXISTS (SELECT 1 FROM prompts WHERE pinned_position = $1)
+                RETURNING *
+                ",
+            )
+            .bind(i32::from(position))
+            .bind(chrono::Utc::now())
+            .bind(&prompt.id)
+            .fetch_optional(&mut *tx)
+            .await?;
+            match pinned {
+                Some(pinned) => prompt = pinned,
+                None => skipped_pins.push(position),
+            }
+        }
+        installed.push(prompt);
+    }
+    
+    tx.commit().await?;
+    
+    compact_changes_after_write(pool).await;
+    
+    Ok(InstalledPrompts { prompts: installed, skipped_pins })
+}
+
+/// プロンプトを1件作成する（IDは新規に発行）
+async fn insert_prompt<'e, E>(executor: E, request: CreatePromptRequest) -> Result<Prompt, Box<dyn std::error::Error>>
+where
+    E: sqlx::Executor<'e, Database = sqlx::Sqlite>,
+{
     let id = Uuid::new_v4().to_string();
     let now = chrono::Utc::now();
     
@@ -325,8 +814,8 @@ pub async fn create_prompt(request: CreatePromptRequest) -> Result<Prompt, Box<d
     
     let prompt = sqlx::query_as::<_, Prompt>(
         r"
-        INSERT INTO prompts (id, title, content, tags, quick_access_key, created_at, updated_at, pinned_position, pinned_at)
-        VALUES ($1, $2, $3, $4, $5, $6, $7, NULL, NULL)
+        INSERT INTO prompts (id, title, content, tags, quick_access_key, created_at, updated_at, pinned_position, pinned_at, status, visibility, workspace_id, is_sensitive, paste_confirmation)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, NULL, NULL, $8, $9, $10, $11, $12)
         RETURNING *
         ",
     )
@@ -337,54 +826,134 @@ pub async fn create_prompt(request: CreatePromptRequest) -> Result<Prompt, Box<d
     .bind(&request.quick_access_key)
     .bind(now)
     .bind(now)
-    .fetch_one(pool)
+    .bind(request.status.unwrap_or_default())
+    .bind(request.visibility.unwrap_or_default())
+    .bind(&request.workspace_id)
+    .bind(request.is_sensitive.unwrap_or(false))
+    .bind(request.paste_confirmation.unwrap_or_default())
+    .fetch_one(executor)
     .await?;
     
     Ok(prompt)
 }
 
 /// プロンプト取得（ID指定）
+///
+/// シークレットプロンプトは復号して返す（保管庫がロック中ならエラー）
 pub async fn get_prompt(id: &str) -> Result<Option<Prompt>, Box<dyn std::error::Error>> {
-    let pool = get_db_pool();
+    let pool = &get_db_pool()?;
+    
+    let prompt = fetch_prompt(pool, id).await?;
     
-    let prompt = sqlx::query_as::<_, Prompt>("SELECT * FROM prompts WHERE id = $1")
+    match prompt {
+        Some(mut prompt) if prompt.is_secret => {
+            prompt.content = crate::vault::decrypt_content(&prompt.id, &prompt.content)?;
+            Ok(Some(prompt))
+        }
+        prompt => Ok(prompt),
+    }
+}
+
+/// プロンプトが1件以上あるかどうか
+pub async fn has_prompts() -> Result<bool, Box<dyn std::error::Error>> {
+    let pool = &get_db_pool()?;
+    Ok(sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM prompts)").fetch_one(pool).await?)
+}
+
+/// 前後の空白を除いて同じ本文のプロンプトを探す（重複の確認用）
+///
+/// シークレットプロンプトは本文を暗号化して保存しているため対象外
+pub async fn find_prompt_by_content(content: &str) -> Result<Option<String>, Box<dyn std::error::Error>> {
+    let pool = &get_db_pool()?;
+    Ok(fetch_prompt_id_by_content(pool, content).await?)
+}
+
+/// 比較する前に本文の前後から除く空白
+const CONTENT_TRIM_CHARS: [char; 4] = [' ', '\t', '\n', '\r'];
+
+async fn fetch_prompt_id_by_content(pool: &SqlitePool, content: &str) -> Result<Option<String>, sqlx::Error> {
+    sqlx::query_scalar(
+        "SELECT id FROM prompts WHERE TRIM(content, ' ' || char(9) || char(10) || char(13)) = $1 LIMIT 1"
+    )
+    .bind(content.trim_matches(CONTENT_TRIM_CHARS))
+    .fetch_optional(pool)
+    .await
+}
+
+/// プロンプト取得（一覧と同じく、シークレットの本文は保管庫のロック中は空）
+pub async fn get_prompt_redacted(id: &str) -> Result<Option<Prompt>, Box<dyn std::error::Error>> {
+    let pool = &get_db_pool()?;
+    let prompt = fetch_prompt(pool, id).await?;
+    Ok(redact_secrets(prompt.into_iter().collect()).pop())
+}
+
+/// プロンプト取得（保存されている値のまま、シークレットの本文も暗号化されたまま）
+async fn fetch_prompt(pool: &SqlitePool, id: &str) -> Result<Option<Prompt>, sqlx::Error> {
+    sqlx::query_as::<_, Prompt>("SELECT * FROM prompts WHERE id = $1")
         .bind(id)
         .fetch_optional(pool)
-        .await?;
-    
-    Ok(prompt)
+        .await
+}
+
+/// 一覧・検索結果のシークレットプロンプトの本文を差し替える
+///
+/// 保管庫のロック解除中は復号し、ロック中は空にする
+fn redact_secrets(prompts: Vec<Prompt>) -> Vec<Prompt> {
+    prompts
+        .into_iter()
+        .map(|mut prompt| {
+            if prompt.is_secret {
+                prompt.content = crate::vault::redacted_content(&prompt.id, &prompt.content);
+            }
+            prompt
+        })
+        .collect()
 }
 
 /// 全プロンプト取得
 pub async fn get_all_prompts() -> Result<Vec<Prompt>, Box<dyn std::error::Error>> {
-    let pool = get_db_pool();
+    list_prompts(&PromptFilter::default()).await
+}
+
+/// 条件を指定してプロンプト一覧を取得
+pub async fn list_prompts(filter: &PromptFilter) -> Result<Vec<Prompt>, Box<dyn std::error::Error>> {
+    let pool = &get_db_pool()?;
     
-    let prompts = sqlx::query_as::<_, Prompt>(
-        "SELECT * FROM prompts ORDER BY updated_at DESC"
-    )
-    .fetch_all(pool)
-    .await?;
+    let sql = format!("SELECT * FROM prompts WHERE {PROMPT_FILTER_SQL} ORDER BY updated_at DESC");
+    let prompts = sqlx::query_as::<_, Prompt>(&sql)
+        .bind(filter.status)
+        .bind(filter.visibility)
+        .bind(&filter.tag_id)
+        .bind(&filter.workspace_id)
+        .fetch_all(pool)
+        .await?;
     
-    Ok(prompts)
+    Ok(redact_secrets(prompts))
 }
 
 /// プロンプト検索（既存API - 後方互換性維持）
-pub async fn search_prompts(query: &str) -> Result<Vec<Prompt>, Box<dyn std::error::Error>> {
-    let pool = get_db_pool();
+pub async fn search_prompts(query: &str, filter: &PromptFilter) -> Result<Vec<Prompt>, Box<dyn std::error::Error>> {
+    let pool = &get_db_pool()?;
     let search_term = format!("%{query}%");
     
-    let prompts = sqlx::query_as::<_, Prompt>(
+    let sql = format!(
         r"
         SELECT * FROM prompts 
-        WHERE title LIKE $1 OR content LIKE $1 OR tags LIKE $1
+        WHERE {PROMPT_FILTER_SQL}
+        AND (title LIKE $5 OR (is_secret = 0 AND content LIKE $5) OR tags LIKE $5)
         ORDER BY updated_at DESC
-        ",
-    )
-    .bind(&search_term)
-    .fetch_all(pool)
-    .await?;
+        "
+    );
+    let prompts = sqlx::query_as::<_, Prompt>(&sql)
+        .bind(filter.status)
+        .bind(filter.visibility)
+        .bind(&filter.tag_id)
+        .bind(&filter.workspace_id)
+        .bind(&search_term)
+        .fetch_all(pool)
+        .await?;
     
-    Ok(prompts)
+    Ok(redact_secrets(prompts))
 }
 
 /// 高速検索（ショートカット用）
@@ -396,8 +965,8 @@ pub async fn search_prompts(query: &str) -> Result<Vec<Prompt>, Box<dyn std::err
 /// 
 /// @param query 検索クエリ
 /// @returns 検索結果（優先度順）
-pub async fn search_prompts_fast(query: &str) -> Result<Vec<Prompt>, Box<dyn std::error::Error>> {
-    let pool = get_db_pool();
+pub async fn search_prompts_fast(query: &str, filter: &PromptFilter) -> Result<Vec<Prompt>, Box<dyn std::error::Error>> {
+    let pool = &get_db_pool()?;
     
     if query.trim().is_empty() {
         return Ok(vec![]);
@@ -407,37 +976,43 @@ pub async fn search_prompts_fast(query: &str) -> Result<Vec<Prompt>, Box<dyn std
     let prefix_term = format!("{query}%");
     
     // 優先度付き検索クエリ
-    let prompts = sqlx::query_as::<_, Prompt>(
+    let sql = format!(
         r#"
         SELECT *, 
         CASE 
             -- 1. タイトル完全一致（最優先）
-            WHEN title = $3 THEN 1
+            WHEN title = $7 THEN 1
             -- 2. タイトル前方一致
-            WHEN title LIKE $2 THEN 2
+            WHEN title LIKE $6 THEN 2
             -- 3. タイトル部分一致
-            WHEN title LIKE $1 THEN 3
+            WHEN title LIKE $5 THEN 3
             -- 4. タグ完全一致
-            WHEN tags LIKE '%"' || $3 || '"%' THEN 4
+            WHEN tags LIKE '%"' || $7 || '"%' THEN 4
             -- 5. タグ部分一致
-            WHEN tags LIKE $1 THEN 5
-            -- 6. 内容一致
-            WHEN content LIKE $1 THEN 6
+            WHEN tags LIKE $5 THEN 5
+            -- 6. 内容一致（シークレットの本文は検索しない）
+            WHEN is_secret = 0 AND content LIKE $5 THEN 6
             ELSE 7
         END as priority
         FROM prompts 
-        WHERE title LIKE $1 OR content LIKE $1 OR tags LIKE $1
+        WHERE {PROMPT_FILTER_SQL}
+        AND (title LIKE $5 OR (is_secret = 0 AND content LIKE $5) OR tags LIKE $5)
         ORDER BY priority ASC, updated_at DESC
         LIMIT 20
-        "#,
-    )
-    .bind(&search_term)   // $1: 部分一致用
-    .bind(&prefix_term)   // $2: 前方一致用
-    .bind(query)          // $3: 完全一致用
-    .fetch_all(pool)
-    .await?;
+        "#
+    );
+    let prompts = sqlx::query_as::<_, Prompt>(&sql)
+        .bind(filter.status)          // $1-$4: 絞り込み条件
+        .bind(filter.visibility)
+        .bind(&filter.tag_id)
+        .bind(&filter.workspace_id)
+        .bind(&search_term)           // $5: 部分一致用
+        .bind(&prefix_term)           // $6: 前方一致用
+        .bind(query)                  // $7: 完全一致用
+        .fetch_all(pool)
+        .await?;
     
-    Ok(prompts)
+    Ok(redact_secrets(prompts))
 }
 
 /// プロンプト更新
@@ -445,17 +1020,21 @@ pub async fn update_prompt(
     id: &str,
     request: UpdatePromptRequest,
 ) -> Result<Option<Prompt>, Box<dyn std::error::Error>> {
-    let pool = get_db_pool();
+    let pool = &get_db_pool()?;
     
-    // 既存プロンプトを取得
-    let existing = match get_prompt(id).await? {
+    // 既存プロンプトを取得（シークレットの本文は暗号化されたまま）
+    let existing = match fetch_prompt(pool, id).await? {
         Some(prompt) => prompt,
         None => return Ok(None),
     };
     
     // 更新フィールドの決定
     let title = request.title.or(existing.title);
-    let content = request.content.unwrap_or(existing.content);
+    let content = match request.content {
+        Some(content) if existing.is_secret => crate::vault::encrypt_content(id, &content)?,
+        Some(content) => content,
+        None => existing.content,
+    };
     
     // タグの処理
     let tags_json = match request.tags {
@@ -466,13 +1045,21 @@ pub async fn update_prompt(
     // クイックアクセスキーの処理
     let quick_access_key = request.quick_access_key.or(existing.quick_access_key);
     
+    // 状態・公開範囲・ワークスペースの処理
+    let status = request.status.unwrap_or(existing.status);
+    let visibility = request.visibility.unwrap_or(existing.visibility);
+    let workspace_id = request.workspace_id.unwrap_or(existing.workspace_id);
+    let is_sensitive = request.is_sensitive.unwrap_or(existing.is_sensitive);
+    let paste_confirmation = request.paste_confirmation.unwrap_or(existing.paste_confirmation);
+    
     let now = chrono::Utc::now();
     
     let updated_prompt = sqlx::query_as::<_, Prompt>(
         r"
         UPDATE prompts 
-        SET title = $1, content = $2, tags = $3, quick_access_key = $4, updated_at = $5
-        WHERE id = $6
+        SET title = $1, content = $2, tags = $3, quick_access_key = $4, updated_at = $5,
+            status = $6, visibility = $7, workspace_id = $8, is_sensitive = $9, paste_confirmation = $10
+        WHERE id = $11
         RETURNING *
         ",
     )
@@ -481,39 +1068,167 @@ pub async fn update_prompt(
     .bind(&tags_json)
     .bind(&quick_access_key)
     .bind(now)
+    .bind(status)
+    .bind(visibility)
+    .bind(&workspace_id)
+    .bind(is_sensitive)
+    .bind(paste_confirmation)
     .bind(id)
     .fetch_one(pool)
     .await?;
     
-    Ok(Some(updated_prompt))
+    compact_changes_after_write(pool).await;
+    
+    Ok(redact_secrets(vec![updated_prompt]).pop())
+}
+
+/// 指定IDでプロンプトを保存する（外部ソースとの同期用）
+///
+/// 存在しない場合は作成し、存在する場合は内容を上書きする
+/// ピン留め状態は変更しない（`pin_prompt`/`unpin_prompt`を使用すること）
+/// 状態・公開範囲・ワークスペース・機密フラグ・ペースト前の確認は指定がなければ既存の値を維持する
+/// シークレットプロンプトは上書きしない（エラーを返す）
+pub async fn upsert_prompt(
+    id: &str,
+    request: CreatePromptRequest,
+) -> Result<Prompt, Box<dyn std::error::Error>> {
+    let pool = &get_db_pool()?;
+    let now = chrono::Utc::now();
+
+    if fetch_prompt(pool, id).await?.is_some_and(|prompt| prompt.is_secret) {
+        return Err("Secret prompts cannot be overwritten by sync".into());
+    }
+
+    // タグをJSON文字列に変換
+    let tags_json = match request.tags {
+        Some(tags) => Some(serde_json::to_string(&tags)?),
+        None => None,
+    };
+
+    let prompt = sqlx::query_as::<_, Prompt>(
+        r"
+        INSERT INTO prompts (id, title, content, tags, quick_access_key, created_at, updated_at, pinned_position, pinned_at, status, visibility, workspace_id, is_sensitive, paste_confirmation)
+        VALUES ($1, $2, $3, $4, $5, $6, $6, NULL, NULL, COALESCE($7, 'active'), COALESCE($8, 'private'), $9, COALESCE($10, 0), COALESCE($11, 'default'))
+        ON CONFLICT(id) DO UPDATE SET
+            title = excluded.title,
+            content = excluded.content,
+            tags = excluded.tags,
+            quick_access_key = excluded.quick_access_key,
+            updated_at = excluded.updated_at,
+            status = COALESCE($7, prompts.status),
+            visibility = COALESCE($8, prompts.visibility),
+            workspace_id = COALESCE($9, prompts.workspace_id),
+            is_sensitive = COALESCE($10, prompts.is_sensitive),
+            paste_confirmation = COALESCE($11, prompts.paste_confirmation)
+        RETURNING *
+        ",
+    )
+    .bind(id)
+    .bind(&request.title)
+    .bind(&request.content)
+    .bind(&tags_json)
+    .bind(&request.quick_access_key)
+    .bind(now)
+    .bind(request.status)
+    .bind(request.visibility)
+    .bind(&request.workspace_id)
+    .bind(request.is_sensitive)
+    .bind(request.paste_confirmation)
+    .fetch_one(pool)
+    .await?;
+
+    compact_changes_after_write(pool).await;
+
+    Ok(prompt)
+}
+
+/// プロンプトをシークレットにする・解除する（保管庫のロック解除が必要）
+///
+/// シークレットにすると本文を暗号化して保存し、解除すると平文に戻す
+pub async fn set_prompt_secret(id: &str, secret: bool) -> Result<Option<Prompt>, Box<dyn std::error::Error>> {
+    let pool = &get_db_pool()?;
+    
+    let existing = match fetch_prompt(pool, id).await? {
+        Some(prompt) => prompt,
+        None => return Ok(None),
+    };
+    if existing.is_secret == secret {
+        return Ok(redact_secrets(vec![existing]).pop());
+    }
+    
+    let content = if secret {
+        crate::vault::encrypt_content(id, &existing.content)?
+    } else {
+        crate::vault::decrypt_content(id, &existing.content)?
+    };
+    
+    let updated_prompt = sqlx::query_as::<_, Prompt>(
+        "UPDATE prompts SET content = $1, is_secret = $2, updated_at = $3 WHERE id = $4 RETURNING *",
+    )
+    .bind(&content)
+    .bind(secret)
+    .bind(chrono::Utc::now())
+    .bind(id)
+    .fetch_one(pool)
+    .await?;
+    
+    compact_changes_after_write(pool).await;
+    
+    Ok(redact_secrets(vec![updated_prompt]).pop())
 }
 
 /// プロンプト削除
 pub async fn delete_prompt(id: &str) -> Result<bool, Box<dyn std::error::Error>> {
-    let pool = get_db_pool();
+    let pool = get_db_pool()?;
+    delete_prompt_in(&pool, id).await
+}
+
+/// プロンプト削除（プール指定版、同期処理用）
+pub(crate) async fn delete_prompt_in(pool: &SqlitePool, id: &str) -> Result<bool, Box<dyn std::error::Error>> {
+    let mut tx = pool.begin().await?;
+    
+    // APIサーバーと同期済みの場合は削除を伝播するための墓標を残す
+    sqlx::query(
+        r"
+        INSERT OR REPLACE INTO api_sync_tombstones (remote_id, local_id, deleted_at)
+        SELECT remote_id, local_id, $2 FROM api_sync_links WHERE local_id = $1
+        ",
+    )
+    .bind(id)
+    .bind(chrono::Utc::now())
+    .execute(&mut *tx)
+    .await?;
+    sqlx::query("DELETE FROM api_sync_links WHERE local_id = $1")
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
     
     let result = sqlx::query("DELETE FROM prompts WHERE id = $1")
         .bind(id)
-        .execute(pool)
+        .execute(&mut *tx)
         .await?;
     
+    tx.commit().await?;
+    
+    compact_changes_after_write(pool).await;
+    
     Ok(result.rows_affected() > 0)
 }
 
 /// プロンプトをピン留めする
 /// 
-/// position: 1-10の範囲でピン留め位置を指定
+/// position: 1-36の範囲でピン留め位置を指定
 /// 既に同じ位置にピン留めされているプロンプトがある場合は、そのピン留めを解除
 pub async fn pin_prompt(prompt_id: &str, position: u8) -> Result<(), Box<dyn std::error::Error>> {
-    if !(1..=10).contains(&position) {
-        return Err("Pin position must be between 1 and 10".into());
+    if !(1..=MAX_PIN_POSITION).contains(&position) {
+        return Err(format!("Pin position must be between 1 and {MAX_PIN_POSITION}").into());
     }
     
-    let pool = get_db_pool();
+    let pool = &get_db_pool()?;
     let now = chrono::Utc::now();
     
     // プロンプトが存在するかチェック
-    let existing = get_prompt(prompt_id).await?;
+    let existing = fetch_prompt(pool, prompt_id).await?;
     if existing.is_none() {
         return Err("Prompt not found".into());
     }
@@ -546,18 +1261,20 @@ pub async fn pin_prompt(prompt_id: &str, position: u8) -> Result<(), Box<dyn std
     // トランザクション確定
     tx.commit().await?;
     
+    compact_changes_after_write(pool).await;
+    
     Ok(())
 }
 
 /// プロンプトのピン留めを解除する
 /// 
-/// position: 解除するピン留め位置（1-10）
+/// position: 解除するピン留め位置（1-36）
 pub async fn unpin_prompt(position: u8) -> Result<(), Box<dyn std::error::Error>> {
-    if !(1..=10).contains(&position) {
-        return Err("Pin position must be between 1 and 10".into());
+    if !(1..=MAX_PIN_POSITION).contains(&position) {
+        return Err(format!("Pin position must be between 1 and {MAX_PIN_POSITION}").into());
     }
     
-    let pool = get_db_pool();
+    let pool = &get_db_pool()?;
     
     let result = sqlx::query(
         "UPDATE prompts SET pinned_position = NULL, pinned_at = NULL WHERE pinned_position = $1"
@@ -570,6 +1287,8 @@ pub async fn unpin_prompt(position: u8) -> Result<(), Box<dyn std::error::Error>
         return Err("No prompt found at the specified pin position".into());
     }
     
+    compact_changes_after_write(pool).await;
+    
     Ok(())
 }
 
@@ -577,7 +1296,7 @@ pub async fn unpin_prompt(position: u8) -> Result<(), Box<dyn std::error::Error>
 /// 
 /// ピン留め位置の昇順で返す（1, 2, 3, ...）
 pub async fn get_pinned_prompts() -> Result<Vec<Prompt>, Box<dyn std::error::Error>> {
-    let pool = get_db_pool();
+    let pool = &get_db_pool()?;
     
     let prompts = sqlx::query_as::<_, Prompt>(
         "SELECT * FROM prompts WHERE pinned_position IS NOT NULL ORDER BY pinned_position ASC"
@@ -585,28 +1304,156 @@ pub async fn get_pinned_prompts() -> Result<Vec<Prompt>, Box<dyn std::error::Err
     .fetch_all(pool)
     .await?;
     
-    Ok(prompts)
+    Ok(redact_secrets(prompts))
 }
 
 /// 指定されたピン留め位置のプロンプトをクリップボードにコピーする
 /// 
-/// position: コピーするピン留め位置（1-10）
-/// プロンプトの内容をクリップボードにコピーし、成功/失敗を返す
-pub async fn get_pinned_prompt_content(position: u8) -> Result<Option<String>, Box<dyn std::error::Error>> {
-    if !(1..=10).contains(&position) {
-        return Err("Pin position must be between 1 and 10".into());
+/// position: コピーするピン留め位置（1-36）
+/// プロンプトの内容と機密かどうかを返す
+pub async fn get_pinned_prompt_content(position: u8) -> Result<Option<PinnedContent>, Box<dyn std::error::Error>> {
+    if !(1..=MAX_PIN_POSITION).contains(&position) {
+        return Err(format!("Pin position must be between 1 and {MAX_PIN_POSITION}").into());
     }
     
-    let pool = get_db_pool();
+    let pool = &get_db_pool()?;
     
-    let prompt: Option<(String,)> = sqlx::query_as(
-        "SELECT content FROM prompts WHERE pinned_position = $1"
+    let prompt: Option<(String, String, bool, bool, PasteConfirmation)> = sqlx::query_as(
+        "SELECT id, content, is_secret, is_sensitive, paste_confirmation FROM prompts WHERE pinned_position = $1"
     )
     .bind(i32::from(position))
     .fetch_optional(pool)
     .await?;
     
-    Ok(prompt.map(|(content,)| content))
+    prompt.map(paste_content).transpose()
+}
+
+/// 指定IDのプロンプトのペースト用の内容を取得する（ペースト履歴から再度ペーストする場合）
+pub async fn get_prompt_paste_content(id: &str) -> Result<Option<PinnedContent>, Box<dyn std::error::Error>> {
+    let pool = &get_db_pool()?;
+    
+    let prompt: Option<(String, String, bool, bool, PasteConfirmation)> = sqlx::query_as(
+        "SELECT id, content, is_secret, is_sensitive, paste_confirmation FROM prompts WHERE id = $1"
+    )
+    .bind(id)
+    .fetch_optional(pool)
+    .await?;
+    
+    prompt.map(paste_content).transpose()
+}
+
+/// ペースト用の内容に変換（シークレットプロンプトは保管庫で復号）
+fn paste_content(
+    (id, content, is_secret, is_sensitive, confirmation): (String, String, bool, bool, PasteConfirmation),
+) -> Result<PinnedContent, Box<dyn std::error::Error>> {
+    if is_secret {
+        let content = crate::vault::decrypt_content(&id, &content)?;
+        return Ok(PinnedContent { id, content, sensitive: true, confirmation });
+    }
+    Ok(PinnedContent { id, content, sensitive: is_sensitive, confirmation })
+}
+
+/// 指定シーケンス番号より後の変更を取得する
+///
+/// since: 前回取得した`next_seq`（初回は0）
+/// limit: 最大取得件数（1-1000、省略時は1000）
+pub async fn get_changes_since(since: i64, limit: Option<u32>) -> Result<ChangeSet, Box<dyn std::error::Error>> {
+    let pool = get_db_pool()?;
+    fetch_changes_since(&pool, since, limit).await
+}
+
+/// 変更ジャーナルの取得（プール指定版）
+pub(crate) async fn fetch_changes_since(
+    pool: &SqlitePool,
+    since: i64,
+    limit: Option<u32>,
+) -> Result<ChangeSet, Box<dyn std::error::Error>> {
+    let limit = limit.unwrap_or(CHANGE_JOURNAL_MAX_PAGE).clamp(1, CHANGE_JOURNAL_MAX_PAGE);
+
+    let purged_through: i64 = sqlx::query_scalar("SELECT purged_through FROM change_journal_meta WHERE id = 1")
+        .fetch_optional(pool)
+        .await?
+        .unwrap_or(0);
+
+    // 1件多く取得して続きの有無を判定
+    let mut changes = sqlx::query_as::<_, Change>(
+        "SELECT seq, prompt_id, op, changed_at FROM changes WHERE seq > $1 ORDER BY seq ASC LIMIT $2"
+    )
+    .bind(since)
+    .bind(i64::from(limit) + 1)
+    .fetch_all(pool)
+    .await?;
+
+    let has_more = changes.len() > limit as usize;
+    changes.truncate(limit as usize);
+
+    let next_seq = match changes.last() {
+        Some(change) => change.seq,
+        None => since.max(purged_through),
+    };
+
+    Ok(ChangeSet {
+        changes,
+        next_seq,
+        has_more,
+        reset_required: since < purged_through,
+    })
+}
+
+/// 変更ジャーナルが上限を超えている場合にコンパクションする
+///
+/// 1. プロンプトごとに最新のエントリだけを残す（最終状態は失われない）
+/// 2. それでも上限を超える場合は古いエントリを上限の半分まで破棄し、
+///    破棄した位置を記録する（それより古いカーソルには`reset_required`を返す）
+pub(crate) async fn compact_changes_if_needed(
+    pool: &SqlitePool,
+    max_entries: i64,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM changes")
+        .fetch_one(pool)
+        .await?;
+    if count <= max_entries {
+        return Ok(());
+    }
+
+    let mut tx = pool.begin().await?;
+
+    sqlx::query("DELETE FROM changes WHERE seq NOT IN (SELECT MAX(seq) FROM changes GROUP BY prompt_id)")
+        .execute(&mut *tx)
+        .await?;
+
+    let remaining: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM changes")
+        .fetch_one(&mut *tx)
+        .await?;
+    if remaining > max_entries {
+        let purge_count = remaining - max_entries / 2;
+        let purged_through: i64 = sqlx::query_scalar(
+            "SELECT seq FROM changes ORDER BY seq ASC LIMIT 1 OFFSET $1"
+        )
+        .bind(purge_count - 1)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        sqlx::query("DELETE FROM changes WHERE seq <= $1")
+            .bind(purged_through)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("UPDATE change_journal_meta SET purged_through = $1 WHERE id = 1")
+            .bind(purged_through)
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    tx.commit().await?;
+
+    Ok(())
+}
+
+/// 書き込み後のコンパクション（失敗しても書き込み自体は成功扱い）
+async fn compact_changes_after_write(pool: &SqlitePool) {
+    if let Err(e) = compact_changes_if_needed(pool, CHANGE_JOURNAL_MAX_ENTRIES).await {
+        eprintln!("Failed to compact change journal: {e}");
+    }
 }
 
 
@@ -765,6 +1612,23 @@ mod tests {
         assert_eq!(count.0, 20);
     }
 
+    #[tokio::test]
+    async fn test_fetch_prompt_id_by_content() {
+        let pool = create_test_pool().await;
+        sqlx::query("INSERT INTO prompts (id, title, content, tags) VALUES ($1, $2, $3, $4)")
+            .bind("existing")
+            .bind("Title")
+            .bind("Summarize this text\n")
+            .bind("[]")
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let found = fetch_prompt_id_by_content(&pool, "  Summarize this text\r\n").await.unwrap();
+        assert_eq!(found.as_deref(), Some("existing"));
+        assert_eq!(fetch_prompt_id_by_content(&pool, "Summarize this").await.unwrap(), None);
+    }
+
     #[tokio::test]
     async fn test_pin_prompt_functionality() {
         let pool = create_test_pool().await;
@@ -898,4 +1762,234 @@ mod tests {
             }
         }
     }
-}
\ No newline at end of file
+    
+    /// スキーマ全体（変更ジャーナル含む）を初期化したテスト用プール
+    async fn create_journal_test_pool() -> SqlitePool {
+        let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+        init_database_schema(&pool).await.unwrap();
+        pool
+    }
+    
+    async fn insert_test_prompt(pool: &SqlitePool, id: &str) {
+        sqlx::query("INSERT INTO prompts (id, content, created_at, updated_at) VALUES ($1, 'x', CURRENT_TIMESTAMP, CURRENT_TIMESTAMP)")
+            .bind(id)
+            .execute(pool)
+            .await
+            .unwrap();
+    }
+    
+    async fn execute(pool: &SqlitePool, sql: &str) {
+        sqlx::query(sql).execute(pool).await.unwrap();
+    }
+    
+    #[tokio::test]
+    async fn test_change_journal_records_all_operations() {
+        let pool = create_journal_test_pool().await;
+        
+        insert_test_prompt(&pool, "p1").await;
+        execute(&pool, "UPDATE prompts SET content = 'b' WHERE id = 'p1'").await;
+        // 内容が変わらない更新は記録しない
+        execute(&pool, "UPDATE prompts SET content = 'b' WHERE id = 'p1'").await;
+        execute(&pool, "UPDATE prompts SET pinned_position = 1 WHERE id = 'p1'").await;
+        execute(&pool, "UPDATE prompts SET pinned_position = NULL WHERE id = 'p1'").await;
+        execute(&pool, "DELETE FROM prompts WHERE id = 'p1'").await;
+        
+        let change_set = fetch_changes_since(&pool, 0, None).await.unwrap();
+        let ops: Vec<&str> = change_set.changes.iter().map(|c| c.op.as_str()).collect();
+        assert_eq!(ops, vec!["create", "update", "pin", "unpin", "delete"]);
+        assert!(change_set.changes.windows(2).all(|w| w[0].seq < w[1].seq));
+        assert_eq!(change_set.next_seq, change_set.changes.last().unwrap().seq);
+        assert!(!change_set.has_more);
+        assert!(!change_set.reset_required);
+    }
+    
+    #[tokio::test]
+    async fn test_get_changes_since_pagination() {
+        let pool = create_journal_test_pool().await;
+        for i in 0..5 {
+            insert_test_prompt(&pool, &format!("p{i}")).await;
+        }
+        
+        let first = fetch_changes_since(&pool, 0, Some(3)).await.unwrap();
+        assert_eq!(first.changes.len(), 3);
+        assert!(first.has_more);
+        
+        let second = fetch_changes_since(&pool, first.next_seq, Some(3)).await.unwrap();
+        assert_eq!(second.changes.len(), 2);
+        assert!(!second.has_more);
+        
+        let empty = fetch_changes_since(&pool, second.next_seq, Some(3)).await.unwrap();
+        assert!(empty.changes.is_empty());
+        assert_eq!(empty.next_seq, second.next_seq);
+    }
+    
+    #[tokio::test]
+    async fn test_compaction_keeps_latest_change_per_prompt() {
+        let pool = create_journal_test_pool().await;
+        insert_test_prompt(&pool, "p1").await;
+        for i in 1..=5 {
+            sqlx::query("UPDATE prompts SET content = $1 WHERE id = 'p1'")
+                .bind(format!("v{i}"))
+                .execute(&pool)
+                .await
+                .unwrap();
+        }
+        insert_test_prompt(&pool, "p2").await;
+        execute(&pool, "DELETE FROM prompts WHERE id = 'p2'").await;
+        
+        compact_changes_if_needed(&pool, 4).await.unwrap();
+        
+        let change_set = fetch_changes_since(&pool, 0, None).await.unwrap();
+        let ops: Vec<(&str, &str)> = change_set
+            .changes
+            .iter()
+            .map(|c| (c.prompt_id.as_str(), c.op.as_str()))
+            .collect();
+        assert_eq!(ops, vec![("p1", "update"), ("p2", "delete")]);
+        assert!(!change_set.reset_required);
+    }
+    
+    #[tokio::test]
+    async fn test_compaction_purges_old_entries_and_requires_reset() {
+        let pool = create_journal_test_pool().await;
+        for i in 0..10 {
+            insert_test_prompt(&pool, &format!("p{i}")).await;
+        }
+        
+        compact_changes_if_needed(&pool, 6).await.unwrap();
+        
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM changes").fetch_one(&pool).await.unwrap();
+        assert_eq!(count, 3);
+        
+        let stale = fetch_changes_since(&pool, 0, None).await.unwrap();
+        assert!(stale.reset_required);
+        assert_eq!(stale.changes.len(), 3);
+        
+        let current = fetch_changes_since(&pool, stale.next_seq, None).await.unwrap();
+        assert!(!current.reset_required);
+        assert!(current.changes.is_empty());
+    }
+    
+    async fn fetch_filtered(pool: &SqlitePool, filter: &PromptFilter) -> Vec<String> {
+        let sql = format!("SELECT * FROM prompts WHERE {PROMPT_FILTER_SQL} ORDER BY id");
+        sqlx::query_as::<_, Prompt>(&sql)
+            .bind(filter.status)
+            .bind(filter.visibility)
+            .bind(&filter.tag_id)
+            .bind(&filter.workspace_id)
+            .fetch_all(pool)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|prompt| prompt.id)
+            .collect()
+    }
+    
+    #[tokio::test]
+    async fn test_prompt_filter_matches_api_fields() {
+        let pool = create_journal_test_pool().await;
+        let rows = [
+            ("p1", r#"["review","code"]"#, "active", "private", Some("wsp_team")),
+            ("p2", r#"["code"]"#, "draft", "shared", Some("wsp_team")),
+            ("p3", r#"["reviewer"]"#, "archived", "public", None),
+        ];
+        for (id, tags, status, visibility, workspace_id) in rows {
+            sqlx::query(
+                "INSERT INTO prompts (id, content, tags, created_at, updated_at, status, visibility, workspace_id) VALUES ($1, 'x', $2, CURRENT_TIMESTAMP, CURRENT_TIMESTAMP, $3, $4, $5)"
+            )
+            .bind(id)
+            .bind(tags)
+            .bind(status)
+            .bind(visibility)
+            .bind(workspace_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+        
+        assert_eq!(fetch_filtered(&pool, &PromptFilter::default()).await, vec!["p1", "p2", "p3"]);
+        
+        let by_status = PromptFilter { status: Some(PromptStatus::Draft), ..Default::default() };
+        assert_eq!(fetch_filtered(&pool, &by_status).await, vec!["p2"]);
+        
+        let by_visibility = PromptFilter { visibility: Some(PromptVisibility::Public), ..Default::default() };
+        assert_eq!(fetch_filtered(&pool, &by_visibility).await, vec!["p3"]);
+        
+        // タグは部分一致ではなく完全一致
+        let by_tag = PromptFilter { tag_id: Some("review".to_string()), ..Default::default() };
+        assert_eq!(fetch_filtered(&pool, &by_tag).await, vec!["p1"]);
+        
+        let combined = PromptFilter {
+            tag_id: Some("code".to_string()),
+            workspace_id: Some("wsp_team".to_string()),
+            status: Some(PromptStatus::Active),
+            ..Default::default()
+        };
+        assert_eq!(fetch_filtered(&pool, &combined).await, vec!["p1"]);
+    }
+    
+    #[test]
+    fn test_update_request_workspace_is_tri_state() {
+        let parse = |json: &str| serde_json::from_str::<UpdatePromptRequest>(json).unwrap().workspace_id;
+        assert_eq!(parse(r#"{"title":"t"}"#), None);
+        assert_eq!(parse(r#"{"workspaceId":null}"#), Some(None));
+        assert_eq!(parse(r#"{"workspaceId":"wsp_team"}"#), Some(Some("wsp_team".to_string())));
+    }
+    
+    #[tokio::test]
+    async fn test_migration_adds_status_visibility_and_workspace() {
+        // 旧スキーマのデータベースに既存データがある状態からマイグレーション
+        let pool = create_test_pool().await;
+        sqlx::query("INSERT INTO prompts (id, title, content, tags) VALUES ('old', 'Old', 'content', '[]')")
+            .execute(&pool)
+            .await
+            .unwrap();
+        
+        init_database_schema(&pool).await.unwrap();
+        
+        let prompt = sqlx::query_as::<_, Prompt>("SELECT * FROM prompts WHERE id = 'old'")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(prompt.status, PromptStatus::Active);
+        assert_eq!(prompt.visibility, PromptVisibility::Private);
+        assert_eq!(prompt.workspace_id, None);
+        assert!(!prompt.is_secret);
+        assert!(!prompt.is_sensitive);
+    }
+    
+    #[tokio::test]
+    async fn test_secret_prompts_are_redacted_and_not_searched_by_content() {
+        let pool = create_journal_test_pool().await;
+        execute(&pool, "INSERT INTO prompts (id, title, content, created_at, updated_at, is_secret) VALUES ('s1', 'Token', 'vault:v1:c2VjcmV0LXRva2Vu', CURRENT_TIMESTAMP, CURRENT_TIMESTAMP, 1)").await;
+        execute(&pool, "INSERT INTO prompts (id, title, content, created_at, updated_at) VALUES ('p1', 'Plain', 'vault:v1 is mentioned here', CURRENT_TIMESTAMP, CURRENT_TIMESTAMP)").await;
+        
+        let matched: Vec<String> = sqlx::query_scalar(
+            "SELECT id FROM prompts WHERE title LIKE $1 OR (is_secret = 0 AND content LIKE $1) OR tags LIKE $1 ORDER BY id"
+        )
+        .bind("%vault:v1%")
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        assert_eq!(matched, vec!["p1"]);
+        
+        // 保管庫がロック中の場合、一覧では本文を返さない
+        let prompts = sqlx::query_as::<_, Prompt>("SELECT * FROM prompts ORDER BY id")
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+        let prompts = redact_secrets(prompts);
+        assert_eq!(prompts[0].content, "vault:v1 is mentioned here");
+        assert_eq!(prompts[1].content, "");
+        assert!(prompts[1].is_secret);
+    }
+    
+    #[test]
+    fn test_workspace_id_format() {
+        assert!(is_valid_workspace_id("wsp_demo"));
+        assert!(is_valid_workspace_id("wsp_Team42"));
+        assert!(!is_valid_workspace_id("wsp_"));
+        assert!(!is_valid_workspace_id("demo"));
+        assert!(!is_valid_workspace_id("wsp_team-1"));
+    }
+}
@@ -0,0 +1,736 @@
+/*!
+ * データベースの暗号化（保存時の暗号化）
+ *
+ * SQLCipherでデータベースファイル全体（本文・タイトル・タグを含む）を暗号化する
+ *
+ * - 鍵: ユーザーのパスフレーズからArgon2idで導出し、接続プールの接続設定（メモリ上）にのみ保持
+ * - 有効化・無効化・パスフレーズ変更: `sqlcipher_export`で別ファイルに書き出してから置き換え
+ * - ロック: 接続プールを閉じて鍵を破棄（ロック解除にはパスフレーズが必要）
+ * - バックアップ: データベースファイルをそのまま複製するため、暗号化中は暗号化されたまま保存される。
+ *   有効化した時点で残っている平文のバックアップも暗号化する（できない場合は削除）。
+ *   復元時は現在の鍵で開けるバックアップのみ受け付ける
+ * - 起動時はデータベースファイルのヘッダーで暗号化されているかを判断し、設定をファイルに合わせる
+ *   （置き換えと設定の保存の間で終了しても、ファイルと設定が食い違ったままにならない）
+ *
+ * ソルトは一度生成したら変更しない（過去のバックアップも当時のパスフレーズで開けるようにするため）
+ */
+use serde::{Deserialize, Serialize};
+use sqlx::ConnectOptions;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Emitter};
+use zeroize::Zeroizing;
+
+use crate::database;
+use crate::environment::Environment;
+
+/// 暗号化設定ファイル名（アプリデータディレクトリに保存）
+const CONFIG_FILENAME: &str = "encryption.json";
+
+/// 鍵長（SQLCipherのraw key）
+const KEY_LEN: usize = 32;
+
+/// ソルト長
+const SALT_LEN: usize = 16;
+
+/// パスフレーズの最小文字数
+const MIN_PASSPHRASE_CHARS: usize = 8;
+
+/// パスフレーズの最大バイト数
+const MAX_PASSPHRASE_BYTES: usize = 1024;
+
+/// 平文SQLiteファイルの先頭
+const SQLITE_HEADER: &[u8; 16] = b"SQLite format 3\0";
+
+/// バックアップの保存先（アプリデータディレクトリ内、updaterモジュールと同じ）
+const BACKUP_DIRNAME: &str = "backups";
+
+/// Argon2idのパラメータ
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KdfParams {
+    /// メモリコスト（KiB）
+    pub memory_kib: u32,
+    pub iterations: u32,
+    pub parallelism: u32,
+}
+
+impl Default for KdfParams {
+    fn default() -> Self {
+        Self {
+            memory_kib: 64 * 1024,
+            iterations: 3,
+            parallelism: 1,
+        }
+    }
+}
+
+/// 暗号化設定（永続化）
+///
+/// 鍵そのものは保存しない
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EncryptionConfig {
+    pub enabled: bool,
+    /// Argon2のソルト（Base64）
+    pub salt: Option<String>,
+    #[serde(default)]
+    pub kdf: KdfParams,
+}
+
+impl EncryptionConfig {
+    fn config_path() -> Result<PathBuf, String> {
+        let env = Environment::current();
+        let data_dir = env.data_dir().map_err(|e| e.to_string())?;
+        Ok(data_dir.join(CONFIG_FILENAME))
+    }
+
+    /// 設定ファイルから読み込み（存在しない場合はデフォルト）
+    pub fn load() -> Result<Self, String> {
+        let path = Self::config_path()?;
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(&path).map_err(|e| e.to_string())?;
+        serde_json::from_str(&content).map_err(|e| format!("Invalid encryption config: {e}"))
+    }
+
+    /// 設定ファイルへ保存
+    pub fn save(&self) -> Result<(), String> {
+        let content = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        std::fs::write(Self::config_path()?, content).map_err(|e| format!("Failed to save encryption config: {e}"))
+    }
+
+    /// 保存済みのソルトを取得
+    fn salt(&self) -> Result<Vec<u8>, String> {
+        use base64::{engine::general_purpose, Engine as _};
+        let salt = self.salt.as_deref().ok_or("Encryption salt is missing")?;
+        general_purpose::STANDARD
+            .decode(salt)
+            .map_err(|_| "Invalid salt in encryption config".to_string())
+    }
+
+    /// ソルトを取得（未作成なら生成）
+    fn salt_or_generate(&mut self) -> Result<Vec<u8>, String> {
+        if self.salt.is_some() {
+            return self.salt();
+        }
+        use base64::{engine::general_purpose, Engine as _};
+        let salt = generate_salt();
+        self.salt = Some(general_purpose::STANDARD.encode(&salt));
+        Ok(salt)
+    }
+}
+
+/// データベースの暗号化が有効かどうか
+pub fn is_encryption_enabled() -> Result<bool, String> {
+    EncryptionConfig::load().map(|config| config.enabled)
+}
+
+/// データベースファイルが暗号化されているかをヘッダーで判断し、設定をファイルに合わせる（起動時に呼ぶ）
+pub(crate) fn detect_database_encryption(db_path: &Path) -> Result<bool, String> {
+    let encrypted = match is_encrypted_file(db_path) {
+        Ok(encrypted) => encrypted,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => false,
+        Err(e) => return Err(format!("Failed to read database header: {e}")),
+    };
+
+    let mut config = EncryptionConfig::load()?;
+    if config.enabled != encrypted {
+        if encrypted && config.salt.is_none() {
+            return Err("Database is encrypted but the encryption salt is missing".to_string());
+        }
+        eprintln!("Encryption config did not match the database file. Setting enabled = {encrypted}");
+        config.enabled = encrypted;
+        config.save()?;
+    }
+    Ok(encrypted)
+}
+
+/// パスフレーズからArgon2idで32バイトの鍵を導出（シークレットプロンプトの保管庫でも使用）
+pub(crate) fn derive_key_bytes(passphrase: &str, salt: &[u8], params: KdfParams) -> Result<Zeroizing<[u8; KEY_LEN]>, String> {
+    let params = argon2::Params::new(params.memory_kib, params.iterations, params.parallelism, Some(KEY_LEN))
+        .map_err(|e| format!("Invalid key derivation parameters: {e}"))?;
+    let argon2 = argon2::Argon2::new(argon2::Algorithm::Argon2id, argon2::Version::V0x13, params);
+    let mut key = Zeroizing::new([0u8; KEY_LEN]);
+    argon2
+        .hash_password_into(passphrase.as_bytes(), salt, key.as_mut())
+        .map_err(|e| format!("Failed to derive key: {e}"))?;
+    Ok(key)
+}
+
+/// ランダムなソルトを生成
+pub(crate) fn generate_salt() -> Vec<u8> {
+    use rand::RngCore;
+    let mut salt = vec![0u8; SALT_LEN];
+    rand::rngs::OsRng.fill_bytes(&mut salt);
+    salt
+}
+
+/// パスフレーズから導出した鍵（破棄時にゼロクリア）
+pub(crate) struct DatabaseKey(Zeroizing<[u8; KEY_LEN]>);
+
+impl DatabaseKey {
+    /// Argon2idで鍵を導出
+    pub(crate) fn derive(passphrase: &str, salt: &[u8], params: KdfParams) -> Result<Self, String> {
+        derive_key_bytes(passphrase, salt, params).map(Self)
+    }
+
+    /// SQLCipherのraw key表記（`x'...'`、SQLCipher側の鍵導出を省略）
+    fn raw_key(&self) -> Zeroizing<String> {
+        use std::fmt::Write;
+        let mut hex = Zeroizing::new(String::with_capacity(KEY_LEN * 2 + 3));
+        hex.push_str("x'");
+        for byte in self.0.iter() {
+            let _ = write!(hex, "{byte:02X}");
+        }
+        hex.push('\'');
+        hex
+    }
+
+    /// `PRAGMA key`に渡す値
+    pub(crate) fn pragma_value(&self) -> Zeroizing<String> {
+        Zeroizing::new(format!("\"{}\"", self.raw_key().as_str()))
+    }
+}
+
+/// 暗号化されたファイルかどうか（平文SQLiteのヘッダーがない）
+pub(crate) fn is_encrypted_file(path: &Path) -> std::io::Result<bool> {
+    use std::io::Read;
+    let mut header = [0u8; 16];
+    let mut file = std::fs::File::open(path)?;
+    match file.read_exact(&mut header) {
+        Ok(()) => Ok(&header != SQLITE_HEADER),
+        // 空のファイルは未作成のデータベース
+        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+/// 鍵の誤りによるエラーかどうか（SQLITE_NOTADB）
+fn is_wrong_key(error: &(dyn std::error::Error + 'static)) -> bool {
+    error
+        .downcast_ref::<sqlx::Error>()
+        .and_then(|e| e.as_database_error())
+        .and_then(|e| e.code())
+        .is_some_and(|code| code == "26")
+}
+
+/// 鍵でデータベースを開けるか確認
+async fn verify_key(db_path: &Path, key: &DatabaseKey) -> Result<(), String> {
+    let mut conn = sqlx::sqlite::SqliteConnectOptions::new()
+        .filename(db_path)
+        .pragma("key", key.pragma_value().to_string())
+        .connect()
+        .await
+        .map_err(|e| wrong_key_message(&e))?;
+    let result = sqlx::query("SELECT COUNT(*) FROM sqlite_master")
+        .execute(&mut conn)
+        .await
+        .map(|_| ())
+        .map_err(|e| wrong_key_message(&e));
+    let _ = sqlx::Connection::close(conn).await;
+    result
+}
+
+fn wrong_key_message(error: &sqlx::Error) -> String {
+    if is_wrong_key(error) {
+        "Incorrect passphrase".to_string()
+    } else {
+        error.to_string()
+    }
+}
+
+/// データベースファイルを別の鍵で別ファイルに書き出す（鍵が`None`なら平文）
+///
+/// 接続プールの接続ではなく専用の接続で実行する
+async fn export_database(
+    source: &Path,
+    source_key: Option<&DatabaseKey>,
+    dest: &Path,
+    dest_key: Option<&DatabaseKey>,
+) -> Result<(), String> {
+    if dest.exists() {
+        std::fs::remove_file(dest).map_err(|e| e.to_string())?;
+    }
+    let mut options = sqlx::sqlite::SqliteConnectOptions::new()
+        .filename(source)
+        .create_if_missing(true);
+    if let Some(key) = source_key {
+        options = options.pragma("key", key.pragma_value().to_string());
+    }
+    let mut conn = options.connect().await.map_err(|e| wrong_key_message(&e))?;
+
+    let attach_key = dest_key.map(DatabaseKey::raw_key).unwrap_or_default();
+    let result = async {
+        sqlx::query("ATTACH DATABASE $1 AS export KEY $2")
+            .bind(dest.to_string_lossy().to_string())
+            .bind(attach_key.as_str())
+            .execute(&mut conn)
+            .await
+            .map_err(|e| format!("Failed to create export database: {e}"))?;
+        let exported = sqlx::raw_sql("SELECT sqlcipher_export('export')")
+            .execute(&mut conn)
+            .await
+            .map(|_| ())
+            .map_err(|e| format!("Failed to export database: {e}"));
+        sqlx::raw_sql("DETACH DATABASE export")
+            .execute(&mut conn)
+            .await
+            .map_err(|e| e.to_string())?;
+        exported
+    }
+    .await;
+    let _ = sqlx::Connection::close(conn).await;
+    result
+}
+
+/// データベースを新しい鍵で書き出して置き換え、接続プールを開き直す
+///
+/// 置き換え前に失敗した場合は元のデータベースのまま。設定（`next`）は置き換えた後に保存する
+/// （保存に失敗しても、次回起動時にファイルのヘッダーから復元される）
+async fn reencrypt(
+    current_key: Option<&DatabaseKey>,
+    new_key: Option<&DatabaseKey>,
+    next: &EncryptionConfig,
+) -> Result<(), String> {
+    let db_path = database::database_path().map_err(|e| e.to_string())?;
+    let temp_path = db_path.with_extension("db.rekey");
+
+    // 書き出し中の書き込みを防ぐためプールを閉じる（WALはチェックポイントされ削除される）
+    let pool = database::take_pool_for_lock().map_err(|e| e.to_string())?;
+    pool.close().await;
+
+    let swapped = async {
+        export_database(&db_path, current_key, &temp_path, new_key).await?;
+        std::fs::rename(&temp_path, &db_path).map_err(|e| format!("Failed to replace database: {e}"))
+    }
+    .await;
+    if swapped.is_err() {
+        let _ = std::fs::remove_file(&temp_path);
+    } else {
+        for suffix in ["-wal", "-shm"] {
+            let mut side_file = db_path.clone().into_os_string();
+            side_file.push(suffix);
+            let _ = std::fs::remove_file(side_file);
+        }
+    }
+
+    let key = if swapped.is_ok() { new_key } else { current_key };
+    let pragma = key.map(DatabaseKey::pragma_value);
+    let pool = database::open_database(&db_path, pragma.as_ref().map(|key| key.as_str()))
+        .await
+        .map_err(|e| format!("Failed to reopen database: {e}"))?;
+    database::replace_pool(pool).map_err(|e| e.to_string())?;
+    swapped?;
+    next.save()
+}
+
+/// バックアップの保存先
+fn backup_dir() -> Result<PathBuf, String> {
+    let data_dir = Environment::current().data_dir().map_err(|e| e.to_string())?;
+    Ok(data_dir.join(BACKUP_DIRNAME))
+}
+
+/// 平文のバックアップを暗号化する（暗号化できないものは削除）
+///
+/// 暗号化を有効にしても、それ以前のバックアップに平文の内容が残らないようにする
+async fn encrypt_plaintext_backups(dir: &Path, key: &DatabaseKey) -> Result<usize, String> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+        Err(e) => return Err(format!("Failed to read backup directory: {e}")),
+    };
+
+    let mut encrypted = 0;
+    for path in entries.filter_map(Result::ok).map(|entry| entry.path()) {
+        if !path.is_file() || path.extension().is_none_or(|ext| ext != "db") {
+            continue;
+        }
+        if is_encrypted_file(&path).unwrap_or(true) {
+            continue;
+        }
+
+        let temp_path = path.with_extension("db.rekey");
+        let result = async {
+            export_database(&path, None, &temp_path, Some(key)).await?;
+            std::fs::rename(&temp_path, &path).map_err(|e| e.to_string())
+        }
+        .await;
+        match result {
+            Ok(()) => encrypted += 1,
+            Err(e) => {
+                eprintln!("Failed to encrypt backup {}: {e}. Removing it.", path.display());
+                let _ = std::fs::remove_file(&temp_path);
+                std::fs::remove_file(&path)
+                    .map_err(|e| format!("Failed to remove unencrypted backup {}: {e}", path.display()))?;
+            }
+        }
+    }
+    Ok(encrypted)
+}
+
+/// パスフレーズの長さを確認（保管庫でも使用）
+pub(crate) fn validate_passphrase(passphrase: &str) -> Result<(), String> {
+    if passphrase.chars().count() < MIN_PASSPHRASE_CHARS {
+        return Err(format!("Passphrase must be at least {MIN_PASSPHRASE_CHARS} characters"));
+    }
+    if passphrase.len() > MAX_PASSPHRASE_BYTES {
+        return Err("Passphrase too long".to_string());
+    }
+    Ok(())
+}
+
+/// 鍵導出はCPU・メモリを使うため専用スレッドで実行
+async fn derive_key(passphrase: Zeroizing<String>, salt: Vec<u8>, params: KdfParams) -> Result<DatabaseKey, String> {
+    tokio::task::spawn_blocking(move || DatabaseKey::derive(&passphrase, &salt, params))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// 設定のソルトと入力されたパスフレーズから鍵を導出して確認
+async fn verified_key(config: &EncryptionConfig, passphrase: Zeroizing<String>) -> Result<DatabaseKey, String> {
+    let key = derive_key(passphrase, config.salt()?, config.kdf).await?;
+    let db_path = database::database_path().map_err(|e| e.to_string())?;
+    verify_key(&db_path, &key).await?;
+    Ok(key)
+}
+
+/// データベースの状態変化を通知し、データベースを使うバックグラウンド処理を追従させる
+fn notify_state(app_handle: &AppHandle, locked: bool) {
+    if locked {
+        crate::lan_sync::stop_service();
+        crate::markdown_sync::stop_watching();
+    } else {
+        crate::lan_sync::resume_from_config(app_handle);
+        crate::markdown_sync::resume_from_config(app_handle);
+    }
+    // ロック中はピン留めプロンプトを読めないため、トレイのメニューを作り直す
+    crate::tray::refresh_tray(app_handle);
+    let event = if locked { "database-locked" } else { "database-unlocked" };
+    if let Err(e) = app_handle.emit(event, ()) {
+        eprintln!("Failed to emit {event} event: {e}");
+    }
+}
+
+/// 暗号化の状態を取得
+#[tauri::command]
+pub async fn get_database_encryption_status() -> Result<serde_json::Value, String> {
+    let config = EncryptionConfig::load()?;
+    Ok(serde_json::json!({
+        "enabled": config.enabled,
+        "locked": database::is_database_locked(),
+    }))
+}
+
+/// データベースの暗号化を有効化
+#[tauri::command]
+pub async fn enable_database_encryption(app_handle: AppHandle, passphrase: String) -> Result<(), String> {
+    let passphrase = Zeroizing::new(passphrase);
+    validate_passphrase(&passphrase)?;
+    let previous = EncryptionConfig::load()?;
+    if previous.enabled {
+        return Err("Database encryption is already enabled".to_string());
+    }
+
+    let mut next = previous.clone();
+    let salt = next.salt_or_generate()?;
+    // 暗号化した後に設定の保存に失敗しても鍵を導出できるよう、ソルトは先に保存する
+    if previous.salt.is_none() {
+        next.save()?;
+    }
+    next.enabled = true;
+    let key = derive_key(passphrase, salt, next.kdf).await?;
+
+    let result = reencrypt(None, Some(&key), &next).await;
+    notify_state(&app_handle, false);
+    result?;
+
+    let backups = encrypt_plaintext_backups(&backup_dir()?, &key).await?;
+    if backups > 0 {
+        println!("Encrypted {backups} existing backups");
+    }
+    Ok(())
+}
+
+/// データベースの暗号化を無効化（現在のパスフレーズが必要）
+#[tauri::command]
+pub async fn disable_database_encryption(app_handle: AppHandle, passphrase: String) -> Result<(), String> {
+    let previous = EncryptionConfig::load()?;
+    if !previous.enabled {
+        return Err("Database encryption is not enabled".to_string());
+    }
+    if database::is_database_locked() {
+        return Err("Unlock the database first".to_string());
+    }
+    let key = verified_key(&previous, Zeroizing::new(passphrase)).await?;
+
+    let next = EncryptionConfig { enabled: false, ..previous.clone() };
+    let result = reencrypt(Some(&key), None, &next).await;
+    notify_state(&app_handle, false);
+    result
+}
+
+/// パスフレーズを変更
+#[tauri::command]
+pub async fn change_database_passphrase(
+    app_handle: AppHandle,
+    current_passphrase: String,
+    new_passphrase: String,
+) -> Result<(), String> {
+    let new_passphrase = Zeroizing::new(new_passphrase);
+    validate_passphrase(&new_passphrase)?;
+    let config = EncryptionConfig::load()?;
+    if !config.enabled {
+        return Err("Database encryption is not enabled".to_string());
+    }
+    if database::is_database_locked() {
+        return Err("Unlock the database first".to_string());
+    }
+    let current_key = verified_key(&config, Zeroizing::new(current_passphrase)).await?;
+    let new_key = derive_key(new_passphrase, config.salt()?, config.kdf).await?;
+
+    let result = reencrypt(Some(&current_key), Some(&new_key), &config).await;
+    notify_state(&app_handle, false);
+    result
+}
+
+/// パスフレーズでデータベースのロックを解除
+#[tauri::command]
+pub async fn unlock_database(app_handle: AppHandle, passphrase: String) -> Result<(), String> {
+    if !database::is_database_locked() {
+        return Err("Database is not locked".to_string());
+    }
+    let config = EncryptionConfig::load()?;
+    let key = verified_key(&config, Zeroizing::new(passphrase)).await?;
+
+    let db_path = database::database_path().map_err(|e| e.to_string())?;
+    let pool = database::open_database(&db_path, Some(key.pragma_value().as_str()))
+        .await
+        .map_err(|e| format!("Failed to open database: {e}"))?;
+    database::install_unlocked_pool(pool).map_err(|e| e.to_string())?;
+
+    println!("Database unlocked");
+    notify_state(&app_handle, false);
+    crate::app_state::resume_deferred_launch(&app_handle);
+    Ok(())
+}
+
+/// データベースをロック（接続を閉じて鍵を破棄）
+#[tauri::command]
+pub async fn lock_database(app_handle: AppHandle) -> Result<(), String> {
+    if !is_encryption_enabled()? {
+        return Err("Database encryption is not enabled".to_string());
+    }
+    let pool = database::take_pool_for_lock().map_err(|e| e.to_string())?;
+    pool.close().await;
+
+    println!("Database locked");
+    notify_state(&app_handle, true);
+    Ok(())
+}
+
+/// バックアップを現在の暗号化設定と鍵で復元できるか確認
+///
+/// pool: 接続中のデータベース（この接続の鍵でバックアップを開けるか確認する）
+pub(crate) async fn ensure_backup_matches_encryption(pool: &sqlx::SqlitePool, backup_path: &Path) -> Result<(), String> {
+    let encrypted = is_encrypted_file(backup_path).map_err(|e| format!("Failed to read backup: {e}"))?;
+    match (encrypted, is_encryption_enabled()?) {
+        (true, false) => Err("Backup is encrypted. Enable database encryption with the passphrase used for this backup before restoring.".to_string()),
+        (false, true) => Err("Backup is not encrypted. Disable database encryption before restoring it.".to_string()),
+        _ => ensure_backup_opens_with_current_key(pool, backup_path, encrypted).await,
+    }
+}
+
+/// バックアップを接続中のデータベースと同じ鍵で開けるか確認
+///
+/// 接続プールの接続設定（鍵を含む）のまま、ファイルだけをバックアップの複製に替えて開く
+/// （接続時にジャーナルモードが書き換わるため、バックアップそのものは開かない）。
+/// パスフレーズを変更する前の暗号化バックアップはここで拒否される
+async fn ensure_backup_opens_with_current_key(
+    pool: &sqlx::SqlitePool,
+    backup_path: &Path,
+    encrypted: bool,
+) -> Result<(), String> {
+    let to_error = |e: sqlx::Error| {
+        if !is_wrong_key(&e) {
+            format!("Failed to read backup: {e}")
+        } else if encrypted {
+            "Backup was encrypted with a different passphrase. Change the passphrase back to the one used for this backup before restoring.".to_string()
+        } else {
+            "Backup is not a valid database".to_string()
+        }
+    };
+
+    let check_path = backup_path.with_extension("db.check");
+    std::fs::copy(backup_path, &check_path).map_err(|e| format!("Failed to read backup: {e}"))?;
+
+    let result = async {
+        let mut conn = (*pool.connect_options())
+            .clone()
+            .filename(&check_path)
+            .create_if_missing(false)
+            .connect()
+            .await
+            .map_err(to_error)?;
+        let result = sqlx::query("SELECT COUNT(*) FROM sqlite_master")
+            .execute(&mut conn)
+            .await
+            .map(|_| ())
+            .map_err(to_error);
+        let _ = sqlx::Connection::close(conn).await;
+        result
+    }
+    .await;
+    for suffix in ["", "-wal", "-shm"] {
+        let mut file = check_path.clone().into_os_string();
+        file.push(suffix);
+        let _ = std::fs::remove_file(file);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_db_path(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("prompalette-encryption-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir.join(name)
+    }
+
+    fn test_key(passphrase: &str) -> DatabaseKey {
+        let params = KdfParams { memory_kib: 1024, iterations: 1, parallelism: 1 };
+        DatabaseKey::derive(passphrase, b"0123456789abcdef", params).unwrap()
+    }
+
+    #[test]
+    fn test_key_depends_on_passphrase_and_salt() {
+        let params = KdfParams { memory_kib: 1024, iterations: 1, parallelism: 1 };
+        let a = DatabaseKey::derive("correct horse", b"0123456789abcdef", params).unwrap();
+        let b = DatabaseKey::derive("correct horse", b"0123456789abcdef", params).unwrap();
+        let c = DatabaseKey::derive("correct horse", b"fedcba9876543210", params).unwrap();
+        let d = DatabaseKey::derive("battery staple", b"0123456789abcdef", params).unwrap();
+
+        assert_eq!(*a.0, *b.0);
+        assert_ne!(*a.0, *c.0);
+        assert_ne!(*a.0, *d.0);
+        assert!(a.pragma_value().starts_with("\"x'"));
+        assert_eq!(a.raw_key().len(), KEY_LEN * 2 + 3);
+    }
+
+    #[tokio::test]
+    async fn test_exported_database_is_encrypted_and_requires_key() {
+        let plain_path = temp_db_path("plain.db");
+        let encrypted_path = plain_path.with_file_name("encrypted.db");
+        let pool = database::open_database(&plain_path, None).await.unwrap();
+        sqlx::query("INSERT INTO prompts (id, content, created_at, updated_at) VALUES ('p1', 'internal architecture', datetime('now'), datetime('now'))")
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        pool.close().await;
+
+        let key = test_key("correct horse");
+        export_database(&plain_path, None, &encrypted_path, Some(&key)).await.unwrap();
+
+        assert!(is_encrypted_file(&encrypted_path).unwrap());
+        assert!(!is_encrypted_file(&plain_path).unwrap());
+        let raw = std::fs::read(&encrypted_path).unwrap();
+        assert!(!raw.windows(21).any(|w| w == b"internal architecture"));
+
+        // 間違った鍵では開けない
+        let wrong = test_key("battery staple");
+        assert_eq!(verify_key(&encrypted_path, &wrong).await.unwrap_err(), "Incorrect passphrase");
+        let error = database::open_database(&encrypted_path, Some(wrong.pragma_value().as_str()))
+            .await
+            .unwrap_err();
+        assert!(is_wrong_key(error.as_ref()));
+
+        // 正しい鍵では開けて、スキーマとデータが引き継がれている
+        verify_key(&encrypted_path, &key).await.unwrap();
+        let pool = database::open_database(&encrypted_path, Some(key.pragma_value().as_str()))
+            .await
+            .unwrap();
+        let content: String = sqlx::query_scalar("SELECT content FROM prompts WHERE id = 'p1'")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(content, "internal architecture");
+
+        // 平文に戻す
+        pool.close().await;
+        let decrypted_path = plain_path.with_file_name("decrypted.db");
+        export_database(&encrypted_path, Some(&key), &decrypted_path, None).await.unwrap();
+        assert!(!is_encrypted_file(&decrypted_path).unwrap());
+        assert!(export_database(&encrypted_path, Some(&wrong), &decrypted_path, None).await.is_err());
+
+        let _ = std::fs::remove_dir_all(plain_path.parent().unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_plaintext_backups_are_encrypted() {
+        let backup_path = temp_db_path("manual_backup_1.db");
+        let dir = backup_path.parent().unwrap().to_path_buf();
+        let pool = database::open_database(&backup_path, None).await.unwrap();
+        sqlx::query("INSERT INTO prompts (id, content, created_at, updated_at) VALUES ('p1', 'internal architecture', datetime('now'), datetime('now'))")
+            .execute(&pool)
+            .await
+            .unwrap();
+        // WALの内容をデータベースファイルに書き出してから閉じる
+        sqlx::query("PRAGMA wal_checkpoint(TRUNCATE)").execute(&pool).await.unwrap();
+        pool.close().await;
+        for suffix in ["-wal", "-shm"] {
+            let _ = std::fs::remove_file(dir.join(format!("manual_backup_1.db{suffix}")));
+        }
+        std::fs::write(dir.join("notes.txt"), "not a backup").unwrap();
+
+        let key = test_key("correct horse");
+        assert_eq!(encrypt_plaintext_backups(&dir, &key).await.unwrap(), 1);
+        assert!(is_encrypted_file(&backup_path).unwrap());
+        verify_key(&backup_path, &key).await.unwrap();
+        assert_eq!(std::fs::read_to_string(dir.join("notes.txt")).unwrap(), "not a backup");
+
+        // 暗号化済みのバックアップはそのまま
+        assert_eq!(encrypt_plaintext_backups(&dir, &key).await.unwrap(), 0);
+        assert_eq!(encrypt_plaintext_backups(&dir.join("missing"), &key).await.unwrap(), 0);
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test]
+    async fn test_backup_must_open_with_current_key() {
+        let plain_path = temp_db_path("plain.db");
+        let dir = plain_path.parent().unwrap().to_path_buf();
+        database::open_database(&plain_path, None).await.unwrap().close().await;
+
+        let key = test_key("correct horse");
+        let old_key = test_key("battery staple");
+        let current_backup = dir.join("current_backup.db");
+        let old_backup = dir.join("old_backup.db");
+        export_database(&plain_path, None, &current_backup, Some(&key)).await.unwrap();
+        export_database(&plain_path, None, &old_backup, Some(&old_key)).await.unwrap();
+
+        let live_path = dir.join("live.db");
+        export_database(&plain_path, None, &live_path, Some(&key)).await.unwrap();
+        let pool = database::open_database(&live_path, Some(key.pragma_value().as_str()))
+            .await
+            .unwrap();
+
+        ensure_backup_opens_with_current_key(&pool, &current_backup, true).await.unwrap();
+        let error = ensure_backup_opens_with_current_key(&pool, &old_backup, true)
+            .await
+            .unwrap_err();
+        assert!(error.contains("different passphrase"), "{error}");
+        // 確認用の複製は残らない
+        assert!(!dir.join("old_backup.db.check").exists());
+
+        pool.close().await;
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_passphrase_validation() {
+        assert!(validate_passphrase("short").is_err());
+        assert!(validate_passphrase("long enough").is_ok());
+        assert!(validate_passphrase(&"a".repeat(MAX_PASSPHRASE_BYTES + 1)).is_err());
+    }
+}
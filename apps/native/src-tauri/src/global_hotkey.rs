@@ -1,27 +1,37 @@
 /*!
- * グローバルホットキーによるパレット即時ペースト機能（CGEvent版）
+ * グローバルホットキーによるパレット即時ペースト機能
  * 
  * Cmd+Ctrl+数字キー (1-9, 0) でパレット位置のプロンプトを
  * アクティブアプリケーションに直接ペーストする機能を提供
  * 
+ * リーダーキーモード（任意）: リーダーキーの後、待ち時間内に押した1キーで位置を選ぶ
+ * （数字キー 1-9, 0 → 位置1-10、英字キー a-z → 位置11-36、Escapeで取り消し）
+ * 
+ * ペースト履歴: 最近ペーストしたプロンプトをメモリ内に記録し（再起動で消える）、
+ * 直前のプロンプトを再度ペーストするホットキーと、最近のプロンプトを順にペーストするホットキーを提供
+ * 
  * 設計方針:
- * - CGEventを使用してネイティブなCmd+V操作を実現
+ * - ペースト方式・送信手段はpasteモジュールに委譲（CGEvent、enigo、wtypeなど）
  * - 複雑なデバウンスやリトライ機構を排除
  * - OS標準のペーストUXと完全に同じ動作
  */
+use serde::{Deserialize, Serialize};
 use tauri::{AppHandle, Emitter};
-use tauri_plugin_global_shortcut::GlobalShortcutExt;
-use tauri_plugin_clipboard_manager::ClipboardExt;
-use std::collections::HashMap;
-use std::sync::atomic::{AtomicBool, Ordering};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Mutex;
 use std::time::{Duration, Instant};
 
-use crate::commands;
-
 /// ペースト処理中フラグ（ホットキー再発火防止のみ）
 static IS_PASTING: AtomicBool = AtomicBool::new(false);
 
+/// パレットのペースト処理中かどうか（他のキー送信と重ならないようにする）
+pub(crate) fn is_pasting() -> bool {
+    IS_PASTING.load(Ordering::Relaxed)
+}
+
 /// 最後のホットキー実行時間（キーリピート防止）
 static LAST_HOTKEY_TIMES: std::sync::OnceLock<Mutex<HashMap<u8, Instant>>> = std::sync::OnceLock::new();
 
@@ -31,8 +41,41 @@ pub struct HotkeyError {
     pub error: String,
 }
 
-/// パレット位置とショートカットキーのマッピング
-const PALETTE_HOTKEYS: &[(u8, &str)] = &[
+/// パレット位置の範囲
+const PALETTE_POSITIONS: std::ops::RangeInclusive<u8> = 1..=crate::database::MAX_PIN_POSITION;
+
+/// リーダーキーの登録に使う位置（実際のパレット位置は後続キーで決まる）
+const LEADER_POSITION: u8 = 0;
+
+/// 直前のプロンプトを再度ペーストするホットキーの登録に使う位置
+const REPEAT_LAST_POSITION: u8 = u8::MAX - 1;
+
+/// 最近のプロンプトを順にペーストするホットキーの登録に使う位置
+const CYCLE_RECENT_POSITION: u8 = u8::MAX;
+
+/// ペースト履歴の最大件数
+const PASTE_HISTORY_LEN: usize = 10;
+
+/// 最近のプロンプトを順にペーストする操作を続けて押したとみなす間隔
+const CYCLE_RESET_AFTER: Duration = Duration::from_secs(3);
+
+/// ペースト履歴のホットキーのデフォルト
+const DEFAULT_REPEAT_LAST_HOTKEY: &str = "CommandOrControl+Alt+Shift+V";
+const DEFAULT_CYCLE_RECENT_HOTKEY: &str = "CommandOrControl+Alt+Shift+R";
+
+/// リーダーキーモードの待ち時間（ミリ秒）
+const DEFAULT_CHORD_TIMEOUT_MS: u64 = 1500;
+const MIN_CHORD_TIMEOUT_MS: u64 = 300;
+const MAX_CHORD_TIMEOUT_MS: u64 = 5000;
+
+/// リーダーキーモードのデフォルトのリーダーキー
+const DEFAULT_CHORD_LEADER: &str = "Alt+Shift+Space";
+
+/// リーダーキーモードを取り消すキー
+const CHORD_CANCEL_KEY: &str = "Escape";
+
+/// パレット位置とショートカットキーのデフォルトのマッピング
+const DEFAULT_PALETTE_HOTKEYS: &[(u8, &str)] = &[
     (1, "CommandOrControl+Control+1"),
     (2, "CommandOrControl+Control+2"), 
     (3, "CommandOrControl+Control+3"),
@@ -45,80 +88,643 @@ const PALETTE_HOTKEYS: &[(u8, &str)] = &[
     (10, "CommandOrControl+Control+0"), // 位置10は0キー
 ];
 
+/// パレット位置とショートカットキーの割り当て
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PaletteHotkey {
+    pub position: u8,
+    pub hotkey: String,
+}
+
+/// リーダーキーモードの設定
+///
+/// 有効な場合は位置ごとのホットキーの代わりにリーダーキーのみ登録し、
+/// リーダーキーの後に押した数字キー（1-9, 0）または英字キー（a-z）で位置を選ぶ
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChordConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_chord_leader")]
+    pub leader: String,
+    /// リーダーキーの後、位置を選ぶキーを待つ時間（ミリ秒）
+    #[serde(default = "default_chord_timeout_ms")]
+    pub timeout_ms: u64,
+}
+
+fn default_chord_leader() -> String {
+    DEFAULT_CHORD_LEADER.to_string()
+}
+
+fn default_chord_timeout_ms() -> u64 {
+    DEFAULT_CHORD_TIMEOUT_MS
+}
+
+impl Default for ChordConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            leader: default_chord_leader(),
+            timeout_ms: DEFAULT_CHORD_TIMEOUT_MS,
+        }
+    }
+}
+
+impl ChordConfig {
+    fn validate(&self) -> Result<(), String> {
+        let leader = Shortcut::from_str(&self.leader)
+            .map_err(|e| format!("Invalid leader key '{}': {e}", self.leader))?;
+        if leader.mods.is_empty() {
+            return Err(format!("Leader key '{}' needs at least one modifier key", self.leader));
+        }
+        if !(MIN_CHORD_TIMEOUT_MS..=MAX_CHORD_TIMEOUT_MS).contains(&self.timeout_ms) {
+            return Err(format!(
+                "Chord timeout must be between {MIN_CHORD_TIMEOUT_MS} and {MAX_CHORD_TIMEOUT_MS} milliseconds"
+            ));
+        }
+        Ok(())
+    }
+
+    fn timeout(&self) -> Duration {
+        Duration::from_millis(self.timeout_ms.clamp(MIN_CHORD_TIMEOUT_MS, MAX_CHORD_TIMEOUT_MS))
+    }
+}
+
+/// ペースト履歴のホットキー設定（`null`の場合はホットキーなし）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HistoryHotkeyConfig {
+    /// 直前にペーストしたプロンプトを再度ペースト
+    #[serde(default = "default_repeat_last_hotkey")]
+    pub repeat_last: Option<String>,
+    /// 押すたびに1つ前にペーストしたプロンプトをペースト（続けて押すと順にさかのぼる）
+    #[serde(default = "default_cycle_recent_hotkey")]
+    pub cycle_recent: Option<String>,
+}
+
+fn default_repeat_last_hotkey() -> Option<String> {
+    Some(DEFAULT_REPEAT_LAST_HOTKEY.to_string())
+}
+
+fn default_cycle_recent_hotkey() -> Option<String> {
+    Some(DEFAULT_CYCLE_RECENT_HOTKEY.to_string())
+}
+
+impl Default for HistoryHotkeyConfig {
+    fn default() -> Self {
+        Self {
+            repeat_last: default_repeat_last_hotkey(),
+            cycle_recent: default_cycle_recent_hotkey(),
+        }
+    }
+}
+
+impl HistoryHotkeyConfig {
+    fn bindings(&self) -> impl Iterator<Item = PaletteHotkey> + '_ {
+        [(REPEAT_LAST_POSITION, &self.repeat_last), (CYCLE_RECENT_POSITION, &self.cycle_recent)]
+            .into_iter()
+            .filter_map(|(position, hotkey)| Some(PaletteHotkey { position, hotkey: hotkey.clone()? }))
+    }
+}
+
+/// パレットホットキー設定（アプリケーション設定の`palette`セクション、割り当てのない位置はホットキーなし）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PaletteHotkeyConfig {
+    pub hotkeys: Vec<PaletteHotkey>,
+    #[serde(default)]
+    pub chord: ChordConfig,
+    #[serde(default)]
+    pub history: HistoryHotkeyConfig,
+}
+
+impl Default for PaletteHotkeyConfig {
+    fn default() -> Self {
+        Self {
+            hotkeys: DEFAULT_PALETTE_HOTKEYS
+                .iter()
+                .map(|&(position, hotkey)| PaletteHotkey { position, hotkey: hotkey.to_string() })
+                .collect(),
+            chord: ChordConfig::default(),
+            history: HistoryHotkeyConfig::default(),
+        }
+    }
+}
+
+impl PaletteHotkeyConfig {
+    /// 実際に登録するホットキー（リーダーキーモードでは位置ごとのホットキーの代わりにリーダーキー）
+    pub(crate) fn bindings(&self) -> Vec<PaletteHotkey> {
+        let mut bindings = if self.chord.enabled {
+            vec![PaletteHotkey { position: LEADER_POSITION, hotkey: self.chord.leader.clone() }]
+        } else {
+            self.hotkeys.clone()
+        };
+        bindings.extend(self.history.bindings());
+        bindings
+    }
+
+    /// 指定位置をペーストするキー操作（表示用、リーダーキーモードでは「リーダーキー, 後続キー」）
+    pub(crate) fn hotkey_hint(&self, position: u8) -> Option<String> {
+        if self.chord.enabled {
+            let (key, _) = chord_follow_up_keys().into_iter().find(|(_, target)| *target == position)?;
+            return Some(format!("{}, {key}", self.chord.leader));
+        }
+        self.hotkeys
+            .iter()
+            .find(|hotkey| hotkey.position == position)
+            .map(|hotkey| hotkey.hotkey.clone())
+    }
+
+    /// 割り当ての妥当性を確認（登録前に検出できる問題のみ）
+    pub(crate) fn validate(&self) -> Result<(), String> {
+        let mut positions = HashSet::new();
+        let mut shortcuts: HashMap<u32, &str> = HashMap::new();
+
+        for PaletteHotkey { position, hotkey } in &self.hotkeys {
+            if !PALETTE_POSITIONS.contains(position) {
+                return Err(format!(
+                    "Palette position must be between 1 and {}: {position}",
+                    PALETTE_POSITIONS.end()
+                ));
+            }
+            if !positions.insert(*position) {
+                return Err(format!("Palette position {position} is assigned more than once"));
+            }
+            let shortcut = Shortcut::from_str(hotkey)
+                .map_err(|e| format!("Invalid hotkey '{hotkey}' for position {position}: {e}"))?;
+            // 修飾キーなしのホットキーは通常の入力を奪うため禁止
+            if shortcut.mods.is_empty() {
+                return Err(format!("Hotkey '{hotkey}' needs at least one modifier key"));
+            }
+            if let Some(other) = shortcuts.insert(shortcut.id(), hotkey) {
+                return Err(format!("Hotkey '{hotkey}' conflicts with '{other}'"));
+            }
+        }
+        for PaletteHotkey { hotkey, .. } in self.history.bindings() {
+            crate::shortcuts::validate_global_shortcut(&hotkey)?;
+        }
+        self.chord.validate()
+    }
+}
+
+/// 現在登録中のパレットホットキー
+static ACTIVE_HOTKEYS: Mutex<Vec<PaletteHotkey>> = Mutex::new(Vec::new());
+
 /**
  * パレット用グローバルホットキーを登録
+ * 
+ * 保存済みの設定（未設定の場合はデフォルト）を登録する。
+ * 他のアプリケーションが使用しているなどで登録できない位置があっても、残りの位置は登録する
+ * （登録できなかった位置は`retry_shortcut_registration`で再試行できる）
  */
 #[tauri::command]
 pub async fn register_palette_hotkeys(app_handle: AppHandle) -> Result<(), HotkeyError> {
-    // パレットホットキー登録開始
-    
-    for &(position, hotkey) in PALETTE_HOTKEYS {
-        // ホットキー登録: {} -> 位置 {}
-        
-        app_handle
-            .global_shortcut()
-            .on_shortcut(hotkey, move |app_handle, _shortcut, event| {
-                // ホットキーイベント受信
-                
-                // キーダウンイベントのみ処理（キーアップは無視）
-                match event.state {
-                    tauri_plugin_global_shortcut::ShortcutState::Released => {
-                        return; // キーアップ無視
-                    }
-                    tauri_plugin_global_shortcut::ShortcutState::Pressed => {
-                        // キーダウン検知
-                    }
-                }
-                
-                // IS_PASTINGフラグもここでチェック
-                if IS_PASTING.load(Ordering::Relaxed) {
-                    return; // ペースト処理中は無視
+    if crate::shortcuts::is_paused() {
+        return Ok(()); // 再開時に登録する
+    }
+    let config = crate::settings::load_or_default().palette;
+    let errors = register_missing_palette_hotkeys(&app_handle, &config.bindings());
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(HotkeyError { error: errors.join("\n") })
+    }
+}
+
+/**
+ * 未登録のパレットホットキーのみ登録（位置ごとに独立して登録し、エラーを返す）
+ */
+pub(crate) fn register_missing_palette_hotkeys(app_handle: &AppHandle, hotkeys: &[PaletteHotkey]) -> Vec<String> {
+    let Ok(mut active) = ACTIVE_HOTKEYS.lock() else {
+        return vec!["Palette hotkey state is poisoned".to_string()];
+    };
+    let mut errors = Vec::new();
+    for entry in hotkeys {
+        if active.contains(entry) {
+            continue;
+        }
+        match register_palette_hotkey(app_handle, entry.position, &entry.hotkey) {
+            Ok(()) => active.push(entry.clone()),
+            Err(e) => errors.push(e),
+        }
+    }
+    errors
+}
+
+/// 登録状況・エラーの記録に使う所有者ID
+pub(crate) fn palette_owner_id(position: u8) -> String {
+    match position {
+        LEADER_POSITION => "palette:leader".to_string(),
+        REPEAT_LAST_POSITION => "palette:repeat_last".to_string(),
+        CYCLE_RECENT_POSITION => "palette:cycle_recent".to_string(),
+        _ => format!("palette:{position}"),
+    }
+}
+
+/// 登録状況に表示する種類（`palette` / `palette_leader` / `repeat_last` / `cycle_recent`）
+pub(crate) fn binding_kind(position: u8) -> &'static str {
+    match position {
+        LEADER_POSITION => "palette_leader",
+        REPEAT_LAST_POSITION => "repeat_last",
+        CYCLE_RECENT_POSITION => "cycle_recent",
+        _ => "palette",
+    }
+}
+
+/**
+ * パレットホットキーを差し替え
+ * 
+ * 現在のホットキーを解除してから新しい組み合わせを全て登録する。
+ * 1つでも登録に失敗した場合は登録済みの分を解除し、以前の組み合わせを登録し直す
+ */
+pub(crate) fn apply_palette_hotkeys(app_handle: &AppHandle, hotkeys: &[PaletteHotkey]) -> Result<(), String> {
+    let mut active = ACTIVE_HOTKEYS.lock().map_err(|_| "Palette hotkey state is poisoned".to_string())?;
+    let previous = std::mem::take(&mut *active);
+    unregister_hotkeys(app_handle, &previous);
+    disarm_chord(app_handle, "cancelled");
+
+    let mut registered = Vec::new();
+    for entry in hotkeys {
+        if let Err(e) = register_palette_hotkey(app_handle, entry.position, &entry.hotkey) {
+            unregister_hotkeys(app_handle, &registered);
+            for old in &previous {
+                match register_palette_hotkey(app_handle, old.position, &old.hotkey) {
+                    Ok(()) => active.push(old.clone()),
+                    Err(restore_err) => eprintln!("Failed to restore hotkey '{}': {restore_err}", old.hotkey),
                 }
-                
-                // キーリピート防止：300ms以内の同じキーは無視
-                let last_times = LAST_HOTKEY_TIMES.get_or_init(|| Mutex::new(HashMap::new()));
-                let now = Instant::now();
-                
-                {
-                    let mut times = match last_times.lock() {
-                        Ok(times) => times,
-                        Err(_) => return, // Mutex汚染時は静かに終了
-                    };
-                    if let Some(&last_time) = times.get(&position) {
-                        let elapsed = now.duration_since(last_time);
-                        if elapsed < Duration::from_millis(300) {
-                            return; // キーリピート防止
-                        }
-                    }
-                    times.insert(position, now);
+            }
+            return Err(e);
+        }
+        registered.push(entry.clone());
+    }
+
+    *active = registered;
+    Ok(())
+}
+
+fn unregister_hotkeys(app_handle: &AppHandle, hotkeys: &[PaletteHotkey]) {
+    for PaletteHotkey { hotkey, .. } in hotkeys {
+        if let Err(e) = app_handle.global_shortcut().unregister(hotkey.as_str()) {
+            eprintln!("Failed to unregister hotkey '{hotkey}': {e}");
+        }
+    }
+}
+
+/**
+ * パレット位置1つ分のホットキーを登録
+ */
+fn register_palette_hotkey(app_handle: &AppHandle, position: u8, hotkey: &str) -> Result<(), String> {
+    let result = app_handle
+        .global_shortcut()
+        .on_shortcut(hotkey, move |app_handle, _shortcut, event| {
+            // ホットキーイベント受信
+            
+            // キーダウンイベントのみ処理（キーアップは無視）
+            match event.state {
+                tauri_plugin_global_shortcut::ShortcutState::Released => {
+                    return; // キーアップ無視
                 }
-                let handle = app_handle.clone();
-                tauri::async_runtime::spawn(async move {
-                    let _ = handle_palette_hotkey(handle, position).await; // 静かに処理
-                });
-            })
-            .map_err(|e| {
-                HotkeyError {
-                    error: format!("パレット位置{position}のホットキー '{hotkey}'の登録に失敗しました: {e}")
+                tauri_plugin_global_shortcut::ShortcutState::Pressed => {
+                    // キーダウン検知
                 }
-            })?;
+            }
             
-        // ホットキー登録成功
+            match position {
+                LEADER_POSITION => arm_chord(app_handle),
+                REPEAT_LAST_POSITION => trigger_paste(app_handle, position, PasteTarget::RepeatLast),
+                CYCLE_RECENT_POSITION => trigger_paste(app_handle, position, PasteTarget::CycleRecent),
+                _ => trigger_paste(app_handle, position, PasteTarget::Pinned(position)),
+            }
+        })
+        .map_err(|e| match position {
+            REPEAT_LAST_POSITION | CYCLE_RECENT_POSITION => {
+                format!("ペースト履歴のホットキー '{hotkey}'の登録に失敗しました: {e}")
+            }
+            _ => format!("パレット位置{position}のホットキー '{hotkey}'の登録に失敗しました: {e}"),
+        });
+    crate::shortcuts::record_registration(&palette_owner_id(position), hotkey, &result);
+    result
+}
+
+/**
+ * 再入・キーリピートを確認してからペーストを開始
+ * 
+ * `guard_key`: キーリピート防止に使うキー（ホットキーの登録位置）
+ */
+fn trigger_paste(app_handle: &AppHandle, guard_key: u8, target: PasteTarget) {
+    // IS_PASTINGフラグもここでチェック
+    if IS_PASTING.load(Ordering::Relaxed) {
+        return; // ペースト処理中は無視
+    }
+    if !passes_repeat_guard(guard_key) {
+        return; // キーリピート防止
+    }
+    let handle = app_handle.clone();
+    tauri::async_runtime::spawn(async move {
+        let _ = handle_palette_hotkey(handle, target).await; // 静かに処理
+    });
+}
+
+/// キーリピート防止：300ms以内の同じキーは無視（処理してよい場合はtrue）
+fn passes_repeat_guard(position: u8) -> bool {
+    let last_times = LAST_HOTKEY_TIMES.get_or_init(|| Mutex::new(HashMap::new()));
+    let now = Instant::now();
+    
+    let mut times = match last_times.lock() {
+        Ok(times) => times,
+        Err(_) => return false, // Mutex汚染時は静かに終了
+    };
+    if let Some(&last_time) = times.get(&position) {
+        let elapsed = now.duration_since(last_time);
+        if elapsed < Duration::from_millis(300) {
+            return false;
+        }
+    }
+    times.insert(position, now);
+    true
+}
+
+/// 待機中のリーダーキーモード
+struct ArmedChord {
+    generation: u64,
+    /// 待機中だけ登録している後続キー
+    keys: Vec<String>,
+}
+
+/// リーダーキーモードの状態（待機していない場合はNone）
+static ARMED_CHORD: Mutex<Option<ArmedChord>> = Mutex::new(None);
+
+/// 待機ごとに増やす世代番号（古いタイムアウトや後続キーを無視するため）
+static CHORD_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// 後続キーとパレット位置（1-9 → 1-9、0 → 10、a-z → 11-36）
+fn chord_follow_up_keys() -> Vec<(String, u8)> {
+    let digits = (1..=9u8).map(|digit| (digit.to_string(), digit)).chain([("0".to_string(), 10)]);
+    let letters = (b'A'..=b'Z').map(|letter| ((letter as char).to_string(), letter - b'A' + 11));
+    digits.chain(letters).collect()
+}
+
+/**
+ * リーダーキー押下時の処理
+ * 
+ * 後続キー（数字・英字）とEscapeを一時的に登録し、待ち時間が過ぎたら解除する
+ */
+fn arm_chord(app_handle: &AppHandle) {
+    if IS_PASTING.load(Ordering::Relaxed) {
+        return; // ペースト処理中は無視
+    }
+    if !passes_repeat_guard(LEADER_POSITION) {
+        return; // キーリピート防止
+    }
+    let timeout = crate::settings::load_or_default().palette.chord.timeout();
+
+    let Ok(mut armed) = ARMED_CHORD.lock() else {
+        return;
+    };
+    if armed.is_some() {
+        return; // 既に待機中
+    }
+    let generation = CHORD_GENERATION.fetch_add(1, Ordering::Relaxed) + 1;
+
+    let mut keys = Vec::new();
+    for (key, position) in chord_follow_up_keys() {
+        let registered = app_handle.global_shortcut().on_shortcut(key.as_str(), move |app_handle, _shortcut, event| {
+            if event.state != tauri_plugin_global_shortcut::ShortcutState::Pressed {
+                return;
+            }
+            if let Some(keys) = take_armed_chord(generation) {
+                finish_chord(app_handle, keys, "selected");
+                trigger_paste(app_handle, position, PasteTarget::Pinned(position));
+            }
+        });
+        match registered {
+            Ok(()) => keys.push(key),
+            Err(e) => eprintln!("Failed to register chord key '{key}': {e}"),
+        }
+    }
+    let cancel = app_handle.global_shortcut().on_shortcut(CHORD_CANCEL_KEY, move |app_handle, _shortcut, event| {
+        if event.state != tauri_plugin_global_shortcut::ShortcutState::Pressed {
+            return;
+        }
+        if let Some(keys) = take_armed_chord(generation) {
+            finish_chord(app_handle, keys, "cancelled");
+        }
+    });
+    match cancel {
+        Ok(()) => keys.push(CHORD_CANCEL_KEY.to_string()),
+        Err(e) => eprintln!("Failed to register chord key '{CHORD_CANCEL_KEY}': {e}"),
     }
 
-    // パレットホットキー登録完了
+    *armed = Some(ArmedChord { generation, keys });
+    drop(armed);
+    let _ = app_handle.emit("palette-chord-armed", serde_json::json!({
+        "timeout_ms": timeout.as_millis() as u64
+    }));
+
+    let handle = app_handle.clone();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(timeout).await;
+        if let Some(keys) = take_armed_chord(generation) {
+            finish_chord(&handle, keys, "timeout");
+        }
+    });
+}
+
+/// 待機中の世代が一致する場合のみ待機を終了し、登録していた後続キーを返す
+fn take_armed_chord(generation: u64) -> Option<Vec<String>> {
+    let mut armed = ARMED_CHORD.lock().ok()?;
+    if armed.as_ref()?.generation != generation {
+        return None;
+    }
+    armed.take().map(|chord| chord.keys)
+}
+
+/// 後続キーを解除してフロントエンドに通知
+fn finish_chord(app_handle: &AppHandle, keys: Vec<String>, reason: &str) {
+    for key in keys {
+        if let Err(e) = app_handle.global_shortcut().unregister(key.as_str()) {
+            eprintln!("Failed to unregister chord key '{key}': {e}");
+        }
+    }
+    let _ = app_handle.emit("palette-chord-disarmed", serde_json::json!({ "reason": reason }));
+}
+
+/// 待機中のリーダーキーモードを取り消す
+fn disarm_chord(app_handle: &AppHandle, reason: &str) {
+    let keys = ARMED_CHORD.lock().ok().and_then(|mut armed| armed.take()).map(|chord| chord.keys);
+    if let Some(keys) = keys {
+        finish_chord(app_handle, keys, reason);
+    }
+}
+
+/**
+ * 保存済みのパレットホットキー設定を取得
+ */
+#[tauri::command]
+pub async fn get_palette_hotkeys() -> Result<PaletteHotkeyConfig, HotkeyError> {
+    crate::settings::load()
+        .map(|settings| settings.palette)
+        .map_err(|error| HotkeyError { error })
+}
+
+/**
+ * パレットホットキーを変更して保存
+ * 
+ * 新しい組み合わせが全て登録できた場合のみ保存する（失敗時は以前の組み合わせのまま）
+ */
+#[tauri::command]
+pub async fn set_palette_hotkeys(app_handle: AppHandle, hotkeys: Vec<PaletteHotkey>) -> Result<(), HotkeyError> {
+    let settings = crate::settings::update_with(&app_handle, |settings| {
+        settings.palette.hotkeys = hotkeys;
+        Ok(())
+    })
+    .map_err(|error| HotkeyError { error })?;
+
+    let _ = app_handle.emit("palette-hotkeys-changed", &settings.palette);
     Ok(())
 }
 
 /**
- * パレットホットキー押下時の処理
+ * リーダーキーモードの設定を変更して保存
+ * 
+ * 有効にすると位置ごとのホットキーを解除し、リーダーキーのみ登録する
+ */
+#[tauri::command]
+pub async fn set_palette_chord(app_handle: AppHandle, chord: ChordConfig) -> Result<(), HotkeyError> {
+    let settings = crate::settings::update_with(&app_handle, |settings| {
+        settings.palette.chord = chord;
+        Ok(())
+    })
+    .map_err(|error| HotkeyError { error })?;
+
+    let _ = app_handle.emit("palette-hotkeys-changed", &settings.palette);
+    Ok(())
+}
+
+/**
+ * パレットホットキーをデフォルトに戻す
+ */
+#[tauri::command]
+pub async fn reset_palette_hotkeys(app_handle: AppHandle) -> Result<(), HotkeyError> {
+    set_palette_hotkeys(app_handle, PaletteHotkeyConfig::default().hotkeys).await
+}
+
+/// ペーストするプロンプト
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PasteTarget {
+    /// パレット位置のピン留めプロンプト
+    Pinned(u8),
+    /// 直前にペーストしたプロンプト
+    RepeatLast,
+    /// 最近ペーストしたプロンプトを順に
+    CycleRecent,
+}
+
+impl PasteTarget {
+    /// イベントで通知するペースト元
+    fn source(self) -> &'static str {
+        match self {
+            Self::Pinned(_) => "palette",
+            Self::RepeatLast => "repeat_last",
+            Self::CycleRecent => "cycle_recent",
+        }
+    }
+}
+
+/// ペースト履歴のエントリ
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PasteHistoryEntry {
+    pub prompt_id: String,
+    /// パレット位置（パレットホットキーでペーストした場合）
+    pub position: Option<u8>,
+    pub pasted_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// 最近ペーストしたプロンプト（新しい順、同じプロンプトは1件のみ）
+static PASTE_HISTORY: Mutex<VecDeque<PasteHistoryEntry>> = Mutex::new(VecDeque::new());
+
+/// ペースト履歴に追加（既にある場合は先頭に移動）
+fn push_history(history: &mut VecDeque<PasteHistoryEntry>, entry: PasteHistoryEntry) {
+    history.retain(|existing| existing.prompt_id != entry.prompt_id);
+    history.push_front(entry);
+    history.truncate(PASTE_HISTORY_LEN);
+}
+
+/// 最近のプロンプトを順にペーストしている途中の状態
+#[derive(Debug, Clone)]
+struct CycleState {
+    /// 開始時点の履歴（途中でペーストした分で順番が変わらないように固定）
+    entries: Vec<PasteHistoryEntry>,
+    next: usize,
+    last_at: Instant,
+}
+
+static PASTE_CYCLE: Mutex<Option<CycleState>> = Mutex::new(None);
+
+/// 次にペーストする履歴のエントリを選ぶ
+///
+/// 間を空けて押した場合は1つ前（直前は`RepeatLast`で再度ペーストできるため）から始め、
+/// 続けて押すと順にさかのぼる（最後まで行ったら最新に戻る）
+fn next_cycle_entry(
+    state: Option<CycleState>,
+    history: &VecDeque<PasteHistoryEntry>,
+    now: Instant,
+) -> Option<(PasteHistoryEntry, CycleState)> {
+    let state = match state {
+        Some(state) if now.duration_since(state.last_at) < CYCLE_RESET_AFTER => state,
+        _ => CycleState {
+            entries: history.iter().cloned().collect(),
+            next: 1,
+            last_at: now,
+        },
+    };
+    if state.entries.is_empty() {
+        return None;
+    }
+    let entry = state.entries[state.next % state.entries.len()].clone();
+    Some((entry, CycleState { next: state.next + 1, last_at: now, ..state }))
+}
+
+/// ペーストするプロンプトを決めて内容を取得
+async fn resolve_paste_target(
+    target: PasteTarget,
+) -> Result<Option<(crate::database::PinnedContent, Option<u8>)>, Box<dyn std::error::Error>> {
+    let entry = match target {
+        PasteTarget::Pinned(position) => {
+            let pinned = crate::database::get_pinned_prompt_content(position).await?;
+            return Ok(pinned.map(|pinned| (pinned, Some(position))));
+        }
+        PasteTarget::RepeatLast => PASTE_HISTORY.lock().ok().and_then(|history| history.front().cloned()),
+        PasteTarget::CycleRecent => {
+            let history = PASTE_HISTORY.lock().map(|history| history.clone()).unwrap_or_default();
+            let mut cycle = PASTE_CYCLE.lock().map_err(|_| "Paste cycle state is poisoned")?;
+            next_cycle_entry(cycle.take(), &history, Instant::now()).map(|(entry, state)| {
+                *cycle = Some(state);
+                entry
+            })
+        }
+    };
+    let Some(entry) = entry else {
+        return Ok(None);
+    };
+
+    let content = crate::database::get_prompt_paste_content(&entry.prompt_id).await?;
+    if content.is_none() {
+        // 削除されたプロンプトは履歴から除く
+        if let Ok(mut history) = PASTE_HISTORY.lock() {
+            history.retain(|existing| existing.prompt_id != entry.prompt_id);
+        }
+    }
+    Ok(content.map(|content| (content, entry.position)))
+}
+
+/**
+ * パレットホットキー・ペースト履歴のホットキー押下時の処理
  * 
  * シンプルな流れ:
- * 1. 指定位置のピン留めプロンプトを取得
+ * 1. ペーストするプロンプト（指定位置のピン留めプロンプト、またはペースト履歴）を取得
+ *    （大きなプロンプトはpaste_guardモジュールで承認を待つ）
  * 2. クリップボードにコピー  
  * 3. CGEventでネイティブなCmd+Vを送信
+ * 4. ペースト履歴に記録
  */
-async fn handle_palette_hotkey(app_handle: AppHandle, position: u8) -> Result<(), HotkeyError> {
+async fn handle_palette_hotkey(app_handle: AppHandle, target: PasteTarget) -> Result<(), HotkeyError> {
     // 簡単な再入防止のみ
     if IS_PASTING.load(Ordering::Relaxed) {
         return Ok(());
@@ -131,36 +737,85 @@ async fn handle_palette_hotkey(app_handle: AppHandle, position: u8) -> Result<()
         IS_PASTING.store(false, Ordering::Relaxed);
     };
     
-    // 1. ピン留めプロンプトを取得（静音）
-    let pinned_prompts_response = if let Ok(response) = commands::get_pinned_prompts().await { response } else {
-        cleanup();
-        return Ok(()); // 静かに失敗
-    };
-    
-    // 2. 指定位置のプロンプトを検索（静音）
-    let prompt_text = if let Some(text) = pinned_prompts_response.data
-        .iter()
-        .find(|p| p.pinned_position == Some(position))
-        .map(|p| p.content.clone()) { text } else {
-        cleanup();
-        return Ok(()); // 静かに失敗
+    // 1-2. ペーストするプロンプトを取得（静音）
+    // シークレットプロンプトは保管庫のロック解除中のみ（ロック中はロック解除を促す）
+    let (pinned, position) = match resolve_paste_target(target).await {
+        Ok(Some(resolved)) => resolved,
+        Ok(None) => {
+            cleanup();
+            return Ok(()); // 静かに失敗
+        }
+        Err(e) => {
+            if e.to_string() == crate::vault::VAULT_LOCKED_ERROR {
+                let _ = app_handle.emit("vault-unlock-required", serde_json::json!({
+                    "position": position_of(target),
+                    "source": target.source()
+                }));
+            }
+            cleanup();
+            return Ok(()); // 静かに失敗
+        }
     };
 
-    // 3. クリップボードにコピー（静音）
-    if app_handle.clipboard().write_text(prompt_text.clone()).is_err() {
+    // 大きなプロンプト（またはプロンプトごとの設定で常に確認）は承認を待つ
+    let paste_config = crate::settings::load_or_default().paste;
+    if paste_config.needs_confirmation(&pinned.content, pinned.confirmation) {
+        let request = crate::paste_guard::PasteConfirmationRequest {
+            request_id: uuid::Uuid::new_v4().to_string(),
+            position,
+            source: target.source(),
+            prompt_id: pinned.id.clone(),
+            chars: pinned.content.chars().count(),
+            threshold: paste_config.confirm_threshold,
+        };
+        // 承認を待つ間は他のホットキーを受け付ける（待っている間に別のペーストが始まった場合は中止）
+        IS_PASTING.store(false, Ordering::Relaxed);
+        let approved = crate::paste_guard::request_confirmation(&app_handle, request).await;
+        if IS_PASTING.compare_exchange(false, true, Ordering::Relaxed, Ordering::Relaxed).is_err() {
+            return Ok(());
+        }
+        if !approved {
+            cleanup();
+            return Ok(());
+        }
+    }
+
+    // 3-4. 設定したペースト方式で送信（方式と送信手段はpasteモジュールで選択）
+    // 送信できなかった場合もクリップボードにはコピー済みのまま残すので、ユーザーは手動ペースト可能
+    // キー送信は外部ヘルパーの完了を待つため、非同期ランタイムのスレッドを塞がないようにする
+    let paste_app = app_handle.clone();
+    let content = pinned.content.clone();
+    let sensitive = pinned.sensitive;
+    let pasted = tauri::async_runtime::spawn_blocking(move || {
+        crate::paste::paste_prompt(&paste_app, &content, sensitive)
+    })
+    .await
+    .map_err(|e| format!("Paste task failed: {e}"))
+    .and_then(|result| result);
+    if let Err(e) = pasted {
+        let _ = app_handle.emit("paste-failed", serde_json::json!({
+            "position": position,
+            "source": target.source(),
+            "error": e,
+            "action": "manual_paste_required"
+        }));
         cleanup();
-        return Ok(()); // 静かに失敗
+        return Ok(());
     }
-    
-    // 4. 即座にネイティブなCmd+V送信（遅延なし・静音）
-    if send_native_paste().is_err() {
-        // 失敗時も静かに処理（標準Cmd+V同様）
-        // クリップボードにはコピー済みなので、ユーザーは手動ペースト可能
+
+    if let Ok(mut history) = PASTE_HISTORY.lock() {
+        push_history(&mut history, PasteHistoryEntry {
+            prompt_id: pinned.id.clone(),
+            position,
+            pasted_at: chrono::Utc::now(),
+        });
     }
+    crate::tray::refresh_tray(&app_handle);
 
     // 5. 静かな成功通知（デバッグ情報は含まない）
     let _ = app_handle.emit("palette-pasted", serde_json::json!({
         "position": position,
+        "source": target.source(),
         "success": true,
         "timestamp": chrono::Utc::now().to_rfc3339()
         // "text" は除外（セキュリティ向上）
@@ -169,57 +824,33 @@ async fn handle_palette_hotkey(app_handle: AppHandle, position: u8) -> Result<()
     Ok(())
 }
 
+/// パレット位置（ピン留めプロンプトの場合）
+fn position_of(target: PasteTarget) -> Option<u8> {
+    match target {
+        PasteTarget::Pinned(position) => Some(position),
+        PasteTarget::RepeatLast | PasteTarget::CycleRecent => None,
+    }
+}
+
 /**
- * CGEventを使用してネイティブなCmd+Vを送信
- * OS標準のペーストと完全に同じ動作を実現
+ * ペースト履歴を取得（新しい順）
+ * 
+ * 記録するのはプロンプトIDのみで、内容は含まない
  */
-fn send_native_paste() -> Result<(), String> {
-    #[cfg(target_os = "macos")]
-    {
-        use core_graphics::event::{
-            CGEvent, CGEventFlags, CGEventTapLocation
-        };
-        use core_graphics::event_source::{CGEventSource, CGEventSourceStateID};
-        
-        // CGEventで直接キーイベントを送信（標準Cmd+Vと同じ）
-        let source = CGEventSource::new(CGEventSourceStateID::HIDSystemState)
-            .map_err(|()| "Failed to create event source".to_string())?;
-        
-        // V key (keycode 9) + Command modifier
-        let key_down = CGEvent::new_keyboard_event(source.clone(), 9, true)
-            .map_err(|()| "Failed to create key down event".to_string())?;
-        let key_up = CGEvent::new_keyboard_event(source, 9, false)
-            .map_err(|()| "Failed to create key up event".to_string())?;
-        
-        // Commandフラグ設定
-        key_down.set_flags(CGEventFlags::CGEventFlagCommand);
-        key_up.set_flags(CGEventFlags::CGEventFlagCommand);
-        
-        // 即座にキーイベント送信
-        key_down.post(CGEventTapLocation::HID);
-        key_up.post(CGEventTapLocation::HID);
-        
-        Ok(())
-    }
-    
-    #[cfg(not(target_os = "macos"))]
-    {
-        // Windows/Linuxでは従来のenigo使用（即座に実行）
-        use enigo::{Enigo, Key, Keyboard, Settings, Direction};
-        
-        let mut enigo = Enigo::new(&Settings::default())
-            .map_err(|e| format!("Failed to create Enigo: {}", e))?;
-        
-        // Ctrl+V送信（即座）
-        enigo.key(Key::Control, Direction::Press)
-            .map_err(|e| format!("Failed to press Ctrl: {}", e))?;
-        enigo.key(Key::Unicode('v'), Direction::Click)
-            .map_err(|e| format!("Failed to click V: {}", e))?;
-        enigo.key(Key::Control, Direction::Release)
-            .map_err(|e| format!("Failed to release Ctrl: {}", e))?;
-        
-        Ok(())
-    }
+#[tauri::command]
+pub async fn get_paste_history() -> Result<Vec<PasteHistoryEntry>, HotkeyError> {
+    PASTE_HISTORY
+        .lock()
+        .map(|history| history.iter().cloned().collect())
+        .map_err(|_| HotkeyError { error: "Paste history is poisoned".to_string() })
+}
+
+/// ペースト履歴（新しい順、トレイメニュー用）
+pub(crate) fn recent_pastes() -> Vec<PasteHistoryEntry> {
+    PASTE_HISTORY
+        .lock()
+        .map(|history| history.iter().cloned().collect())
+        .unwrap_or_default()
 }
 
 /**
@@ -227,11 +858,12 @@ fn send_native_paste() -> Result<(), String> {
  */
 #[tauri::command]
 pub async fn unregister_palette_hotkeys(app_handle: AppHandle) -> Result<(), HotkeyError> {
-    for &(_, hotkey) in PALETTE_HOTKEYS {
-        if let Err(e) = app_handle.global_shortcut().unregister(hotkey) {
-            eprintln!("Failed to unregister hotkey '{hotkey}': {e}");
-        }
-    }
+    let hotkeys = match ACTIVE_HOTKEYS.lock() {
+        Ok(mut active) => std::mem::take(&mut *active),
+        Err(_) => return Err(HotkeyError { error: "Palette hotkey state is poisoned".to_string() }),
+    };
+    unregister_hotkeys(&app_handle, &hotkeys);
+    disarm_chord(&app_handle, "cancelled");
     
     println!("Unregistered all palette hotkeys");
     Ok(())
@@ -243,11 +875,12 @@ pub async fn unregister_palette_hotkeys(app_handle: AppHandle) -> Result<(), Hot
 #[tauri::command]
 pub async fn get_palette_hotkey_status(app_handle: AppHandle) -> Result<serde_json::Value, HotkeyError> {
     let mut status = HashMap::new();
+    let config = crate::settings::load().map_err(|error| HotkeyError { error })?.palette;
     
-    for &(position, hotkey) in PALETTE_HOTKEYS {
+    for PaletteHotkey { position, hotkey } in &config.hotkeys {
         let is_registered = app_handle
             .global_shortcut()
-            .is_registered(hotkey);
+            .is_registered(hotkey.as_str());
             
         status.insert(position.to_string(), serde_json::json!({
             "hotkey": hotkey,
@@ -258,7 +891,7 @@ pub async fn get_palette_hotkey_status(app_handle: AppHandle) -> Result<serde_js
     
     Ok(serde_json::json!({
         "palette_hotkeys": status,
-        "total_hotkeys": PALETTE_HOTKEYS.len()
+        "total_hotkeys": config.hotkeys.len()
     }))
 }
 
@@ -268,11 +901,146 @@ mod tests {
     
     #[test]
     fn test_palette_hotkeys_mapping() {
-        assert_eq!(PALETTE_HOTKEYS.len(), 10);
+        let config = PaletteHotkeyConfig::default();
+        assert_eq!(config.hotkeys.len(), 10);
         
-        let positions: Vec<u8> = PALETTE_HOTKEYS.iter().map(|(pos, _)| *pos).collect();
+        let positions: Vec<u8> = config.hotkeys.iter().map(|entry| entry.position).collect();
         for i in 1..=10 {
             assert!(positions.contains(&i), "Position {i} is missing");
         }
+        assert!(config.validate().is_ok());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_palette_hotkey_validation() {
+        let config = |entries: &[(u8, &str)]| PaletteHotkeyConfig {
+            hotkeys: entries
+                .iter()
+                .map(|&(position, hotkey)| PaletteHotkey { position, hotkey: hotkey.to_string() })
+                .collect(),
+            chord: ChordConfig::default(),
+            history: HistoryHotkeyConfig::default(),
+        };
+
+        // 一部の位置のみの割り当ては可能
+        assert!(config(&[(1, "Alt+Shift+1"), (10, "Alt+Shift+0")]).validate().is_ok());
+
+        assert!(config(&[(0, "Alt+Shift+1")]).validate().is_err());
+        assert!(config(&[(37, "Alt+Shift+1")]).validate().is_err());
+        assert!(config(&[(1, "Alt+Shift+1"), (1, "Alt+Shift+2")]).validate().is_err());
+        assert!(config(&[(1, "Alt+Shift+Nope")]).validate().is_err());
+        assert!(config(&[(1, "1")]).validate().is_err());
+        // 修飾キーの順序が違っても同じ組み合わせは重複
+        assert!(config(&[(1, "Alt+Shift+1"), (2, "Shift+Alt+1")]).validate().is_err());
+        // 英字キーの位置（11-36）にも割り当て可能
+        assert!(config(&[(36, "Alt+Shift+Z")]).validate().is_ok());
+    }
+
+    #[test]
+    fn test_chord_mode() {
+        let mut config = PaletteHotkeyConfig {
+            history: HistoryHotkeyConfig { repeat_last: None, cycle_recent: None },
+            ..PaletteHotkeyConfig::default()
+        };
+        assert_eq!(config.bindings().len(), 10);
+
+        config.chord.enabled = true;
+        assert!(config.validate().is_ok());
+        let bindings = config.bindings();
+        assert_eq!(bindings.len(), 1);
+        assert_eq!(palette_owner_id(bindings[0].position), "palette:leader");
+
+        config.chord.timeout_ms = 100;
+        assert!(config.validate().is_err());
+        config.chord.timeout_ms = DEFAULT_CHORD_TIMEOUT_MS;
+        config.chord.leader = "Space".to_string();
+        assert!(config.validate().is_err());
+
+        // 保存済みの設定にリーダーキーモードがない場合は無効
+        let saved: PaletteHotkeyConfig = serde_json::from_str(r#"{"hotkeys":[]}"#).unwrap();
+        assert_eq!(saved.chord, ChordConfig::default());
+    }
+
+    #[test]
+    fn test_chord_follow_up_keys() {
+        let keys = chord_follow_up_keys();
+        assert_eq!(keys.len(), crate::database::MAX_PIN_POSITION as usize);
+        assert!(keys.contains(&("1".to_string(), 1)));
+        assert!(keys.contains(&("0".to_string(), 10)));
+        assert!(keys.contains(&("A".to_string(), 11)));
+        assert!(keys.contains(&("Z".to_string(), 36)));
+        for (key, _) in &keys {
+            assert!(Shortcut::from_str(key).is_ok(), "{key} should parse");
+        }
+    }
+
+    #[test]
+    fn test_hotkey_hint() {
+        let config = PaletteHotkeyConfig::default();
+        assert_eq!(config.hotkey_hint(1).as_deref(), Some("CommandOrControl+Control+1"));
+        assert_eq!(config.hotkey_hint(11), None);
+
+        let chord = PaletteHotkeyConfig {
+            chord: ChordConfig { enabled: true, ..ChordConfig::default() },
+            ..PaletteHotkeyConfig::default()
+        };
+        let leader = chord.chord.leader.clone();
+        assert_eq!(chord.hotkey_hint(10), Some(format!("{leader}, 0")));
+        assert_eq!(chord.hotkey_hint(11), Some(format!("{leader}, A")));
+        assert_eq!(chord.hotkey_hint(37), None);
+    }
+
+    #[test]
+    fn test_history_hotkeys() {
+        let config = PaletteHotkeyConfig::default();
+        let kinds: Vec<&str> = config.bindings().iter().map(|entry| binding_kind(entry.position)).collect();
+        assert_eq!(kinds.iter().filter(|kind| **kind == "palette").count(), 10);
+        assert!(kinds.contains(&"repeat_last") && kinds.contains(&"cycle_recent"));
+
+        // `null`で無効、省略時はデフォルト
+        let saved: PaletteHotkeyConfig =
+            serde_json::from_str(r#"{"hotkeys":[],"history":{"cycle_recent":null}}"#).unwrap();
+        assert_eq!(saved.history.repeat_last, default_repeat_last_hotkey());
+        let bindings = saved.bindings();
+        assert_eq!(bindings.len(), 1);
+        assert_eq!(palette_owner_id(bindings[0].position), "palette:repeat_last");
+
+        let mut invalid = PaletteHotkeyConfig::default();
+        invalid.history.repeat_last = Some("V".to_string());
+        assert!(invalid.validate().is_err());
+    }
+
+    #[test]
+    fn test_paste_history_and_cycle() {
+        let entry = |id: &str| PasteHistoryEntry {
+            prompt_id: id.to_string(),
+            position: None,
+            pasted_at: chrono::Utc::now(),
+        };
+        let mut history = VecDeque::new();
+        for id in ["a", "b", "c", "a"] {
+            push_history(&mut history, entry(id));
+        }
+        let ids: Vec<&str> = history.iter().map(|entry| entry.prompt_id.as_str()).collect();
+        assert_eq!(ids, ["a", "c", "b"]);
+        for i in 0..PASTE_HISTORY_LEN {
+            push_history(&mut history, entry(&i.to_string()));
+        }
+        assert_eq!(history.len(), PASTE_HISTORY_LEN);
+
+        let history: VecDeque<_> = ["a", "c", "b"].into_iter().map(entry).collect();
+        let now = Instant::now();
+        // 1つ前から始めて順にさかのぼり、最後まで行ったら最新に戻る
+        let (first, state) = next_cycle_entry(None, &history, now).unwrap();
+        assert_eq!(first.prompt_id, "c");
+        let (second, state) = next_cycle_entry(Some(state), &history, now).unwrap();
+        assert_eq!(second.prompt_id, "b");
+        let (third, state) = next_cycle_entry(Some(state), &history, now).unwrap();
+        assert_eq!(third.prompt_id, "a");
+        // 間を空けると最初からやり直す
+        let (restart, _) = next_cycle_entry(Some(state), &history, now + CYCLE_RESET_AFTER).unwrap();
+        assert_eq!(restart.prompt_id, "c");
+
+        assert!(next_cycle_entry(None, &VecDeque::new(), now).is_none());
+    }
+}
@@ -1,157 +0,0 @@
-/**
- * セキュリティを強化したグローバルホットキー実装の推奨例
- */
-
-use tauri::{AppHandle, Emitter};
-use tauri_plugin_global_shortcut::GlobalShortcutExt;
-use tauri_plugin_clipboard_manager::ClipboardExt;
-use std::collections::HashMap;
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Mutex;
-use std::time::{Duration, Instant};
-use serde::{Serialize, Deserialize};
-
-/// ホットキー設定構造体
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct HotkeyConfig {
-    pub enabled: bool,
-    pub modifier1: String,
-    pub modifier2: String,
-    pub conflict_override: bool,
-}
-
-/// セキュリティ設定
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct SecurityConfig {
-    pub log_prompts: bool,  // デフォルトfalse
-    pub emit_full_content: bool,  // デフォルトfalse
-    pub require_confirmation: bool,  // 大きなプロンプトの場合
-}
-
-/// アクセシビリティ権限チェック（macOS）
-#[cfg(target_os = "macos")]
-pub fn check_accessibility_permission() -> Result<bool, String> {
-    use std::process::Command;
-    
-    let output = Command::new("osascript")
-        .arg("-e")
-        .arg("tell application \"System Events\" to return UI elements enabled")
-        .output()
-        .map_err(|e| format!("Failed to check accessibility: {}", e))?;
-    
-    let result = String::from_utf8_lossy(&output.stdout).trim() == "true";
-    Ok(result)
-}
-
-/// セキュアなペースト処理
-async fn handle_palette_hotkey_secure(
-    app_handle: AppHandle,
-    position: u8,
-    config: &SecurityConfig,
-) -> Result<(), HotkeyError> {
-    // アクセシビリティ権限確認
-    #[cfg(target_os = "macos")]
-    {
-        if !check_accessibility_permission()? {
-            return Err(HotkeyError {
-                error: "アクセシビリティ権限が必要です。システム設定から許可してください。".to_string()
-            });
-        }
-    }
-    
-    // プロンプト取得
-    let prompt_text = get_prompt_for_position(position).await?;
-    
-    // セキュリティチェック
-    if config.require_confirmation && prompt_text.len() > 1000 {
-        // 大きなプロンプトの場合は確認を求める
-        if !confirm_large_paste(&app_handle, prompt_text.len()).await? {
-            return Ok(());
-        }
-    }
-    
-    // クリップボードにコピー
-    app_handle.clipboard().write_text(prompt_text.clone())?;
-    
-    // ペースト実行
-    send_native_paste()?;
-    
-    // セキュアな通知（内容は含まない）
-    emit_secure_notification(&app_handle, position, config)?;
-    
-    Ok(())
-}
-
-/// セキュアな通知送信
-fn emit_secure_notification(
-    app_handle: &AppHandle,
-    position: u8,
-    config: &SecurityConfig,
-) -> Result<(), String> {
-    let mut notification = serde_json::json!({
-        "position": position,
-        "success": true,
-        "timestamp": chrono::Utc::now().to_rfc3339()
-    });
-    
-    // 設定に応じて詳細情報を追加（デフォルトは含まない）
-    if config.emit_full_content {
-        // 明示的に許可された場合のみ
-        // notification["content"] = ...
-    }
-    
-    app_handle.emit("palette-pasted", notification)
-        .map_err(|e| format!("Failed to emit event: {}", e))
-}
-
-/// ホットキー衝突検出
-pub async fn detect_hotkey_conflicts(hotkey: &str) -> Vec<ConflictInfo> {
-    let mut conflicts = Vec::new();
-    
-    // 既知のシステムショートカット
-    let system_shortcuts = vec![
-        ("CommandOrControl+C", "Copy"),
-        ("CommandOrControl+V", "Paste"),
-        ("CommandOrControl+X", "Cut"),
-        // ... その他
-    ];
-    
-    for (key, action) in system_shortcuts {
-        if key == hotkey {
-            conflicts.push(ConflictInfo {
-                app_name: "System".to_string(),
-                action: action.to_string(),
-                severity: ConflictSeverity::High,
-            });
-        }
-    }
-    
-    conflicts
-}
-
-#[derive(Debug, Serialize)]
-pub struct ConflictInfo {
-    pub app_name: String,
-    pub action: String,
-    pub severity: ConflictSeverity,
-}
-
-#[derive(Debug, Serialize)]
-pub enum ConflictSeverity {
-    Low,
-    Medium,
-    High,
-}
-
-/// ユーザー確認ダイアログ
-async fn confirm_large_paste(app_handle: &AppHandle, size: usize) -> Result<bool, String> {
-    // フロントエンドに確認を求める
-    app_handle.emit("confirm-large-paste", serde_json::json!({
-        "size": size,
-        "size_kb": size / 1024,
-    })).map_err(|e| format!("Failed to request confirmation: {}", e))?;
-    
-    // 実際の実装では、フロントエンドからの応答を待つ
-    // ここでは簡略化
-    Ok(true)
-}
\ No newline at end of file
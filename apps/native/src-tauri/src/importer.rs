@@ -0,0 +1,592 @@
+/*!
+ * 会話エクスポートからのプロンプト取り込み
+ *
+ * ChatGPT / Claude のデータエクスポートに含まれる `conversations.json` を
+ * オフラインで解析し、ユーザーが書いたメッセージをプロンプト候補として抽出する
+ *
+ * - ネットワークアクセスは行わない（ローカルファイルのみ）
+ * - ChatGPTの会話は表示中の分岐（`current_node`から遡った経路）のみ対象（再生成・編集前の分岐は除く）
+ * - 正規化した内容で重複排除
+ * - 会話タイトルを推奨タイトルとして提示
+ * - 解析できない会話はスキップして件数を返す（他の会話の取り込みは続ける）
+ */
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+use crate::commands::{create_prompt, truncate_title, ErrorResponse, SuccessResponse};
+use crate::database::{get_all_prompts as db_get_all_prompts, CreatePromptRequest, Prompt};
+
+/// 取り込み対象ファイルの最大サイズ（200MB）
+const MAX_EXPORT_FILE_SIZE: u64 = 200 * 1024 * 1024;
+
+/// プロンプトとして扱う内容の最大長（create_promptの制限と同じ）
+const MAX_CONTENT_LENGTH: usize = 100_000;
+
+/// 取り込んだプロンプトに付与する共通タグ
+pub const IMPORTED_TAG: &str = "imported";
+
+/// エクスポート元サービス
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConversationSource {
+    ChatGpt,
+    Claude,
+}
+
+impl ConversationSource {
+    /// 取り込み元を示すタグ
+    pub fn tag(self) -> &'static str {
+        match self {
+            ConversationSource::ChatGpt => "source:chatgpt",
+            ConversationSource::Claude => "source:claude",
+        }
+    }
+}
+
+/// 取り込み候補（プレビュー表示用）
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportCandidate {
+    /// 正規化した内容から算出した識別子（選択時に使用）
+    pub id: String,
+    /// 推奨タイトル（会話タイトル）
+    pub suggested_title: Option<String>,
+    pub content: String,
+    pub source: ConversationSource,
+    /// 同じ内容が出現した回数
+    pub occurrences: usize,
+    /// 既存プロンプトに同じ内容が存在するか
+    pub already_exists: bool,
+}
+
+/// 会話エクスポートの解析結果
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportPreview {
+    pub candidates: Vec<ImportCandidate>,
+    /// 解析できずにスキップした会話の数
+    pub skipped_conversations: usize,
+}
+
+/// 取り込み対象の選択
+#[derive(Debug, Deserialize)]
+pub struct ImportSelection {
+    pub id: String,
+    /// ユーザーが編集したタイトル（未指定時は推奨タイトル）
+    pub title: Option<String>,
+}
+
+/// 取り込み結果
+#[derive(Debug, Serialize)]
+pub struct ImportResult {
+    pub imported: Vec<Prompt>,
+    pub skipped: usize,
+    /// 解析できずにスキップした会話の数
+    pub skipped_conversations: usize,
+}
+
+/// 取り込みエラー
+#[derive(Debug)]
+pub enum ImportError {
+    Io(String),
+    InvalidFormat(String),
+}
+
+impl std::fmt::Display for ImportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ImportError::Io(detail) => write!(f, "Failed to read export file: {}", detail),
+            ImportError::InvalidFormat(detail) => write!(f, "Unsupported export format: {}", detail),
+        }
+    }
+}
+
+impl std::error::Error for ImportError {}
+
+/// ChatGPT エクスポートの会話
+#[derive(Debug, Deserialize)]
+struct ChatGptConversation {
+    title: Option<String>,
+    mapping: HashMap<String, ChatGptNode>,
+    /// 表示中の分岐の末尾のノード
+    current_node: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatGptNode {
+    message: Option<ChatGptMessage>,
+    parent: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatGptMessage {
+    author: ChatGptAuthor,
+    content: Option<ChatGptContent>,
+    create_time: Option<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatGptAuthor {
+    role: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatGptContent {
+    #[serde(default)]
+    parts: Vec<serde_json::Value>,
+}
+
+/// Claude エクスポートの会話
+#[derive(Debug, Deserialize)]
+struct ClaudeConversation {
+    name: Option<String>,
+    #[serde(default)]
+    chat_messages: Vec<ClaudeMessage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ClaudeMessage {
+    sender: String,
+    #[serde(default)]
+    text: Option<String>,
+    #[serde(default)]
+    content: Vec<ClaudeContentBlock>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ClaudeContentBlock {
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    text: Option<String>,
+}
+
+/// 会話から抽出したユーザーメッセージ
+struct ExtractedMessage {
+    conversation_title: Option<String>,
+    content: String,
+    source: ConversationSource,
+}
+
+/// 内容の正規化（前後の空白除去・連続空白の圧縮・小文字化）
+pub fn normalize_content(content: &str) -> String {
+    content
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+/// 正規化済み内容から安定した識別子を生成（FNV-1a 64bit）
+fn candidate_id(normalized: &str) -> String {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in normalized.as_bytes() {
+        hash ^= u64::from(*byte);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    format!("{hash:016x}")
+}
+
+/// 会話タイトルを推奨タイトルに整形
+fn suggested_title(title: Option<&str>) -> Option<String> {
+    let title = title?.trim();
+    if title.is_empty() {
+        return None;
+    }
+    Some(truncate_title(title).to_string())
+}
+
+/// ChatGPTの会話で表示中の分岐の末尾（`current_node`がない場合は最も新しいメッセージ）
+fn chatgpt_leaf(conversation: &ChatGptConversation) -> Option<&str> {
+    if let Some(current) = conversation.current_node.as_deref() {
+        return Some(current);
+    }
+    conversation
+        .mapping
+        .iter()
+        .filter_map(|(id, node)| Some((id, node.message.as_ref()?.create_time.unwrap_or(0.0))))
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(id, _)| id.as_str())
+}
+
+/// ChatGPT形式の会話からユーザーメッセージを抽出
+///
+/// mappingは再生成・編集した分岐をすべて含むため、表示中の分岐を末尾から親へ遡って会話の順序に並べる
+fn extract_chatgpt(mut conversation: ChatGptConversation) -> Vec<ExtractedMessage> {
+    let mut path = Vec::new();
+    let mut next = chatgpt_leaf(&conversation).map(str::to_string);
+    // 辿ったノードはmappingから取り除くため、親が循環していても終わる
+    while let Some(id) = next {
+        let Some(node) = conversation.mapping.remove(&id) else {
+            break;
+        };
+        next = node.parent;
+        path.extend(node.message);
+    }
+
+    path.into_iter()
+        .rev()
+        .filter(|message| message.author.role == "user")
+        .filter_map(|message| {
+            let content = message
+                .content?
+                .parts
+                .iter()
+                .filter_map(|part| part.as_str())
+                .collect::<Vec<_>>()
+                .join("\n");
+            Some(ExtractedMessage {
+                conversation_title: conversation.title.clone(),
+                content,
+                source: ConversationSource::ChatGpt,
+            })
+        })
+        .collect()
+}
+
+/// Claude形式の会話からユーザーメッセージを抽出
+fn extract_claude(conversation: ClaudeConversation) -> Vec<ExtractedMessage> {
+    conversation
+        .chat_messages
+        .into_iter()
+        .filter(|message| message.sender == "human")
+        .map(|message| {
+            // 新しいエクスポートでは本文がcontentブロックに分割されている
+            let content = match message.text {
+                Some(text) if !text.trim().is_empty() => text,
+                _ => message
+                    .content
+                    .iter()
+                    .filter(|block| block.kind == "text")
+                    .filter_map(|block| block.text.as_deref())
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+            };
+            ExtractedMessage {
+                conversation_title: conversation.name.clone(),
+                content,
+                source: ConversationSource::Claude,
+            }
+        })
+        .collect()
+}
+
+/// `conversations.json` の内容を解析し、重複排除済みの取り込み候補を返す
+///
+/// 個々の会話が解析できない場合はスキップして件数を数える（ファイル全体は失敗させない）
+pub fn parse_conversations_export(json: &str) -> Result<ImportPreview, ImportError> {
+    let conversations: Vec<serde_json::Value> = serde_json::from_str(json)
+        .map_err(|e| ImportError::InvalidFormat(format!("expected a JSON array of conversations: {e}")))?;
+
+    let mut extracted = Vec::new();
+    let mut skipped_conversations = 0;
+    for conversation in conversations {
+        if conversation.get("mapping").is_some() {
+            match serde_json::from_value::<ChatGptConversation>(conversation) {
+                Ok(parsed) => extracted.extend(extract_chatgpt(parsed)),
+                Err(e) => {
+                    eprintln!("Skipping invalid ChatGPT conversation: {e}");
+                    skipped_conversations += 1;
+                }
+            }
+        } else if conversation.get("chat_messages").is_some() {
+            match serde_json::from_value::<ClaudeConversation>(conversation) {
+                Ok(parsed) => extracted.extend(extract_claude(parsed)),
+                Err(e) => {
+                    eprintln!("Skipping invalid Claude conversation: {e}");
+                    skipped_conversations += 1;
+                }
+            }
+        }
+        // どちらの形式でもない要素は無視する
+    }
+
+    // 正規化した内容で重複排除（最初に出現した会話のタイトルを採用）
+    let mut candidates: Vec<ImportCandidate> = Vec::new();
+    let mut index_by_id: HashMap<String, usize> = HashMap::new();
+    for message in extracted {
+        let content = message.content.trim();
+        if content.is_empty() || content.len() > MAX_CONTENT_LENGTH {
+            continue;
+        }
+
+        let id = candidate_id(&normalize_content(content));
+        if let Some(&index) = index_by_id.get(&id) {
+            candidates[index].occurrences += 1;
+            continue;
+        }
+
+        index_by_id.insert(id.clone(), candidates.len());
+        candidates.push(ImportCandidate {
+            id,
+            suggested_title: suggested_title(message.conversation_title.as_deref()),
+            content: content.to_string(),
+            source: message.source,
+            occurrences: 1,
+            already_exists: false,
+        });
+    }
+
+    Ok(ImportPreview {
+        candidates,
+        skipped_conversations,
+    })
+}
+
+/// エクスポートファイルを読み込んで解析
+fn read_export_file(path: &str) -> Result<ImportPreview, ImportError> {
+    let metadata = std::fs::metadata(path).map_err(|e| ImportError::Io(e.to_string()))?;
+    if metadata.len() > MAX_EXPORT_FILE_SIZE {
+        return Err(ImportError::Io("Export file too large (max 200MB)".to_string()));
+    }
+
+    let json = std::fs::read_to_string(path).map_err(|e| ImportError::Io(e.to_string()))?;
+    parse_conversations_export(&json)
+}
+
+/// 既存プロンプトの正規化済み内容一覧
+async fn existing_normalized_contents() -> Result<HashSet<String>, Box<dyn std::error::Error>> {
+    let prompts = db_get_all_prompts().await?;
+    Ok(prompts
+        .iter()
+        .map(|prompt| normalize_content(&prompt.content))
+        .collect())
+}
+
+/// 会話エクスポートのプレビューコマンド
+///
+/// path: `conversations.json` のパス
+#[tauri::command]
+pub async fn preview_conversation_import(
+    path: String,
+) -> Result<SuccessResponse<ImportPreview>, ErrorResponse> {
+    let mut preview = read_export_file(&path).map_err(|e| ErrorResponse {
+        error: e.to_string(),
+    })?;
+
+    let existing = existing_normalized_contents().await.map_err(|e| ErrorResponse {
+        error: format!("Failed to load existing prompts: {e}"),
+    })?;
+
+    for candidate in &mut preview.candidates {
+        candidate.already_exists = existing.contains(&normalize_content(&candidate.content));
+    }
+
+    Ok(SuccessResponse {
+        success: true,
+        data: preview,
+    })
+}
+
+/// 選択した候補をプロンプトとして取り込むコマンド
+///
+/// path: `conversations.json` のパス
+/// selections: プレビューで選択された候補
+#[tauri::command]
+pub async fn import_conversation_prompts(
+    path: String,
+    selections: Vec<ImportSelection>,
+) -> Result<SuccessResponse<ImportResult>, ErrorResponse> {
+    let preview = read_export_file(&path).map_err(|e| ErrorResponse {
+        error: e.to_string(),
+    })?;
+
+    let mut existing = existing_normalized_contents().await.map_err(|e| ErrorResponse {
+        error: format!("Failed to load existing prompts: {e}"),
+    })?;
+
+    let by_id: HashMap<&str, &ImportCandidate> = preview
+        .candidates
+        .iter()
+        .map(|candidate| (candidate.id.as_str(), candidate))
+        .collect();
+
+    let mut imported = Vec::new();
+    let mut skipped = 0;
+    for selection in selections {
+        let Some(candidate) = by_id.get(selection.id.as_str()) else {
+            skipped += 1;
+            continue;
+        };
+
+        // 既存プロンプトや同一バッチ内の重複は作成しない
+        if !existing.insert(normalize_content(&candidate.content)) {
+            skipped += 1;
+            continue;
+        }
+
+        let title = selection
+            .title
+            .filter(|title| !title.trim().is_empty())
+            .or_else(|| candidate.suggested_title.clone())
+            .map(|title| truncate_title(&title).to_string());
+
+        let request = CreatePromptRequest {
+            title,
+            content: candidate.content.clone(),
+            tags: Some(vec![
+                IMPORTED_TAG.to_string(),
+                candidate.source.tag().to_string(),
+            ]),
+            quick_access_key: None,
+            status: None,
+            visibility: None,
+            workspace_id: None,
+            is_sensitive: None,
+            paste_confirmation: None,
+        };
+
+        // 手動作成と同じ検証を通す
+        match create_prompt(request).await {
+            Ok(response) => imported.push(response.data),
+            Err(e) => {
+                eprintln!("Failed to import prompt {}: {}", candidate.id, e.error);
+                skipped += 1;
+            }
+        }
+    }
+
+    Ok(SuccessResponse {
+        success: true,
+        data: ImportResult {
+            imported,
+            skipped,
+            skipped_conversations: preview.skipped_conversations,
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CHATGPT_EXPORT: &str = r#"[
+        {
+            "title": "Code review helper",
+            "current_node": "b",
+            "mapping": {
+                "a": { "message": null, "parent": null },
+                "b": {
+                    "message": {
+                        "author": { "role": "user" },
+                        "content": { "content_type": "text", "parts": ["Review this   diff"] },
+                        "create_time": 2.0
+                    },
+                    "parent": "c"
+                },
+                "c": {
+                    "message": {
+                        "author": { "role": "assistant" },
+                        "content": { "content_type": "text", "parts": ["Sure"] },
+                        "create_time": 3.0
+                    },
+                    "parent": "d"
+                },
+                "d": {
+                    "message": {
+                        "author": { "role": "user" },
+                        "content": { "content_type": "text", "parts": ["Explain the bug"] },
+                        "create_time": 1.0
+                    },
+                    "parent": "a"
+                },
+                "e": {
+                    "message": {
+                        "author": { "role": "user" },
+                        "content": { "content_type": "text", "parts": ["Review the old diff"] },
+                        "create_time": 4.0
+                    },
+                    "parent": "c"
+                }
+            }
+        }
+    ]"#;
+
+    const CLAUDE_EXPORT: &str = r#"[
+        {
+            "uuid": "1",
+            "name": "Refactoring",
+            "chat_messages": [
+                { "sender": "human", "text": "review this diff" },
+                { "sender": "assistant", "text": "OK" },
+                { "sender": "human", "text": "", "content": [{ "type": "text", "text": "Write tests" }] }
+            ]
+        }
+    ]"#;
+
+    #[test]
+    fn test_parse_chatgpt_export_extracts_user_messages_in_order() {
+        let candidates = parse_conversations_export(CHATGPT_EXPORT).unwrap().candidates;
+
+        assert_eq!(candidates.len(), 2);
+        assert_eq!(candidates[0].content, "Explain the bug");
+        assert_eq!(candidates[1].content, "Review this   diff");
+        assert_eq!(candidates[0].suggested_title.as_deref(), Some("Code review helper"));
+        assert_eq!(candidates[0].source, ConversationSource::ChatGpt);
+    }
+
+    #[test]
+    fn test_parse_chatgpt_export_follows_current_branch() {
+        let candidates = parse_conversations_export(CHATGPT_EXPORT).unwrap().candidates;
+        assert!(candidates.iter().all(|c| c.content != "Review the old diff"));
+
+        // current_nodeがない場合は最も新しいメッセージの分岐
+        let mut export: Vec<serde_json::Value> = serde_json::from_str(CHATGPT_EXPORT).unwrap();
+        export[0].as_object_mut().unwrap().remove("current_node");
+        let candidates = parse_conversations_export(&serde_json::to_string(&export).unwrap()).unwrap().candidates;
+        let contents: Vec<&str> = candidates.iter().map(|c| c.content.as_str()).collect();
+        assert_eq!(contents, vec!["Explain the bug", "Review the old diff"]);
+    }
+
+    #[test]
+    fn test_suggested_title_is_cut_on_char_boundary() {
+        let title = suggested_title(Some(&"😀".repeat(100))).unwrap();
+        assert!(title.len() <= 200);
+        assert_eq!(title.chars().count(), 50);
+    }
+
+    #[test]
+    fn test_parse_claude_export_reads_content_blocks() {
+        let candidates = parse_conversations_export(CLAUDE_EXPORT).unwrap().candidates;
+
+        assert_eq!(candidates.len(), 2);
+        assert_eq!(candidates[1].content, "Write tests");
+        assert_eq!(candidates[1].source.tag(), "source:claude");
+    }
+
+    #[test]
+    fn test_duplicates_are_merged_by_normalized_content() {
+        let chatgpt: Vec<serde_json::Value> = serde_json::from_str(CHATGPT_EXPORT).unwrap();
+        let claude: Vec<serde_json::Value> = serde_json::from_str(CLAUDE_EXPORT).unwrap();
+        let combined = serde_json::to_string(&[chatgpt, claude].concat()).unwrap();
+
+        let candidates = parse_conversations_export(&combined).unwrap().candidates;
+        let review = candidates
+            .iter()
+            .find(|c| normalize_content(&c.content) == "review this diff")
+            .unwrap();
+
+        assert_eq!(candidates.len(), 3);
+        assert_eq!(review.occurrences, 2);
+        assert_eq!(review.source, ConversationSource::ChatGpt);
+    }
+
+    #[test]
+    fn test_invalid_export_is_rejected() {
+        assert!(parse_conversations_export("{}").is_err());
+        assert!(parse_conversations_export("[]").unwrap().candidates.is_empty());
+    }
+
+    #[test]
+    fn test_invalid_conversation_is_skipped_and_counted() {
+        let mut export: Vec<serde_json::Value> = serde_json::from_str(CLAUDE_EXPORT).unwrap();
+        // 本文がnullのメッセージは空として扱う
+        export[0]["chat_messages"][1]["text"] = serde_json::Value::Null;
+        // 形式が壊れた会話が1件混ざっていても他の会話は取り込める
+        export.push(serde_json::json!({ "name": "Broken", "chat_messages": [{ "text": "no sender" }] }));
+        export.push(serde_json::json!({ "title": "Broken", "mapping": "not a map" }));
+
+        let preview = parse_conversations_export(&serde_json::to_string(&export).unwrap()).unwrap();
+        let contents: Vec<&str> = preview.candidates.iter().map(|c| c.content.as_str()).collect();
+        assert_eq!(contents, vec!["review this diff", "Write tests"]);
+        assert_eq!(preview.skipped_conversations, 2);
+    }
+}
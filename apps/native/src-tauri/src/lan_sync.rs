@@ -0,0 +1,1364 @@
+/*!
+ * LAN内の端末同士のプロンプト同期（オプトイン）
+ *
+ * サーバーを用意せずに、同じネットワーク上の自分の端末（デスクトップとノートPCなど）で
+ * プロンプトを同期する
+ *
+ * - 発見: mDNS（`_prompalette-sync._tcp.local.`）で同期サービスを広告・探索
+ * - ペアリング: 一方の端末に表示した6桁のコードをもう一方で入力し、ed25519公開鍵を交換
+ * - 同期: ペアリング済みの端末とだけ暗号化チャネルを確立し、変更ジャーナルの差分を交換
+ *   （相手ごとに受信済みのシーケンス番号を保存、双方の変更は`updated_at`の新しい方を採用）
+ * - ジャーナルがコンパクションで失われている場合は全件のスナップショットを送り、
+ *   受信側はスナップショットにない（相手が削除した）プロンプトを削除する
+ * - シークレットにしたプロンプトは送らず、相手の端末では削除として扱う
+ *
+ * ピン留めは端末ごとのホットキー設定のため同期しない
+ */
+mod protocol;
+
+use ed25519_dalek::{SigningKey, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use std::collections::{HashMap, HashSet};
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+use tokio::net::{TcpListener, TcpStream};
+
+use crate::database::{
+    delete_prompt_in, fetch_changes_since, is_valid_workspace_id, Prompt, PromptStatus, PromptVisibility,
+};
+use crate::environment::Environment;
+use protocol::{DeviceIdentity, Hello, ProtocolError, SecureChannel};
+
+/// mDNSのサービスタイプ
+const SERVICE_TYPE: &str = "_prompalette-sync._tcp.local.";
+
+/// 同期設定ファイル名（アプリデータディレクトリに保存）
+const CONFIG_FILENAME: &str = "lan_sync.json";
+
+/// ペアリングコードの有効期間
+const PAIRING_CODE_TTL: Duration = Duration::from_secs(120);
+
+/// ペアリングのハンドシェイクの処理時間の上限
+const PAIRING_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// ペアリング要求を受け付ける最小間隔
+const PAIRING_ATTEMPT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// 1つのコードで受け付けるペアリング要求の最大数
+const MAX_PAIRING_ATTEMPTS: u32 = 5;
+
+/// ペアリングを受け付けていない場合の拒否理由
+const PAIRING_NOT_ACTIVE: &str = "Pairing is not active on this device";
+
+/// 1接続あたりの処理時間の上限
+const CONNECTION_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// 接続確立のタイムアウト
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// 自動同期の間隔
+const AUTO_SYNC_INTERVAL: Duration = Duration::from_secs(60);
+
+/// 1回のバッチで送る変更の最大数
+const BATCH_SIZE: u32 = 500;
+
+/// 本文の最大長（create_promptの制限と同じ）
+const MAX_CONTENT_LENGTH: usize = 100_000;
+
+/// 実行中の同期サービス
+static SERVICE: Mutex<Option<RunningService>> = Mutex::new(None);
+
+/// ペアリング済みの端末
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrustedPeer {
+    pub device_id: String,
+    pub name: String,
+    /// ed25519公開鍵（Base64）
+    pub public_key: String,
+    pub fingerprint: String,
+    /// 相手の変更ジャーナルの受信済みシーケンス番号
+    #[serde(default)]
+    pub last_seq: i64,
+    pub last_synced_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// 最後に接続できたアドレス（mDNSで見つからない場合に使用）
+    pub last_address: Option<String>,
+}
+
+/// LAN同期設定（永続化）
+///
+/// 端末の秘密鍵を含むため、Unixではファイルの権限を0600にする
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LanSyncConfig {
+    pub enabled: bool,
+    pub device_id: Option<String>,
+    pub device_name: Option<String>,
+    /// ed25519秘密鍵（Base64）
+    pub signing_key: Option<String>,
+    #[serde(default)]
+    pub peers: Vec<TrustedPeer>,
+}
+
+impl LanSyncConfig {
+    fn config_path() -> Result<PathBuf, String> {
+        let env = Environment::current();
+        let data_dir = env.data_dir().map_err(|e| e.to_string())?;
+        Ok(data_dir.join(CONFIG_FILENAME))
+    }
+
+    fn load_from(path: &PathBuf) -> Result<Self, String> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        serde_json::from_str(&content).map_err(|e| format!("Invalid LAN sync config: {e}"))
+    }
+
+    fn save_to(&self, path: &PathBuf) -> Result<(), String> {
+        let content = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        std::fs::write(path, content).map_err(|e| format!("Failed to save LAN sync config: {e}"))?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))
+                .map_err(|e| format!("Failed to restrict LAN sync config permissions: {e}"))?;
+        }
+        Ok(())
+    }
+
+    /// 設定ファイルから読み込み（存在しない場合はデフォルト）
+    pub fn load() -> Result<Self, String> {
+        Self::load_from(&Self::config_path()?)
+    }
+
+    /// 設定ファイルへ保存
+    pub fn save(&self) -> Result<(), String> {
+        self.save_to(&Self::config_path()?)
+    }
+
+    /// 端末の識別情報を取得（未作成なら生成して設定に記録）
+    fn identity(&mut self) -> Result<DeviceIdentity, String> {
+        let name = self.device_name.clone().unwrap_or_else(default_device_name);
+        if let (Some(device_id), Some(signing_key)) = (&self.device_id, &self.signing_key) {
+            use base64::{engine::general_purpose, Engine as _};
+            let bytes: [u8; 32] = general_purpose::STANDARD
+                .decode(signing_key)
+                .ok()
+                .and_then(|bytes| bytes.try_into().ok())
+                .ok_or("Invalid device key in LAN sync config")?;
+            return Ok(DeviceIdentity {
+                device_id: device_id.clone(),
+                name,
+                signing_key: SigningKey::from_bytes(&bytes),
+            });
+        }
+
+        let identity = DeviceIdentity::generate(&name);
+        use base64::{engine::general_purpose, Engine as _};
+        self.device_id = Some(identity.device_id.clone());
+        self.signing_key = Some(general_purpose::STANDARD.encode(identity.signing_key.to_bytes()));
+        Ok(identity)
+    }
+}
+
+/// 端末名のデフォルト（ホスト名）
+fn default_device_name() -> String {
+    std::env::var("HOSTNAME")
+        .or_else(|_| std::env::var("COMPUTERNAME"))
+        .ok()
+        .filter(|name| !name.trim().is_empty())
+        .unwrap_or_else(|| "PromPalette".to_string())
+}
+
+/// 同期で送受信するプロンプト（ピン留めは含まない）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptSnapshot {
+    pub id: String,
+    pub title: Option<String>,
+    pub content: String,
+    pub tags: Vec<String>,
+    pub quick_access_key: Option<String>,
+    pub status: PromptStatus,
+    pub visibility: PromptVisibility,
+    pub workspace_id: Option<String>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl From<Prompt> for PromptSnapshot {
+    fn from(prompt: Prompt) -> Self {
+        let tags = prompt
+            .tags
+            .as_deref()
+            .and_then(|tags| serde_json::from_str(tags).ok())
+            .unwrap_or_default();
+        Self {
+            id: prompt.id,
+            title: prompt.title,
+            content: prompt.content,
+            tags,
+            quick_access_key: prompt.quick_access_key,
+            status: prompt.status,
+            visibility: prompt.visibility,
+            workspace_id: prompt.workspace_id,
+            created_at: prompt.created_at,
+            updated_at: prompt.updated_at,
+        }
+    }
+}
+
+/// 変更1件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+enum SyncEntry {
+    Upsert { prompt: PromptSnapshot },
+    Delete { id: String, deleted_at: chrono::DateTime<chrono::Utc> },
+}
+
+/// 暗号化チャネル上のメッセージ
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum SyncMessage {
+    /// 指定シーケンス番号より後の変更を要求
+    Request { since: i64 },
+    /// 変更のバッチ（`has_more`がfalseになるまで続く）
+    ///
+    /// `snapshot`がtrueの場合は送信側の全件（シークレットを除く）。
+    /// `known_seq`は送信側が受信済みの、受信側のジャーナルのシーケンス番号
+    Batch {
+        entries: Vec<SyncEntry>,
+        next_seq: i64,
+        has_more: bool,
+        #[serde(default)]
+        snapshot: bool,
+        #[serde(default)]
+        known_seq: i64,
+    },
+    /// 受信側が全ての変更を反映し終えたことの通知
+    Done,
+}
+
+/// 1端末との同期結果
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct LanSyncReport {
+    pub peer_device_id: String,
+    pub peer_name: String,
+    /// 送信した変更数
+    pub sent: usize,
+    /// 反映した変更数（作成・更新）
+    pub received: usize,
+    /// 反映した削除数
+    pub deleted: usize,
+    /// ローカルの方が新しい等の理由で反映しなかった変更数
+    pub skipped: usize,
+}
+
+/// 同期サービスからの通知
+#[derive(Debug, Clone)]
+pub enum LanSyncEvent {
+    Paired(TrustedPeer),
+    Synced(LanSyncReport),
+}
+
+type Notifier = Box<dyn Fn(LanSyncEvent) + Send + Sync>;
+
+/// 表示中のペアリングコード
+struct PendingPairing {
+    code: String,
+    expires_at: std::time::Instant,
+    /// コードを使用中のハンドシェイク（同時に1つだけ）
+    session: Option<u64>,
+    /// 受け付けたペアリング要求の数
+    attempts: u32,
+    last_attempt: Option<std::time::Instant>,
+}
+
+/// 同期ノード（1端末分の状態）
+///
+/// テストではループバック上に2つ起動して同期できる
+pub(crate) struct LanSyncNode {
+    identity: DeviceIdentity,
+    pool: SqlitePool,
+    peers: Mutex<Vec<TrustedPeer>>,
+    pairing: Mutex<Option<PendingPairing>>,
+    next_pairing_session: std::sync::atomic::AtomicU64,
+    config_path: Option<PathBuf>,
+    notifier: Option<Notifier>,
+}
+
+impl LanSyncNode {
+    pub(crate) fn new(
+        identity: DeviceIdentity,
+        pool: SqlitePool,
+        peers: Vec<TrustedPeer>,
+        config_path: Option<PathBuf>,
+        notifier: Option<Notifier>,
+    ) -> Self {
+        Self {
+            identity,
+            pool,
+            peers: Mutex::new(peers),
+            pairing: Mutex::new(None),
+            next_pairing_session: std::sync::atomic::AtomicU64::new(0),
+            config_path,
+            notifier,
+        }
+    }
+
+    fn notify(&self, event: LanSyncEvent) {
+        if let Some(notifier) = &self.notifier {
+            notifier(event);
+        }
+    }
+
+    pub(crate) fn peers(&self) -> Vec<TrustedPeer> {
+        self.peers.lock().map(|peers| peers.clone()).unwrap_or_default()
+    }
+
+    fn peer(&self, device_id: &str) -> Option<TrustedPeer> {
+        self.peers().into_iter().find(|peer| peer.device_id == device_id)
+    }
+
+    fn peer_key(&self, device_id: &str) -> Option<VerifyingKey> {
+        self.peer(device_id)
+            .and_then(|peer| protocol::decode_public_key(&peer.public_key).ok())
+    }
+
+    /// ペアリング済み端末の情報を更新して保存
+    fn update_peer(&self, device_id: &str, update: impl FnOnce(&mut TrustedPeer)) {
+        if let Ok(mut peers) = self.peers.lock() {
+            if let Some(peer) = peers.iter_mut().find(|peer| peer.device_id == device_id) {
+                update(peer);
+            }
+        }
+        self.persist_peers();
+    }
+
+    fn persist_peers(&self) {
+        let Some(path) = &self.config_path else {
+            return;
+        };
+        let result = LanSyncConfig::load_from(path).and_then(|mut config| {
+            config.peers = self.peers();
+            config.save_to(path)
+        });
+        if let Err(e) = result {
+            eprintln!("Failed to persist LAN sync peers: {e}");
+        }
+    }
+
+    fn add_peer(&self, paired: protocol::PairedPeer, address: Option<String>) -> TrustedPeer {
+        use base64::{engine::general_purpose, Engine as _};
+        let peer = TrustedPeer {
+            device_id: paired.device_id.clone(),
+            name: paired.name,
+            public_key: general_purpose::STANDARD.encode(paired.public_key.as_bytes()),
+            fingerprint: protocol::fingerprint(&paired.public_key),
+            last_seq: 0,
+            last_synced_at: None,
+            last_address: address,
+        };
+        if let Ok(mut peers) = self.peers.lock() {
+            // 再ペアリングの場合は鍵を置き換え、最初から同期し直す
+            peers.retain(|existing| existing.device_id != peer.device_id);
+            peers.push(peer.clone());
+        }
+        self.persist_peers();
+        peer
+    }
+
+    /// ペアリング済み端末を削除
+    pub(crate) fn forget_peer(&self, device_id: &str) -> bool {
+        let removed = self
+            .peers
+            .lock()
+            .map(|mut peers| {
+                let before = peers.len();
+                peers.retain(|peer| peer.device_id != device_id);
+                peers.len() != before
+            })
+            .unwrap_or(false);
+        if removed {
+            self.persist_peers();
+        }
+        removed
+    }
+
+    /// ペアリングコードを発行（有効期間内に1回のハンドシェイクでのみ使用可能）
+    pub(crate) fn start_pairing(&self) -> (String, chrono::DateTime<chrono::Utc>) {
+        use rand::Rng;
+        let code = format!("{:06}", rand::rngs::OsRng.gen_range(0..1_000_000));
+        if let Ok(mut pairing) = self.pairing.lock() {
+            *pairing = Some(PendingPairing {
+                code: code.clone(),
+                expires_at: std::time::Instant::now() + PAIRING_CODE_TTL,
+                session: None,
+                attempts: 0,
+                last_attempt: None,
+            });
+        }
+        let expires_at = chrono::Utc::now() + chrono::Duration::from_std(PAIRING_CODE_TTL).unwrap_or_default();
+        (code, expires_at)
+    }
+
+    /// ペアリング要求にコードを割り当てる（拒否する場合は理由を返す）
+    ///
+    /// コードは割り当てたハンドシェイク専用になり、他の接続からの要求は拒否する。
+    /// コードを消費するのは相手にMACを開示した時点（`consume_pairing`）で、
+    /// それより前に切断された場合は`release_pairing`で再び使えるようにする
+    fn reserve_pairing(&self) -> Result<(u64, String), &'static str> {
+        let mut pairing = self.pairing.lock().map_err(|_| PAIRING_NOT_ACTIVE)?;
+        let now = std::time::Instant::now();
+        let Some(pending) = pairing.as_mut() else {
+            return Err(PAIRING_NOT_ACTIVE);
+        };
+        if pending.expires_at <= now {
+            *pairing = None;
+            return Err(PAIRING_NOT_ACTIVE);
+        }
+        if pending.session.is_some() {
+            return Err("Another pairing is in progress");
+        }
+        if pending.last_attempt.is_some_and(|at| now.duration_since(at) < PAIRING_ATTEMPT_INTERVAL) {
+            return Err("Too many pairing attempts, try again shortly");
+        }
+        if pending.attempts >= MAX_PAIRING_ATTEMPTS {
+            *pairing = None;
+            return Err("Too many pairing attempts, start pairing again");
+        }
+
+        let session = self
+            .next_pairing_session
+            .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        pending.session = Some(session);
+        pending.attempts += 1;
+        pending.last_attempt = Some(now);
+        Ok((session, pending.code.clone()))
+    }
+
+    /// コードを無効化（MACを開示したハンドシェイクから呼ぶ）
+    fn consume_pairing(&self, session: u64) {
+        if let Ok(mut pairing) = self.pairing.lock() {
+            if pairing.as_ref().is_some_and(|pending| pending.session == Some(session)) {
+                *pairing = None;
+            }
+        }
+    }
+
+    /// ハンドシェイクの終了時にコードの割り当てを解除
+    fn release_pairing(&self, session: u64) {
+        if let Ok(mut pairing) = self.pairing.lock() {
+            if let Some(pending) = pairing.as_mut().filter(|pending| pending.session == Some(session)) {
+                pending.session = None;
+            }
+        }
+    }
+
+    /// 接続の待ち受け
+    pub(crate) async fn serve(self: Arc<Self>, listener: TcpListener) {
+        loop {
+            let (stream, address) = match listener.accept().await {
+                Ok(accepted) => accepted,
+                Err(e) => {
+                    eprintln!("LAN sync accept failed: {e}");
+                    tokio::time::sleep(Duration::from_secs(1)).await;
+                    continue;
+                }
+            };
+            let node = Arc::clone(&self);
+            tokio::spawn(async move {
+                match tokio::time::timeout(CONNECTION_TIMEOUT, node.handle_connection(stream)).await {
+                    Ok(Ok(())) => {}
+                    Ok(Err(e)) => eprintln!("LAN sync connection from {address} failed: {e}"),
+                    Err(_) => eprintln!("LAN sync connection from {address} timed out"),
+                }
+            });
+        }
+    }
+
+    async fn handle_connection(&self, mut stream: TcpStream) -> Result<(), String> {
+        let hello: Hello = protocol::recv_plain(&mut stream).await.map_err(|e| e.to_string())?;
+        match hello {
+            Hello::Pair { .. } => {
+                let (session, code) = match self.reserve_pairing() {
+                    Ok(reserved) => reserved,
+                    Err(reason) => {
+                        protocol::send_rejection(&mut stream, reason).await.ok();
+                        return Err(format!("Pairing request rejected: {reason}"));
+                    }
+                };
+                let result = tokio::time::timeout(
+                    PAIRING_HANDSHAKE_TIMEOUT,
+                    protocol::pair_responder(&mut stream, &self.identity, hello, &code, || {
+                        self.consume_pairing(session)
+                    }),
+                )
+                .await;
+                self.release_pairing(session);
+                let paired = result
+                    .map_err(|_| "Pairing handshake timed out".to_string())?
+                    .map_err(|e| e.to_string())?;
+                let peer = self.add_peer(paired, None);
+                self.notify(LanSyncEvent::Paired(peer));
+                Ok(())
+            }
+            Hello::Sync { .. } => {
+                let mut channel = SecureChannel::accept(stream, &self.identity, hello, |id| self.peer_key(id))
+                    .await
+                    .map_err(|e| e.to_string())?;
+                let report = self.exchange_as_responder(&mut channel).await?;
+                self.notify(LanSyncEvent::Synced(report));
+                Ok(())
+            }
+        }
+    }
+
+    /// コードを入力して相手とペアリング
+    pub(crate) async fn pair_with(&self, address: &str, code: &str) -> Result<TrustedPeer, String> {
+        let code = code.trim();
+        if code.len() != 6 || !code.chars().all(|c| c.is_ascii_digit()) {
+            return Err("Pairing code must be 6 digits".to_string());
+        }
+        let mut stream = connect(address).await?;
+        let paired = protocol::pair_initiator(&mut stream, &self.identity, code)
+            .await
+            .map_err(|e| e.to_string())?;
+        let peer = self.add_peer(paired, Some(address.to_string()));
+        self.notify(LanSyncEvent::Paired(peer.clone()));
+        Ok(peer)
+    }
+
+    /// ペアリング済みの相手と同期
+    pub(crate) async fn sync_with(&self, address: &str) -> Result<LanSyncReport, String> {
+        let stream = connect(address).await?;
+        let mut channel = SecureChannel::connect(stream, &self.identity, |id| self.peer_key(id))
+            .await
+            .map_err(|e| e.to_string())?;
+        let peer_id = channel.peer_device_id().to_string();
+        let peer = self.peer(&peer_id).ok_or("Peer is not paired")?;
+
+        let mut report = LanSyncReport {
+            peer_device_id: peer.device_id.clone(),
+            peer_name: peer.name.clone(),
+            ..Default::default()
+        };
+
+        // 相手の変更を受信してから、自分の変更を送信
+        send(&mut channel, &SyncMessage::Request { since: peer.last_seq }).await?;
+        self.receive_batches(&mut channel, &peer_id, &mut report).await?;
+        let since = match recv(&mut channel).await? {
+            SyncMessage::Request { since } => since,
+            _ => return Err("Unexpected message from peer".to_string()),
+        };
+        report.sent = self.send_batches(&mut channel, since).await?;
+        match recv(&mut channel).await? {
+            SyncMessage::Done => {}
+            _ => return Err("Peer did not confirm the sync".to_string()),
+        }
+
+        let address = address.to_string();
+        self.update_peer(&peer_id, |peer| {
+            peer.last_synced_at = Some(chrono::Utc::now());
+            peer.last_address = Some(address);
+        });
+        self.notify(LanSyncEvent::Synced(report.clone()));
+        Ok(report)
+    }
+
+    async fn exchange_as_responder(&self, channel: &mut SecureChannel<TcpStream>) -> Result<LanSyncReport, String> {
+        let peer_id = channel.peer_device_id().to_string();
+        let peer = self.peer(&peer_id).ok_or("Peer is not paired")?;
+        let mut report = LanSyncReport {
+            peer_device_id: peer.device_id.clone(),
+            peer_name: peer.name.clone(),
+            ..Default::default()
+        };
+
+        let since = match recv(channel).await? {
+            SyncMessage::Request { since } => since,
+            _ => return Err("Unexpected message from peer".to_string()),
+        };
+        report.sent = self.send_batches(channel, since).await?;
+
+        send(channel, &SyncMessage::Request { since: peer.last_seq }).await?;
+        self.receive_batches(channel, &peer_id, &mut report).await?;
+        send(channel, &SyncMessage::Done).await?;
+
+        self.update_peer(&peer_id, |peer| peer.last_synced_at = Some(chrono::Utc::now()));
+        Ok(report)
+    }
+
+    /// 変更ジャーナルからバッチを作成して送信
+    async fn send_batches(&self, channel: &mut SecureChannel<TcpStream>, mut since: i64) -> Result<usize, String> {
+        let known_seq = self.peer(channel.peer_device_id()).map_or(0, |peer| peer.last_seq);
+        let mut sent = 0;
+        loop {
+            let (entries, next_seq, has_more, snapshot) = build_batch(&self.pool, since).await?;
+            sent += entries.len();
+            send(channel, &SyncMessage::Batch { entries, next_seq, has_more, snapshot, known_seq }).await?;
+            if !has_more {
+                return Ok(sent);
+            }
+            since = next_seq;
+        }
+    }
+
+    /// バッチを受信して反映し、相手ごとのカーソルを進める
+    async fn receive_batches(
+        &self,
+        channel: &mut SecureChannel<TcpStream>,
+        peer_id: &str,
+        report: &mut LanSyncReport,
+    ) -> Result<(), String> {
+        loop {
+            let (entries, next_seq, has_more, snapshot, known_seq) = match recv(channel).await? {
+                SyncMessage::Batch { entries, next_seq, has_more, snapshot, known_seq } => {
+                    (entries, next_seq, has_more, snapshot, known_seq)
+                }
+                _ => return Err("Unexpected message from peer".to_string()),
+            };
+            let snapshot_ids: Option<HashSet<String>> = snapshot.then(|| {
+                entries
+                    .iter()
+                    .filter_map(|entry| match entry {
+                        SyncEntry::Upsert { prompt } => Some(prompt.id.clone()),
+                        SyncEntry::Delete { .. } => None,
+                    })
+                    .collect()
+            });
+            for entry in entries {
+                apply_entry(&self.pool, entry, report).await?;
+            }
+            if let Some(snapshot_ids) = snapshot_ids {
+                remove_missing_from_snapshot(&self.pool, &snapshot_ids, known_seq, report).await?;
+            }
+            self.update_peer(peer_id, |peer| peer.last_seq = next_seq);
+            if !has_more {
+                return Ok(());
+            }
+        }
+    }
+}
+
+async fn connect(address: &str) -> Result<TcpStream, String> {
+    let address: SocketAddr = address
+        .parse()
+        .map_err(|_| format!("Invalid peer address: {address}"))?;
+    tokio::time::timeout(CONNECT_TIMEOUT, TcpStream::connect(address))
+        .await
+        .map_err(|_| format!("Connection to {address} timed out"))?
+        .map_err(|e| format!("Failed to connect to {address}: {e}"))
+}
+
+async fn send(channel: &mut SecureChannel<TcpStream>, message: &SyncMessage) -> Result<(), String> {
+    channel.send(message).await.map_err(|e: ProtocolError| e.to_string())
+}
+
+async fn recv(channel: &mut SecureChannel<TcpStream>) -> Result<SyncMessage, String> {
+    channel.recv().await.map_err(|e: ProtocolError| e.to_string())
+}
+
+/// 指定シーケンス番号以降の変更をバッチにする（エントリ, 次のシーケンス番号, 続きの有無, スナップショットか）
+///
+/// ジャーナルがコンパクションで失われている場合は全件のスナップショットを送る
+/// シークレットプロンプトは送らず、削除として送る
+async fn build_batch(pool: &SqlitePool, since: i64) -> Result<(Vec<SyncEntry>, i64, bool, bool), String> {
+    let change_set = fetch_changes_since(pool, since, Some(BATCH_SIZE))
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if change_set.reset_required {
+        // スナップショット取得前の位置を記録（以降の変更は次回送られる）
+        let next_seq: i64 = sqlx::query_scalar("SELECT COALESCE(MAX(seq), 0) FROM changes")
+            .fetch_one(pool)
+            .await
+            .map_err(|e| e.to_string())?;
+        let prompts = sqlx::query_as::<_, Prompt>("SELECT * FROM prompts WHERE is_secret = 0")
+            .fetch_all(pool)
+            .await
+            .map_err(|e| e.to_string())?;
+        let entries = prompts
+            .into_iter()
+            .map(|prompt| SyncEntry::Upsert { prompt: prompt.into() })
+            .collect();
+        return Ok((entries, next_seq.max(change_set.next_seq), false, true));
+    }
+
+    // 同じプロンプトの変更は最新の1件にまとめる
+    let mut latest: Vec<(String, String)> = Vec::new();
+    for change in change_set.changes {
+        latest.retain(|(id, _)| id != &change.prompt_id);
+        latest.push((change.prompt_id, change.changed_at));
+    }
+
+    let mut entries = Vec::with_capacity(latest.len());
+    for (id, changed_at) in latest {
+        let prompt = sqlx::query_as::<_, Prompt>("SELECT * FROM prompts WHERE id = $1")
+            .bind(&id)
+            .fetch_optional(pool)
+            .await
+            .map_err(|e| e.to_string())?;
+        entries.push(match prompt {
+            // 相手の端末に残っている以前の内容は削除する
+            Some(prompt) if prompt.is_secret => SyncEntry::Delete { id, deleted_at: prompt.updated_at },
+            Some(prompt) => SyncEntry::Upsert { prompt: prompt.into() },
+            None => SyncEntry::Delete {
+                id,
+                deleted_at: chrono::DateTime::parse_from_rfc3339(&changed_at)
+                    .map(|at| at.with_timezone(&chrono::Utc))
+                    .unwrap_or_else(|_| chrono::Utc::now()),
+            },
+        });
+    }
+
+    Ok((entries, change_set.next_seq, change_set.has_more, false))
+}
+
+/// スナップショットに含まれないプロンプトを削除（相手側で削除・シークレット化されたもの）
+///
+/// 相手が受信済みの位置（`known_seq`）より後にこちらで変更したプロンプトは、
+/// 相手がまだ受け取っていないだけなので残す（この後の送信で相手に届く）
+async fn remove_missing_from_snapshot(
+    pool: &SqlitePool,
+    snapshot_ids: &HashSet<String>,
+    known_seq: i64,
+    report: &mut LanSyncReport,
+) -> Result<(), String> {
+    // コンパクションでエントリが破棄されたプロンプトは破棄した位置までに変更されたとみなす
+    // （ジャーナル導入前から変更のないプロンプトは相手に届いたか分からないため残す）
+    let candidates: Vec<(String,)> = sqlx::query_as(
+        r"
+        SELECT p.id FROM prompts p
+        WHERE p.is_secret = 0
+          AND COALESCE(
+                (SELECT MAX(c.seq) FROM changes c WHERE c.prompt_id = p.id),
+                (SELECT NULLIF(purged_through, 0) FROM change_journal_meta WHERE id = 1)
+              ) <= $1
+        ",
+    )
+    .bind(known_seq)
+    .fetch_all(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    for (id,) in candidates.into_iter().filter(|(id,)| !snapshot_ids.contains(id)) {
+        delete_prompt_in(pool, &id).await.map_err(|e| e.to_string())?;
+        report.deleted += 1;
+    }
+    Ok(())
+}
+
+/// ローカルのプロンプトの更新日時とシークレットかどうか
+async fn local_state(pool: &SqlitePool, id: &str) -> Result<Option<(chrono::DateTime<chrono::Utc>, bool)>, String> {
+    sqlx::query_as("SELECT updated_at, is_secret FROM prompts WHERE id = $1")
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 受信した変更を反映（`updated_at`が新しい方を採用）
+///
+/// ローカルでシークレットにしたプロンプトは変更しない
+async fn apply_entry(pool: &SqlitePool, entry: SyncEntry, report: &mut LanSyncReport) -> Result<(), String> {
+    match entry {
+        SyncEntry::Upsert { prompt } => {
+            let local = local_state(pool, &prompt.id).await?;
+            let is_valid = !prompt.content.trim().is_empty()
+                && prompt.content.len() <= MAX_CONTENT_LENGTH
+                && !prompt.id.trim().is_empty();
+            if !is_valid || local.is_some_and(|(updated_at, is_secret)| is_secret || updated_at >= prompt.updated_at) {
+                report.skipped += 1;
+                return Ok(());
+            }
+
+            let tags = serde_json::to_string(&prompt.tags).map_err(|e| e.to_string())?;
+            let workspace_id = prompt.workspace_id.filter(|id| is_valid_workspace_id(id));
+            sqlx::query(
+                r"
+                INSERT INTO prompts (id, title, content, tags, quick_access_key, created_at, updated_at, pinned_position, pinned_at, status, visibility, workspace_id)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, NULL, NULL, $8, $9, $10)
+                ON CONFLICT(id) DO UPDATE SET
+                    title = excluded.title,
+                    content = excluded.content,
+                    tags = excluded.tags,
+                    quick_access_key = excluded.quick_access_key,
+                    updated_at = excluded.updated_at,
+                    status = excluded.status,
+                    visibility = excluded.visibility,
+                    workspace_id = excluded.workspace_id
+                ",
+            )
+            .bind(&prompt.id)
+            .bind(&prompt.title)
+            .bind(&prompt.content)
+            .bind(tags)
+            .bind(&prompt.quick_access_key)
+            .bind(prompt.created_at)
+            .bind(prompt.updated_at)
+            .bind(prompt.status)
+            .bind(prompt.visibility)
+            .bind(workspace_id)
+            .execute(pool)
+            .await
+            .map_err(|e| e.to_string())?;
+            report.received += 1;
+        }
+        SyncEntry::Delete { id, deleted_at } => {
+            match local_state(pool, &id).await? {
+                // 削除後にこちらで編集されている場合は残す（次回の同期で相手に戻る）
+                Some((local, is_secret)) if is_secret || local > deleted_at => report.skipped += 1,
+                Some(_) => {
+                    delete_prompt_in(pool, &id).await.map_err(|e| e.to_string())?;
+                    report.deleted += 1;
+                }
+                None => {}
+            }
+        }
+    }
+    Ok(())
+}
+
+/// mDNSで見つかった端末
+#[derive(Debug, Clone, Serialize)]
+pub struct DiscoveredPeer {
+    pub device_id: String,
+    pub name: String,
+    pub fingerprint: Option<String>,
+    pub addresses: Vec<String>,
+}
+
+/// mDNSによる広告と探索
+struct Discovery {
+    daemon: mdns_sd::ServiceDaemon,
+    fullname: String,
+    peers: Arc<Mutex<HashMap<String, DiscoveredPeer>>>,
+    task: tauri::async_runtime::JoinHandle<()>,
+}
+
+impl Discovery {
+    fn start(identity: &DeviceIdentity, port: u16) -> Result<Self, String> {
+        let daemon = mdns_sd::ServiceDaemon::new().map_err(|e| format!("Failed to start mDNS: {e}"))?;
+
+        let fingerprint = identity.fingerprint();
+        let host_name = format!("prompalette-{}.local.", &identity.device_id[..8.min(identity.device_id.len())]);
+        let properties = [
+            ("device_id", identity.device_id.as_str()),
+            ("name", identity.name.as_str()),
+            ("fingerprint", fingerprint.as_str()),
+        ];
+        let service = mdns_sd::ServiceInfo::new(SERVICE_TYPE, &identity.device_id, &host_name, "", port, &properties[..])
+            .map_err(|e| format!("Invalid mDNS service: {e}"))?
+            .enable_addr_auto();
+        let fullname = service.get_fullname().to_string();
+        daemon
+            .register(service)
+            .map_err(|e| format!("Failed to advertise LAN sync service: {e}"))?;
+
+        let receiver = daemon
+            .browse(SERVICE_TYPE)
+            .map_err(|e| format!("Failed to browse LAN sync services: {e}"))?;
+        let peers: Arc<Mutex<HashMap<String, DiscoveredPeer>>> = Arc::default();
+        let own_id = identity.device_id.clone();
+        let found = Arc::clone(&peers);
+        let task = tauri::async_runtime::spawn(async move {
+            while let Ok(event) = receiver.recv_async().await {
+                match event {
+                    mdns_sd::ServiceEvent::ServiceResolved(info) => {
+                        let Some(device_id) = info.get_property_val_str("device_id") else {
+                            continue;
+                        };
+                        if device_id == own_id {
+                            continue;
+                        }
+                        let peer = DiscoveredPeer {
+                            device_id: device_id.to_string(),
+                            name: info.get_property_val_str("name").unwrap_or(device_id).to_string(),
+                            fingerprint: info.get_property_val_str("fingerprint").map(str::to_string),
+                            addresses: info
+                                .get_addresses()
+                                .iter()
+                                .map(|ip| SocketAddr::new(*ip, info.get_port()).to_string())
+                                .collect(),
+                        };
+                        if let Ok(mut peers) = found.lock() {
+                            peers.insert(info.get_fullname().to_string(), peer);
+                        }
+                    }
+                    mdns_sd::ServiceEvent::ServiceRemoved(_, fullname) => {
+                        if let Ok(mut peers) = found.lock() {
+                            peers.remove(&fullname);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        });
+
+        Ok(Self { daemon, fullname, peers, task })
+    }
+
+    fn peers(&self) -> Vec<DiscoveredPeer> {
+        self.peers.lock().map(|peers| peers.values().cloned().collect()).unwrap_or_default()
+    }
+
+    fn stop(self) {
+        self.task.abort();
+        if let Err(e) = self.daemon.unregister(&self.fullname) {
+            eprintln!("Failed to unregister LAN sync service: {e}");
+        }
+        if let Err(e) = self.daemon.shutdown() {
+            eprintln!("Failed to stop mDNS: {e}");
+        }
+    }
+}
+
+/// 実行中のサービス
+struct RunningService {
+    node: Arc<LanSyncNode>,
+    port: u16,
+    accept_task: tauri::async_runtime::JoinHandle<()>,
+    sync_task: tauri::async_runtime::JoinHandle<()>,
+    discovery: Option<Discovery>,
+}
+
+impl RunningService {
+    fn stop(self) {
+        self.accept_task.abort();
+        self.sync_task.abort();
+        if let Some(discovery) = self.discovery {
+            discovery.stop();
+        }
+    }
+}
+
+/// 同期相手の接続先候補（mDNSで見つかったアドレス → 最後に接続できたアドレス）
+fn peer_addresses(peer: &TrustedPeer, discovered: &[DiscoveredPeer]) -> Vec<String> {
+    discovered
+        .iter()
+        .filter(|found| found.device_id == peer.device_id)
+        .flat_map(|found| found.addresses.iter().cloned())
+        .chain(peer.last_address.clone())
+        .collect()
+}
+
+/// ペアリング済みの全端末と同期（接続できない端末はスキップ）
+async fn sync_all(node: &LanSyncNode, discovered: &[DiscoveredPeer]) -> Vec<LanSyncReport> {
+    let mut reports = Vec::new();
+    for peer in node.peers() {
+        for address in peer_addresses(&peer, discovered) {
+            match node.sync_with(&address).await {
+                Ok(report) => {
+                    reports.push(report);
+                    break;
+                }
+                Err(e) => eprintln!("LAN sync with {} at {address} failed: {e}", peer.name),
+            }
+        }
+    }
+    reports
+}
+
+fn discovered_peers() -> Vec<DiscoveredPeer> {
+    SERVICE
+        .lock()
+        .ok()
+        .and_then(|service| service.as_ref().and_then(|s| s.discovery.as_ref().map(Discovery::peers)))
+        .unwrap_or_default()
+}
+
+fn running_node() -> Result<Arc<LanSyncNode>, String> {
+    SERVICE
+        .lock()
+        .map_err(|_| "LAN sync state is unavailable".to_string())?
+        .as_ref()
+        .map(|service| Arc::clone(&service.node))
+        .ok_or_else(|| "LAN sync is not enabled".to_string())
+}
+
+/// 同期サービスを起動
+async fn start_service(app_handle: AppHandle) -> Result<(), String> {
+    stop_service();
+
+    let path = LanSyncConfig::config_path()?;
+    let mut config = LanSyncConfig::load_from(&path)?;
+    let identity = config.identity()?;
+    config.save_to(&path)?;
+
+    let pool = crate::database::try_get_db_pool()?;
+    let notifier: Notifier = Box::new(move |event| {
+        let result = match &event {
+            LanSyncEvent::Paired(peer) => app_handle.emit("lan-sync-paired", peer),
+            LanSyncEvent::Synced(report) => {
+                crate::tray::refresh_tray(&app_handle);
+                app_handle.emit("lan-sync-completed", report)
+            }
+        };
+        if let Err(e) = result {
+            eprintln!("Failed to emit LAN sync event: {e}");
+        }
+    });
+    let node = Arc::new(LanSyncNode::new(identity.clone(), pool, config.peers, Some(path), Some(notifier)));
+
+    let listener = TcpListener::bind(("0.0.0.0", 0))
+        .await
+        .map_err(|e| format!("Failed to start LAN sync listener: {e}"))?;
+    let port = listener.local_addr().map_err(|e| e.to_string())?.port();
+    let accept_task = tauri::async_runtime::spawn(Arc::clone(&node).serve(listener));
+
+    // mDNSが使えない環境でもアドレス指定でのペアリング・同期は可能
+    let discovery = match Discovery::start(&identity, port) {
+        Ok(discovery) => Some(discovery),
+        Err(e) => {
+            eprintln!("LAN sync discovery unavailable: {e}");
+            None
+        }
+    };
+
+    let sync_node = Arc::clone(&node);
+    let sync_task = tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(AUTO_SYNC_INTERVAL).await;
+            sync_all(&sync_node, &discovered_peers()).await;
+        }
+    });
+
+    println!("LAN sync listening on port {port}");
+    if let Ok(mut service) = SERVICE.lock() {
+        *service = Some(RunningService { node, port, accept_task, sync_task, discovery });
+    }
+    Ok(())
+}
+
+/// 同期サービスを停止（データベースのロック時にも使用）
+pub(crate) fn stop_service() {
+    let service = SERVICE.lock().ok().and_then(|mut service| service.take());
+    if let Some(service) = service {
+        service.stop();
+    }
+}
+
+/// 起動時に設定に従って同期サービスを再開
+pub fn resume_from_config(app_handle: &AppHandle) {
+    match LanSyncConfig::load() {
+        Ok(config) if config.enabled => {
+            let app_handle = app_handle.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = start_service(app_handle).await {
+                    eprintln!("Failed to resume LAN sync: {e}");
+                }
+            });
+        }
+        Ok(_) => {}
+        Err(e) => eprintln!("Failed to load LAN sync config: {e}"),
+    }
+}
+
+/// LAN同期を有効化
+///
+/// device_name: 他の端末に表示される名前（省略時はホスト名）
+#[tauri::command]
+pub async fn enable_lan_sync(app_handle: AppHandle, device_name: Option<String>) -> Result<serde_json::Value, String> {
+    let mut config = LanSyncConfig::load()?;
+    if let Some(name) = device_name.map(|name| name.trim().to_string()).filter(|name| !name.is_empty()) {
+        if name.chars().count() > 64 {
+            return Err("Device name too long (max 64 characters)".to_string());
+        }
+        config.device_name = Some(name);
+    }
+    config.enabled = true;
+    config.save()?;
+
+    start_service(app_handle).await?;
+    get_lan_sync_status().await
+}
+
+/// LAN同期を無効化（ペアリング情報は保持）
+#[tauri::command]
+pub async fn disable_lan_sync() -> Result<(), String> {
+    stop_service();
+    let mut config = LanSyncConfig::load()?;
+    config.enabled = false;
+    config.save()
+}
+
+/// LAN同期の状態を取得
+#[tauri::command]
+pub async fn get_lan_sync_status() -> Result<serde_json::Value, String> {
+    let config = LanSyncConfig::load()?;
+    let (port, peers, fingerprint) = match SERVICE.lock().ok().as_ref().and_then(|service| service.as_ref()) {
+        Some(service) => (
+            Some(service.port),
+            service.node.peers(),
+            Some(service.node.identity.fingerprint()),
+        ),
+        None => (None, config.peers.clone(), None),
+    };
+    let discovered = discovered_peers();
+    let paired_ids: Vec<&str> = peers.iter().map(|peer| peer.device_id.as_str()).collect();
+
+    Ok(serde_json::json!({
+        "enabled": config.enabled,
+        "running": port.is_some(),
+        "port": port,
+        "device_id": config.device_id,
+        "device_name": config.device_name.unwrap_or_else(default_device_name),
+        "fingerprint": fingerprint,
+        "peers": peers.iter().map(|peer| serde_json::json!({
+            "device_id": peer.device_id,
+            "name": peer.name,
+            "fingerprint": peer.fingerprint,
+            "last_synced_at": peer.last_synced_at.map(|at| at.to_rfc3339()),
+            "online": discovered.iter().any(|found| found.device_id == peer.device_id),
+        })).collect::<Vec<_>>(),
+        "discovered": discovered
+            .iter()
+            .filter(|found| !paired_ids.contains(&found.device_id.as_str()))
+            .collect::<Vec<_>>(),
+    }))
+}
+
+/// ペアリングコードを発行（相手の端末で入力する）
+#[tauri::command]
+pub async fn start_lan_pairing() -> Result<serde_json::Value, String> {
+    let node = running_node()?;
+    let (code, expires_at) = node.start_pairing();
+    Ok(serde_json::json!({
+        "code": code,
+        "expires_at": expires_at.to_rfc3339(),
+        "fingerprint": node.identity.fingerprint(),
+    }))
+}
+
+/// 相手の端末に表示されたコードでペアリング
+///
+/// address: 相手のアドレス（`get_lan_sync_status`の`discovered`に含まれる`ip:port`）
+#[tauri::command]
+pub async fn pair_lan_peer(address: String, code: String) -> Result<TrustedPeer, String> {
+    running_node()?.pair_with(&address, &code).await
+}
+
+/// ペアリング済みの端末と今すぐ同期
+#[tauri::command]
+pub async fn sync_lan_now() -> Result<Vec<LanSyncReport>, String> {
+    let node = running_node()?;
+    Ok(sync_all(&node, &discovered_peers()).await)
+}
+
+/// ペアリングを解除
+#[tauri::command]
+pub async fn forget_lan_peer(device_id: String) -> Result<bool, String> {
+    match running_node() {
+        Ok(node) => Ok(node.forget_peer(&device_id)),
+        Err(_) => {
+            let mut config = LanSyncConfig::load()?;
+            let before = config.peers.len();
+            config.peers.retain(|peer| peer.device_id != device_id);
+            let removed = config.peers.len() != before;
+            config.save()?;
+            Ok(removed)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn create_node(name: &str) -> (Arc<LanSyncNode>, String) {
+        // インメモリDBは接続ごとに別物になるため、接続数を1に制限
+        let pool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        crate::database::init_database_schema(&pool).await.unwrap();
+
+        let node = Arc::new(LanSyncNode::new(DeviceIdentity::generate(name), pool, Vec::new(), None, None));
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        tokio::spawn(Arc::clone(&node).serve(listener));
+        (node, address)
+    }
+
+    async fn insert_prompt(node: &LanSyncNode, id: &str, content: &str) {
+        sqlx::query("INSERT INTO prompts (id, content, tags, created_at, updated_at) VALUES ($1, $2, '[]', $3, $3)")
+            .bind(id)
+            .bind(content)
+            .bind(chrono::Utc::now())
+            .execute(&node.pool)
+            .await
+            .unwrap();
+    }
+
+    async fn content_of(node: &LanSyncNode, id: &str) -> Option<String> {
+        sqlx::query_scalar("SELECT content FROM prompts WHERE id = $1")
+            .bind(id)
+            .fetch_optional(&node.pool)
+            .await
+            .unwrap()
+    }
+
+    async fn paired_nodes() -> (Arc<LanSyncNode>, String, Arc<LanSyncNode>, String) {
+        let (desktop, desktop_address) = create_node("desktop").await;
+        let (laptop, laptop_address) = create_node("laptop").await;
+        let (code, _) = desktop.start_pairing();
+        laptop.pair_with(&desktop_address, &code).await.unwrap();
+        (desktop, desktop_address, laptop, laptop_address)
+    }
+
+    #[tokio::test]
+    async fn test_pairing_on_loopback_stores_keys_on_both_sides() {
+        let (desktop, _, laptop, _) = paired_nodes().await;
+
+        let desktop_peers = desktop.peers();
+        let laptop_peers = laptop.peers();
+        assert_eq!(desktop_peers.len(), 1);
+        assert_eq!(laptop_peers.len(), 1);
+        assert_eq!(desktop_peers[0].device_id, laptop.identity.device_id);
+        assert_eq!(laptop_peers[0].fingerprint, desktop.identity.fingerprint());
+    }
+
+    #[tokio::test]
+    async fn test_pairing_code_is_single_use() {
+        let (desktop, desktop_address) = create_node("desktop").await;
+        let (laptop, _) = create_node("laptop").await;
+
+        let (code, _) = desktop.start_pairing();
+        let wrong = if code == "000000" { "111111" } else { "000000" };
+        assert!(laptop.pair_with(&desktop_address, wrong).await.is_err());
+        // 失敗した時点でコードは無効
+        assert!(laptop.pair_with(&desktop_address, &code).await.is_err());
+        assert!(desktop.peers().is_empty());
+        assert!(laptop.peers().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_abandoned_pairing_request_does_not_use_up_code() {
+        use base64::{engine::general_purpose, Engine as _};
+
+        let (desktop, desktop_address) = create_node("desktop").await;
+        let (laptop, _) = create_node("laptop").await;
+        let (code, _) = desktop.start_pairing();
+
+        // コードを知らない端末がペアリング要求を送り、MACの開示前に切断する
+        let stranger = DeviceIdentity::generate("stranger");
+        let mut stream = TcpStream::connect(&desktop_address).await.unwrap();
+        protocol::send_plain(
+            &mut stream,
+            &Hello::Pair {
+                version: protocol::PROTOCOL_VERSION,
+                device_id: stranger.device_id.clone(),
+                name: stranger.name.clone(),
+                public_key: stranger.public_key_base64(),
+                nonce: general_purpose::STANDARD.encode([0u8; 32]),
+            },
+        )
+        .await
+        .unwrap();
+        let _challenge: serde_json::Value = protocol::recv_plain(&mut stream).await.unwrap();
+        drop(stream);
+
+        // 直後の要求は間隔の制限で拒否される
+        assert!(laptop.pair_with(&desktop_address, &code).await.is_err());
+        tokio::time::sleep(PAIRING_ATTEMPT_INTERVAL).await;
+        assert!(laptop.pair_with(&desktop_address, &code).await.is_ok());
+        assert_eq!(desktop.peers().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_sync_exchanges_changes_in_both_directions() {
+        let (desktop, desktop_address, laptop, _) = paired_nodes().await;
+        insert_prompt(&desktop, "from-desktop", "desktop prompt").await;
+        insert_prompt(&laptop, "from-laptop", "laptop prompt").await;
+
+        let report = laptop.sync_with(&desktop_address).await.unwrap();
+        assert_eq!(report.received, 1);
+        assert_eq!(content_of(&laptop, "from-desktop").await.as_deref(), Some("desktop prompt"));
+        assert_eq!(content_of(&desktop, "from-laptop").await.as_deref(), Some("laptop prompt"));
+
+        // 削除と更新も伝播する
+        delete_prompt_in(&desktop.pool, "from-desktop").await.unwrap();
+        sqlx::query("UPDATE prompts SET content = 'edited', updated_at = $1 WHERE id = 'from-laptop'")
+            .bind(chrono::Utc::now())
+            .execute(&laptop.pool)
+            .await
+            .unwrap();
+
+        let report = laptop.sync_with(&desktop_address).await.unwrap();
+        assert_eq!(report.deleted, 1);
+        assert_eq!(content_of(&laptop, "from-desktop").await, None);
+        assert_eq!(content_of(&desktop, "from-laptop").await.as_deref(), Some("edited"));
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_after_compaction_propagates_deletions() {
+        let (desktop, desktop_address, laptop, _) = paired_nodes().await;
+        for id in ["deleted", "secret", "kept"] {
+            insert_prompt(&desktop, id, "desktop prompt").await;
+        }
+        laptop.sync_with(&desktop_address).await.unwrap();
+
+        // 削除とシークレット化の後、ジャーナルを破棄して差分を送れなくする
+        delete_prompt_in(&desktop.pool, "deleted").await.unwrap();
+        sqlx::query("UPDATE prompts SET is_secret = 1 WHERE id = 'secret'")
+            .execute(&desktop.pool)
+            .await
+            .unwrap();
+        crate::database::compact_changes_if_needed(&desktop.pool, 1).await.unwrap();
+        // 相手にまだ送っていないプロンプトはスナップショットになくても残る
+        insert_prompt(&laptop, "unsent", "laptop prompt").await;
+
+        let report = laptop.sync_with(&desktop_address).await.unwrap();
+        assert_eq!(report.deleted, 2);
+        assert_eq!(content_of(&laptop, "deleted").await, None);
+        assert_eq!(content_of(&laptop, "secret").await, None);
+        assert_eq!(content_of(&laptop, "kept").await.as_deref(), Some("desktop prompt"));
+        assert_eq!(content_of(&laptop, "unsent").await.as_deref(), Some("laptop prompt"));
+        assert_eq!(content_of(&desktop, "unsent").await.as_deref(), Some("laptop prompt"));
+
+        // 差分の同期でもシークレットにしたプロンプトは相手から削除される
+        sqlx::query("UPDATE prompts SET is_secret = 1, content = 'sealed', updated_at = $1 WHERE id = 'kept'")
+            .bind(chrono::Utc::now())
+            .execute(&desktop.pool)
+            .await
+            .unwrap();
+        let report = laptop.sync_with(&desktop_address).await.unwrap();
+        assert_eq!(report.deleted, 1);
+        assert_eq!(content_of(&laptop, "kept").await, None);
+    }
+
+    #[tokio::test]
+    async fn test_newer_local_edit_wins_over_older_remote_edit() {
+        let (desktop, desktop_address, laptop, _) = paired_nodes().await;
+        insert_prompt(&desktop, "shared", "original").await;
+        laptop.sync_with(&desktop_address).await.unwrap();
+
+        let earlier = chrono::Utc::now();
+        sqlx::query("UPDATE prompts SET content = 'desktop edit', updated_at = $1 WHERE id = 'shared'")
+            .bind(earlier)
+            .execute(&desktop.pool)
+            .await
+            .unwrap();
+        sqlx::query("UPDATE prompts SET content = 'laptop edit', updated_at = $1 WHERE id = 'shared'")
+            .bind(earlier + chrono::Duration::seconds(10))
+            .execute(&laptop.pool)
+            .await
+            .unwrap();
+
+        laptop.sync_with(&desktop_address).await.unwrap();
+        assert_eq!(content_of(&desktop, "shared").await.as_deref(), Some("laptop edit"));
+        assert_eq!(content_of(&laptop, "shared").await.as_deref(), Some("laptop edit"));
+    }
+
+    #[tokio::test]
+    async fn test_unpaired_device_cannot_sync() {
+        let (_desktop, desktop_address) = create_node("desktop").await;
+        let (stranger, _) = create_node("stranger").await;
+
+        assert!(stranger.sync_with(&desktop_address).await.is_err());
+    }
+}
@@ -0,0 +1,866 @@
+/*!
+ * LAN同期の通信プロトコル
+ *
+ * フレーム: 4バイト（ビッグエンディアン）の長さ + JSON本文
+ *
+ * ペアリング（短いコードでed25519公開鍵を交換）:
+ * 1. 開始側 → 待受側: `Hello::Pair`（公開鍵・ノンス）
+ * 2. 待受側 → 開始側: `PairChallenge`（公開鍵・ノンス・自分のMACへのコミットメント）
+ * 3. 開始側 → 待受側: `PairCommit`（自分のMACへのコミットメント）
+ * 4. 待受側 → 開始側: `PairReveal`（待受側のMACの開示）
+ * 5. 開始側 → 待受側: `PairProof`（待受側のMACを検証できた場合のみ、開始側のMACを開示）
+ * 6. 待受側 → 開始側: `PairComplete`
+ *
+ * MACはコードをキーにしたHMACのため、開示されたMACからはコードを総当たりできる。
+ * 双方がコミットメントを送ってから開示するため、総当たりで得たコードは
+ * 確定済みのコミットメントに合わせられず、そのハンドシェイクでは使えない
+ * - 開始側は相手がコードを知っていると確認できるまで自分のMACを開示しない
+ *   （mDNSで成りすました相手に入力したコードを渡さない）
+ * - 待受側は開示した時点でコードを無効にする（1つのコードは1回のハンドシェイクでしか使えない）
+ *
+ * 同期セッション（ペアリング済みの端末同士）:
+ * - x25519の一時鍵で鍵共有し、ハンドシェイクの内容を双方がed25519で署名
+ * - 以降のフレームはChaCha20-Poly1305で暗号化（方向ごとに別の鍵とカウンタ）
+ */
+use base64::{engine::general_purpose, Engine as _};
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use hkdf::Hkdf;
+use hmac::{Hmac, Mac};
+use rand::RngCore;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+/// プロトコルバージョン
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// 1フレームの最大サイズ
+const MAX_FRAME_LEN: usize = 16 * 1024 * 1024;
+
+/// ノンス長
+const NONCE_LEN: usize = 32;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// LAN同期プロトコルのエラー
+#[derive(Debug)]
+pub enum ProtocolError {
+    /// 通信エラー
+    Io(std::io::Error),
+    /// 不正なメッセージ
+    InvalidMessage(String),
+    /// 相手の認証に失敗（ペアリングコード・署名の不一致）
+    AuthenticationFailed(String),
+    /// 相手が接続を拒否した
+    Rejected(String),
+}
+
+impl std::fmt::Display for ProtocolError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProtocolError::Io(e) => write!(f, "Connection error: {}", e),
+            ProtocolError::InvalidMessage(msg) => write!(f, "Invalid message: {}", msg),
+            ProtocolError::AuthenticationFailed(msg) => write!(f, "Authentication failed: {}", msg),
+            ProtocolError::Rejected(reason) => write!(f, "Rejected by peer: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for ProtocolError {}
+
+impl From<std::io::Error> for ProtocolError {
+    fn from(err: std::io::Error) -> Self {
+        ProtocolError::Io(err)
+    }
+}
+
+/// この端末の識別情報
+#[derive(Clone)]
+pub struct DeviceIdentity {
+    pub device_id: String,
+    pub name: String,
+    pub signing_key: SigningKey,
+}
+
+impl DeviceIdentity {
+    /// 新しい鍵ペアを生成
+    pub fn generate(name: &str) -> Self {
+        let mut secret = [0u8; 32];
+        rand::rngs::OsRng.fill_bytes(&mut secret);
+        Self {
+            device_id: uuid::Uuid::new_v4().to_string(),
+            name: name.to_string(),
+            signing_key: SigningKey::from_bytes(&secret),
+        }
+    }
+
+    pub fn public_key_base64(&self) -> String {
+        general_purpose::STANDARD.encode(self.signing_key.verifying_key().as_bytes())
+    }
+
+    pub fn fingerprint(&self) -> String {
+        fingerprint(&self.signing_key.verifying_key())
+    }
+}
+
+/// 公開鍵のフィンガープリント（画面で照合しやすい短い形式）
+pub fn fingerprint(key: &VerifyingKey) -> String {
+    let digest = Sha256::digest(key.as_bytes());
+    digest[..8]
+        .chunks(2)
+        .map(|chunk| format!("{:02X}{:02X}", chunk[0], chunk[1]))
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// Base64の公開鍵をデコード
+pub fn decode_public_key(encoded: &str) -> Result<VerifyingKey, ProtocolError> {
+    let bytes = general_purpose::STANDARD
+        .decode(encoded)
+        .map_err(|_| ProtocolError::InvalidMessage("Invalid public key encoding".to_string()))?;
+    let bytes: [u8; 32] = bytes
+        .try_into()
+        .map_err(|_| ProtocolError::InvalidMessage("Invalid public key length".to_string()))?;
+    VerifyingKey::from_bytes(&bytes).map_err(|_| ProtocolError::InvalidMessage("Invalid public key".to_string()))
+}
+
+fn random_bytes<const N: usize>() -> [u8; N] {
+    let mut bytes = [0u8; N];
+    rand::rngs::OsRng.fill_bytes(&mut bytes);
+    bytes
+}
+
+fn encode(bytes: &[u8]) -> String {
+    general_purpose::STANDARD.encode(bytes)
+}
+
+fn decode_fixed<const N: usize>(encoded: &str, what: &str) -> Result<[u8; N], ProtocolError> {
+    general_purpose::STANDARD
+        .decode(encoded)
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| ProtocolError::InvalidMessage(format!("Invalid {what}")))
+}
+
+/// ラベル付きで各フィールドを連結したハッシュ（署名・MACの対象）
+fn transcript(label: &str, fields: &[&[u8]]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(label.as_bytes());
+    for field in fields {
+        hasher.update((field.len() as u32).to_be_bytes());
+        hasher.update(field);
+    }
+    hasher.finalize().into()
+}
+
+/// フレームを送信
+pub async fn write_frame<S: AsyncWrite + Unpin>(stream: &mut S, payload: &[u8]) -> Result<(), ProtocolError> {
+    if payload.len() > MAX_FRAME_LEN {
+        return Err(ProtocolError::InvalidMessage("Frame too large".to_string()));
+    }
+    stream.write_all(&(payload.len() as u32).to_be_bytes()).await?;
+    stream.write_all(payload).await?;
+    stream.flush().await?;
+    Ok(())
+}
+
+/// フレームを受信
+pub async fn read_frame<S: AsyncRead + Unpin>(stream: &mut S) -> Result<Vec<u8>, ProtocolError> {
+    let mut len = [0u8; 4];
+    stream.read_exact(&mut len).await?;
+    let len = u32::from_be_bytes(len) as usize;
+    if len > MAX_FRAME_LEN {
+        return Err(ProtocolError::InvalidMessage("Frame too large".to_string()));
+    }
+    let mut payload = vec![0u8; len];
+    stream.read_exact(&mut payload).await?;
+    Ok(payload)
+}
+
+/// JSONメッセージを平文で送信（ハンドシェイク用）
+pub async fn send_plain<S, T>(stream: &mut S, message: &T) -> Result<(), ProtocolError>
+where
+    S: AsyncWrite + Unpin,
+    T: Serialize,
+{
+    let payload = serde_json::to_vec(message).map_err(|e| ProtocolError::InvalidMessage(e.to_string()))?;
+    write_frame(stream, &payload).await
+}
+
+/// JSONメッセージを平文で受信（ハンドシェイク用）
+pub async fn recv_plain<S, T>(stream: &mut S) -> Result<T, ProtocolError>
+where
+    S: AsyncRead + Unpin,
+    T: DeserializeOwned,
+{
+    let payload = read_frame(stream).await?;
+    serde_json::from_slice(&payload).map_err(|e| ProtocolError::InvalidMessage(e.to_string()))
+}
+
+/// 接続開始時のメッセージ
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Hello {
+    Pair {
+        version: u32,
+        device_id: String,
+        name: String,
+        public_key: String,
+        nonce: String,
+    },
+    Sync {
+        version: u32,
+        device_id: String,
+        nonce: String,
+        ephemeral_key: String,
+    },
+}
+
+/// ペアリング・ハンドシェイク中の応答
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Reply {
+    PairChallenge {
+        version: u32,
+        device_id: String,
+        name: String,
+        public_key: String,
+        nonce: String,
+        commitment: String,
+    },
+    PairCommit {
+        commitment: String,
+    },
+    PairReveal {
+        salt: String,
+        mac: String,
+    },
+    PairProof {
+        salt: String,
+        mac: String,
+    },
+    PairComplete,
+    SyncAccept {
+        device_id: String,
+        nonce: String,
+        ephemeral_key: String,
+        signature: String,
+    },
+    SyncConfirm {
+        signature: String,
+    },
+    Rejected {
+        reason: String,
+    },
+}
+
+/// ペアリングで得た相手の情報
+#[derive(Debug, Clone)]
+pub struct PairedPeer {
+    pub device_id: String,
+    pub name: String,
+    pub public_key: VerifyingKey,
+}
+
+/// 拒否メッセージを送信
+pub async fn send_rejection<S: AsyncWrite + Unpin>(stream: &mut S, reason: &str) -> Result<(), ProtocolError> {
+    send_plain(stream, &Reply::Rejected { reason: reason.to_string() }).await
+}
+
+async fn recv_reply<S: AsyncRead + Unpin>(stream: &mut S) -> Result<Reply, ProtocolError> {
+    match recv_plain(stream).await? {
+        Reply::Rejected { reason } => Err(ProtocolError::Rejected(reason)),
+        reply => Ok(reply),
+    }
+}
+
+fn check_version(version: u32) -> Result<(), ProtocolError> {
+    if version != PROTOCOL_VERSION {
+        return Err(ProtocolError::InvalidMessage(format!("Unsupported protocol version {version}")));
+    }
+    Ok(())
+}
+
+/// ペアリングのMAC
+fn pairing_mac(code: &str, role: &str, transcript: &[u8; 32]) -> [u8; 32] {
+    let mut mac = <HmacSha256 as Mac>::new_from_slice(code.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(role.as_bytes());
+    mac.update(transcript);
+    mac.finalize().into_bytes().into()
+}
+
+fn verify_pairing_mac(code: &str, role: &str, transcript: &[u8; 32], received: &[u8]) -> bool {
+    let mut mac = <HmacSha256 as Mac>::new_from_slice(code.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(role.as_bytes());
+    mac.update(transcript);
+    mac.verify_slice(received).is_ok()
+}
+
+fn commitment(salt: &[u8], mac: &[u8]) -> [u8; 32] {
+    Sha256::new().chain_update(salt).chain_update(mac).finalize().into()
+}
+
+struct PairingTranscript {
+    initiator_id: String,
+    responder_id: String,
+    initiator_key: VerifyingKey,
+    responder_key: VerifyingKey,
+    initiator_nonce: [u8; NONCE_LEN],
+    responder_nonce: [u8; NONCE_LEN],
+}
+
+impl PairingTranscript {
+    fn digest(&self) -> [u8; 32] {
+        transcript(
+            "prompalette-lan-pair-v1",
+            &[
+                self.initiator_id.as_bytes(),
+                self.responder_id.as_bytes(),
+                self.initiator_key.as_bytes(),
+                self.responder_key.as_bytes(),
+                &self.initiator_nonce,
+                &self.responder_nonce,
+            ],
+        )
+    }
+}
+
+/// ペアリングを開始する側（コードを入力した端末）
+pub async fn pair_initiator<S>(stream: &mut S, identity: &DeviceIdentity, code: &str) -> Result<PairedPeer, ProtocolError>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let nonce: [u8; NONCE_LEN] = random_bytes();
+    send_plain(
+        stream,
+        &Hello::Pair {
+            version: PROTOCOL_VERSION,
+            device_id: identity.device_id.clone(),
+            name: identity.name.clone(),
+            public_key: identity.public_key_base64(),
+            nonce: encode(&nonce),
+        },
+    )
+    .await?;
+
+    let (version, device_id, name, public_key, responder_nonce, commitment_value) = match recv_reply(stream).await? {
+        Reply::PairChallenge { version, device_id, name, public_key, nonce, commitment } => (
+            version,
+            device_id,
+            name,
+            decode_public_key(&public_key)?,
+            decode_fixed::<NONCE_LEN>(&nonce, "nonce")?,
+            decode_fixed::<32>(&commitment, "commitment")?,
+        ),
+        _ => return Err(ProtocolError::InvalidMessage("Expected pairing challenge".to_string())),
+    };
+    if let Err(e) = check_version(version) {
+        send_rejection(stream, "Unsupported protocol version").await.ok();
+        return Err(e);
+    }
+
+    let transcript = PairingTranscript {
+        initiator_id: identity.device_id.clone(),
+        responder_id: device_id.clone(),
+        initiator_key: identity.signing_key.verifying_key(),
+        responder_key: public_key,
+        initiator_nonce: nonce,
+        responder_nonce,
+    }
+    .digest();
+
+    // 自分のMACは相手の開示を検証するまで送らない
+    let own_mac = pairing_mac(code, "initiator", &transcript);
+    let own_salt: [u8; 32] = random_bytes();
+    send_plain(stream, &Reply::PairCommit { commitment: encode(&commitment(&own_salt, &own_mac)) }).await?;
+
+    let (salt, mac) = match recv_reply(stream).await? {
+        Reply::PairReveal { salt, mac } => (
+            decode_fixed::<32>(&salt, "salt")?,
+            decode_fixed::<32>(&mac, "mac")?,
+        ),
+        _ => return Err(ProtocolError::InvalidMessage("Expected pairing reveal".to_string())),
+    };
+
+    if commitment(&salt, &mac) != commitment_value || !verify_pairing_mac(code, "responder", &transcript, &mac) {
+        send_rejection(stream, "Pairing code mismatch").await.ok();
+        return Err(ProtocolError::AuthenticationFailed("Pairing code mismatch".to_string()));
+    }
+
+    send_plain(stream, &Reply::PairProof { salt: encode(&own_salt), mac: encode(&own_mac) }).await?;
+
+    match recv_reply(stream).await? {
+        Reply::PairComplete => Ok(PairedPeer { device_id, name, public_key }),
+        _ => Err(ProtocolError::InvalidMessage("Expected pairing completion".to_string())),
+    }
+}
+
+/// ペアリングを待ち受ける側（コードを表示した端末）
+///
+/// `hello`は受信済みの`Hello::Pair`、`code`は表示中のペアリングコード
+/// `on_reveal`はコードをキーにしたMACを開示する直前に呼ばれる（ここでコードを無効にする）
+pub async fn pair_responder<S>(
+    stream: &mut S,
+    identity: &DeviceIdentity,
+    hello: Hello,
+    code: &str,
+    on_reveal: impl FnOnce(),
+) -> Result<PairedPeer, ProtocolError>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let (version, device_id, name, public_key, initiator_nonce) = match hello {
+        Hello::Pair { version, device_id, name, public_key, nonce } => (version, device_id, name, public_key, nonce),
+        Hello::Sync { .. } => return Err(ProtocolError::InvalidMessage("Expected pairing request".to_string())),
+    };
+    check_version(version)?;
+    let public_key = decode_public_key(&public_key)?;
+    let initiator_nonce = decode_fixed::<NONCE_LEN>(&initiator_nonce, "nonce")?;
+
+    let nonce: [u8; NONCE_LEN] = random_bytes();
+    let transcript = PairingTranscript {
+        initiator_id: device_id.clone(),
+        responder_id: identity.device_id.clone(),
+        initiator_key: public_key,
+        responder_key: identity.signing_key.verifying_key(),
+        initiator_nonce,
+        responder_nonce: nonce,
+    }
+    .digest();
+
+    let mac = pairing_mac(code, "responder", &transcript);
+    let salt: [u8; 32] = random_bytes();
+    send_plain(
+        stream,
+        &Reply::PairChallenge {
+            version: PROTOCOL_VERSION,
+            device_id: identity.device_id.clone(),
+            name: identity.name.clone(),
+            public_key: identity.public_key_base64(),
+            nonce: encode(&nonce),
+            commitment: encode(&commitment(&salt, &mac)),
+        },
+    )
+    .await?;
+
+    let initiator_commitment = match recv_reply(stream).await? {
+        Reply::PairCommit { commitment } => decode_fixed::<32>(&commitment, "commitment")?,
+        _ => return Err(ProtocolError::InvalidMessage("Expected pairing commitment".to_string())),
+    };
+
+    on_reveal();
+    send_plain(stream, &Reply::PairReveal { salt: encode(&salt), mac: encode(&mac) }).await?;
+
+    let (proof_salt, proof) = match recv_reply(stream).await {
+        Ok(Reply::PairProof { salt, mac }) => (
+            decode_fixed::<32>(&salt, "salt")?,
+            decode_fixed::<32>(&mac, "mac")?,
+        ),
+        Ok(_) => return Err(ProtocolError::InvalidMessage("Expected pairing proof".to_string())),
+        // 開始側が待受側のMACを検証できなかった（コードの不一致）
+        Err(ProtocolError::Rejected(reason)) => return Err(ProtocolError::AuthenticationFailed(reason)),
+        Err(e) => return Err(e),
+    };
+
+    if commitment(&proof_salt, &proof) != initiator_commitment
+        || !verify_pairing_mac(code, "initiator", &transcript, &proof)
+    {
+        send_rejection(stream, "Pairing code mismatch").await.ok();
+        return Err(ProtocolError::AuthenticationFailed("Pairing code mismatch".to_string()));
+    }
+
+    send_plain(stream, &Reply::PairComplete).await?;
+
+    Ok(PairedPeer { device_id, name, public_key })
+}
+
+/// 暗号化された同期チャネル
+pub struct SecureChannel<S> {
+    stream: S,
+    peer_device_id: String,
+    send_cipher: ChaCha20Poly1305,
+    recv_cipher: ChaCha20Poly1305,
+    send_counter: u64,
+    recv_counter: u64,
+}
+
+struct SessionTranscript<'a> {
+    client_id: &'a str,
+    server_id: &'a str,
+    client_nonce: &'a [u8],
+    server_nonce: &'a [u8],
+    client_ephemeral: &'a [u8],
+    server_ephemeral: &'a [u8],
+}
+
+impl SessionTranscript<'_> {
+    fn digest(&self, role: &str) -> [u8; 32] {
+        transcript(
+            &format!("prompalette-lan-session-v1-{role}"),
+            &[
+                self.client_id.as_bytes(),
+                self.server_id.as_bytes(),
+                self.client_nonce,
+                self.server_nonce,
+                self.client_ephemeral,
+                self.server_ephemeral,
+            ],
+        )
+    }
+
+    /// 共有秘密から方向ごとの鍵を導出（client→server, server→client）
+    fn derive_keys(&self, shared: &[u8]) -> (ChaCha20Poly1305, ChaCha20Poly1305) {
+        let salt = [self.client_nonce, self.server_nonce].concat();
+        let hkdf = Hkdf::<Sha256>::new(Some(&salt), shared);
+        let mut client_to_server = [0u8; 32];
+        let mut server_to_client = [0u8; 32];
+        hkdf.expand(b"prompalette-lan c2s", &mut client_to_server)
+            .expect("32 bytes is a valid HKDF output length");
+        hkdf.expand(b"prompalette-lan s2c", &mut server_to_client)
+            .expect("32 bytes is a valid HKDF output length");
+        (
+            ChaCha20Poly1305::new(Key::from_slice(&client_to_server)),
+            ChaCha20Poly1305::new(Key::from_slice(&server_to_client)),
+        )
+    }
+}
+
+fn verify_signature(key: &VerifyingKey, message: &[u8; 32], signature: &str) -> Result<(), ProtocolError> {
+    let bytes = decode_fixed::<64>(signature, "signature")?;
+    key.verify(message, &Signature::from_bytes(&bytes))
+        .map_err(|_| ProtocolError::AuthenticationFailed("Invalid signature".to_string()))
+}
+
+impl<S: AsyncRead + AsyncWrite + Unpin> SecureChannel<S> {
+    /// 同期セッションを開始（接続する側）
+    ///
+    /// `trusted_key`: 相手の端末IDからペアリング済みの公開鍵を引く
+    pub async fn connect(
+        mut stream: S,
+        identity: &DeviceIdentity,
+        trusted_key: impl Fn(&str) -> Option<VerifyingKey>,
+    ) -> Result<Self, ProtocolError> {
+        let nonce: [u8; NONCE_LEN] = random_bytes();
+        let ephemeral = x25519_dalek::EphemeralSecret::random_from_rng(rand::rngs::OsRng);
+        let ephemeral_public = x25519_dalek::PublicKey::from(&ephemeral);
+
+        send_plain(
+            &mut stream,
+            &Hello::Sync {
+                version: PROTOCOL_VERSION,
+                device_id: identity.device_id.clone(),
+                nonce: encode(&nonce),
+                ephemeral_key: encode(ephemeral_public.as_bytes()),
+            },
+        )
+        .await?;
+
+        let (server_id, server_nonce, server_ephemeral, signature) = match recv_reply(&mut stream).await? {
+            Reply::SyncAccept { device_id, nonce, ephemeral_key, signature } => (
+                device_id,
+                decode_fixed::<NONCE_LEN>(&nonce, "nonce")?,
+                decode_fixed::<32>(&ephemeral_key, "ephemeral key")?,
+                signature,
+            ),
+            _ => return Err(ProtocolError::InvalidMessage("Expected sync accept".to_string())),
+        };
+
+        let server_key = trusted_key(&server_id)
+            .ok_or_else(|| ProtocolError::AuthenticationFailed("Peer is not paired".to_string()))?;
+
+        let session = SessionTranscript {
+            client_id: &identity.device_id,
+            server_id: &server_id,
+            client_nonce: &nonce,
+            server_nonce: &server_nonce,
+            client_ephemeral: ephemeral_public.as_bytes(),
+            server_ephemeral: &server_ephemeral,
+        };
+        verify_signature(&server_key, &session.digest("server"), &signature)?;
+
+        let client_signature = identity.signing_key.sign(&session.digest("client"));
+        send_plain(&mut stream, &Reply::SyncConfirm { signature: encode(&client_signature.to_bytes()) }).await?;
+
+        let shared = ephemeral.diffie_hellman(&x25519_dalek::PublicKey::from(server_ephemeral));
+        let (client_to_server, server_to_client) = session.derive_keys(shared.as_bytes());
+
+        Ok(Self {
+            stream,
+            peer_device_id: server_id,
+            send_cipher: client_to_server,
+            recv_cipher: server_to_client,
+            send_counter: 0,
+            recv_counter: 0,
+        })
+    }
+
+    /// 同期セッションを受け入れる（待ち受ける側）
+    ///
+    /// `hello`は受信済みの`Hello::Sync`
+    pub async fn accept(
+        mut stream: S,
+        identity: &DeviceIdentity,
+        hello: Hello,
+        trusted_key: impl Fn(&str) -> Option<VerifyingKey>,
+    ) -> Result<Self, ProtocolError> {
+        let (version, client_id, client_nonce, client_ephemeral) = match hello {
+            Hello::Sync { version, device_id, nonce, ephemeral_key } => (version, device_id, nonce, ephemeral_key),
+            Hello::Pair { .. } => return Err(ProtocolError::InvalidMessage("Expected sync request".to_string())),
+        };
+        check_version(version)?;
+        let client_nonce = decode_fixed::<NONCE_LEN>(&client_nonce, "nonce")?;
+        let client_ephemeral = decode_fixed::<32>(&client_ephemeral, "ephemeral key")?;
+
+        let Some(client_key) = trusted_key(&client_id) else {
+            send_rejection(&mut stream, "Device is not paired").await.ok();
+            return Err(ProtocolError::AuthenticationFailed("Peer is not paired".to_string()));
+        };
+
+        let nonce: [u8; NONCE_LEN] = random_bytes();
+        let ephemeral = x25519_dalek::EphemeralSecret::random_from_rng(rand::rngs::OsRng);
+        let ephemeral_public = x25519_dalek::PublicKey::from(&ephemeral);
+
+        let session = SessionTranscript {
+            client_id: &client_id,
+            server_id: &identity.device_id,
+            client_nonce: &client_nonce,
+            server_nonce: &nonce,
+            client_ephemeral: &client_ephemeral,
+            server_ephemeral: ephemeral_public.as_bytes(),
+        };
+        let signature = identity.signing_key.sign(&session.digest("server"));
+        send_plain(
+            &mut stream,
+            &Reply::SyncAccept {
+                device_id: identity.device_id.clone(),
+                nonce: encode(&nonce),
+                ephemeral_key: encode(ephemeral_public.as_bytes()),
+                signature: encode(&signature.to_bytes()),
+            },
+        )
+        .await?;
+
+        match recv_reply(&mut stream).await? {
+            Reply::SyncConfirm { signature } => verify_signature(&client_key, &session.digest("client"), &signature)?,
+            _ => return Err(ProtocolError::InvalidMessage("Expected sync confirm".to_string())),
+        }
+
+        let shared = ephemeral.diffie_hellman(&x25519_dalek::PublicKey::from(client_ephemeral));
+        let (client_to_server, server_to_client) = session.derive_keys(shared.as_bytes());
+
+        Ok(Self {
+            stream,
+            peer_device_id: client_id,
+            send_cipher: server_to_client,
+            recv_cipher: client_to_server,
+            send_counter: 0,
+            recv_counter: 0,
+        })
+    }
+
+    /// 認証済みの相手の端末ID
+    pub fn peer_device_id(&self) -> &str {
+        &self.peer_device_id
+    }
+
+    fn nonce(counter: u64) -> Nonce {
+        let mut nonce = [0u8; 12];
+        nonce[4..].copy_from_slice(&counter.to_be_bytes());
+        *Nonce::from_slice(&nonce)
+    }
+
+    /// 暗号化してメッセージを送信
+    pub async fn send<T: Serialize>(&mut self, message: &T) -> Result<(), ProtocolError> {
+        let plaintext = serde_json::to_vec(message).map_err(|e| ProtocolError::InvalidMessage(e.to_string()))?;
+        let ciphertext = self
+            .send_cipher
+            .encrypt(&Self::nonce(self.send_counter), plaintext.as_slice())
+            .map_err(|_| ProtocolError::InvalidMessage("Encryption failed".to_string()))?;
+        self.send_counter += 1;
+        write_frame(&mut self.stream, &ciphertext).await
+    }
+
+    /// メッセージを受信して復号（改ざん・順序入れ替えは認証エラー）
+    pub async fn recv<T: DeserializeOwned>(&mut self) -> Result<T, ProtocolError> {
+        let ciphertext = read_frame(&mut self.stream).await?;
+        let plaintext = self
+            .recv_cipher
+            .decrypt(&Self::nonce(self.recv_counter), ciphertext.as_slice())
+            .map_err(|_| ProtocolError::AuthenticationFailed("Message authentication failed".to_string()))?;
+        self.recv_counter += 1;
+        serde_json::from_slice(&plaintext).map_err(|e| ProtocolError::InvalidMessage(e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_pairing_exchanges_keys() {
+        let (mut a, mut b) = tokio::io::duplex(64 * 1024);
+        let alice = DeviceIdentity::generate("alice");
+        let bob = DeviceIdentity::generate("bob");
+        let bob_for_task = bob.clone();
+
+        let responder = tokio::spawn(async move {
+            let hello: Hello = recv_plain(&mut b).await.unwrap();
+            pair_responder(&mut b, &bob_for_task, hello, "123456", || {}).await
+        });
+        let paired = pair_initiator(&mut a, &alice, "123456").await.unwrap();
+        let responder_paired = responder.await.unwrap().unwrap();
+
+        assert_eq!(paired.device_id, bob.device_id);
+        assert_eq!(paired.public_key, bob.signing_key.verifying_key());
+        assert_eq!(responder_paired.device_id, alice.device_id);
+        assert_eq!(responder_paired.public_key, alice.signing_key.verifying_key());
+    }
+
+    #[tokio::test]
+    async fn test_pairing_with_wrong_code_fails_on_both_sides() {
+        let (mut a, mut b) = tokio::io::duplex(64 * 1024);
+        let alice = DeviceIdentity::generate("alice");
+        let bob = DeviceIdentity::generate("bob");
+
+        let responder = tokio::spawn(async move {
+            let hello: Hello = recv_plain(&mut b).await.unwrap();
+            pair_responder(&mut b, &bob, hello, "123456", || {}).await
+        });
+        let result = pair_initiator(&mut a, &alice, "654321").await;
+
+        assert!(result.is_err());
+        assert!(matches!(responder.await.unwrap(), Err(ProtocolError::AuthenticationFailed(_))));
+    }
+
+    #[tokio::test]
+    async fn test_initiator_does_not_reveal_mac_to_spoofed_responder() {
+        let (mut a, mut b) = tokio::io::duplex(64 * 1024);
+        let alice = DeviceIdentity::generate("alice");
+        let mallory = DeviceIdentity::generate("mallory");
+
+        // コードを知らない成りすましの待受側: 適当なMACにコミットして開示する
+        let spoofed = tokio::spawn(async move {
+            let _hello: Hello = recv_plain(&mut b).await.unwrap();
+            let salt: [u8; 32] = random_bytes();
+            let mac: [u8; 32] = random_bytes();
+            send_plain(
+                &mut b,
+                &Reply::PairChallenge {
+                    version: PROTOCOL_VERSION,
+                    device_id: mallory.device_id.clone(),
+                    name: mallory.name.clone(),
+                    public_key: mallory.public_key_base64(),
+                    nonce: encode(&random_bytes::<NONCE_LEN>()),
+                    commitment: encode(&commitment(&salt, &mac)),
+                },
+            )
+            .await
+            .unwrap();
+            let committed: Reply = recv_plain(&mut b).await.unwrap();
+            send_plain(&mut b, &Reply::PairReveal { salt: encode(&salt), mac: encode(&mac) }).await.unwrap();
+            let after_reveal: Reply = recv_plain(&mut b).await.unwrap();
+            (committed, after_reveal)
+        });
+
+        let result = pair_initiator(&mut a, &alice, "123456").await;
+        let (committed, after_reveal) = spoofed.await.unwrap();
+
+        assert!(matches!(result, Err(ProtocolError::AuthenticationFailed(_))));
+        assert!(matches!(committed, Reply::PairCommit { .. }));
+        assert!(matches!(after_reveal, Reply::Rejected { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_initiator_rejects_unsupported_responder_version() {
+        let (mut a, mut b) = tokio::io::duplex(64 * 1024);
+        let alice = DeviceIdentity::generate("alice");
+        let bob = DeviceIdentity::generate("bob");
+
+        let responder = tokio::spawn(async move {
+            let _hello: Hello = recv_plain(&mut b).await.unwrap();
+            send_plain(
+                &mut b,
+                &Reply::PairChallenge {
+                    version: PROTOCOL_VERSION + 1,
+                    device_id: bob.device_id.clone(),
+                    name: bob.name.clone(),
+                    public_key: bob.public_key_base64(),
+                    nonce: encode(&random_bytes::<NONCE_LEN>()),
+                    commitment: encode(&random_bytes::<32>()),
+                },
+            )
+            .await
+            .unwrap();
+            let reply: Reply = recv_plain(&mut b).await.unwrap();
+            reply
+        });
+
+        let result = pair_initiator(&mut a, &alice, "123456").await;
+
+        assert!(matches!(result, Err(ProtocolError::InvalidMessage(_))));
+        // コミットメントを送らずに拒否する
+        assert!(matches!(responder.await.unwrap(), Reply::Rejected { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_secure_channel_roundtrip_and_unpaired_rejection() {
+        let alice = DeviceIdentity::generate("alice");
+        let bob = DeviceIdentity::generate("bob");
+        let alice_key = alice.signing_key.verifying_key();
+        let bob_key = bob.signing_key.verifying_key();
+        let bob_id = bob.device_id.clone();
+
+        let (a, mut b) = tokio::io::duplex(64 * 1024);
+        let server = tokio::spawn(async move {
+            let hello: Hello = recv_plain(&mut b).await.unwrap();
+            let mut channel = SecureChannel::accept(b, &bob, hello, |_| Some(alice_key)).await.unwrap();
+            let message: String = channel.recv().await.unwrap();
+            channel.send(&format!("echo: {message}")).await.unwrap();
+        });
+        let mut channel = SecureChannel::connect(a, &alice, |id| (id == bob_id).then_some(bob_key))
+            .await
+            .unwrap();
+        channel.send(&"hello").await.unwrap();
+        let reply: String = channel.recv().await.unwrap();
+        server.await.unwrap();
+        assert_eq!(reply, "echo: hello");
+
+        // ペアリングしていない端末からの接続は拒否
+        let stranger = DeviceIdentity::generate("stranger");
+        let bob = DeviceIdentity::generate("bob");
+        let (a, mut b) = tokio::io::duplex(64 * 1024);
+        let server = tokio::spawn(async move {
+            let hello: Hello = recv_plain(&mut b).await.unwrap();
+            SecureChannel::accept(b, &bob, hello, |_| None).await.map(|_| ())
+        });
+        let result = SecureChannel::connect(a, &stranger, |_| None).await;
+        assert!(matches!(result, Err(ProtocolError::Rejected(_))));
+        assert!(server.await.unwrap().is_err());
+    }
+
+    fn test_channel<S>(stream: S) -> SecureChannel<S> {
+        SecureChannel {
+            stream,
+            peer_device_id: String::new(),
+            send_cipher: ChaCha20Poly1305::new(Key::from_slice(&[7u8; 32])),
+            recv_cipher: ChaCha20Poly1305::new(Key::from_slice(&[7u8; 32])),
+            send_counter: 0,
+            recv_counter: 0,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_secure_channel_detects_tampering() {
+        let mut sender = test_channel(tokio::io::join(tokio::io::empty(), Vec::new()));
+        sender.send(&"secret").await.unwrap();
+        let mut frame = std::mem::take(sender.stream.writer_mut());
+        let last = frame.len() - 1;
+        frame[last] ^= 0x01;
+
+        let mut receiver = test_channel(tokio::io::join(std::io::Cursor::new(frame), Vec::new()));
+        let result: Result<String, _> = receiver.recv().await;
+        assert!(matches!(result, Err(ProtocolError::AuthenticationFailed(_))));
+    }
+}
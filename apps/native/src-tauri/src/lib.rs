@@ -11,11 +11,24 @@ mod global_hotkey;
 mod hotkey_test;
 mod tray;
 mod app_state;
+mod autostart;
 mod environment;
 mod updater;
 mod security;
 mod update_urls;
 mod tauri_config;
+mod importer;
+mod markdown_sync;
+mod api_sync;
+mod lan_sync;
+mod encryption;
+mod vault;
+mod clipboard;
+mod paste;
+mod paste_guard;
+mod capture;
+mod settings;
+mod starter;
 
 #[cfg(test)]
 mod security_test;
@@ -25,8 +38,8 @@ mod updater_integration_test;
 
 use commands::{
     copy_pinned_prompt, create_prompt, delete_prompt, get_all_prompts, get_app_info,
-    get_pinned_prompts, get_prompt, init_database, pin_prompt, search_prompts,
-    search_prompts_fast, unpin_prompt, update_prompt
+    get_changes_since, get_pinned_prompts, get_prompt, init_database, pin_prompt, search_prompts,
+    search_prompts_fast, set_prompt_secret, unpin_prompt, update_prompt
 };
 use tauri::{Emitter, Manager};
 
@@ -64,9 +77,9 @@ pub fn run() {
             // 環境に基づいてウィンドウタイトルを設定
             use crate::environment::Environment;
             let env = Environment::current();
-            if let Some(window) = app.get_webview_window("main") {
-                let _ = window.set_title(env.window_title());
-            }
+            
+            // 自動起動が有効な場合は登録内容を現在のアプリの場所で書き直す
+            autostart::refresh_from_config();
             
             // アプリケーション起動時にデータベースを同期的に初期化
             // エラー時はアプリケーション起動を停止
@@ -77,6 +90,28 @@ pub fn run() {
                 eprintln!("Application cannot start without database. Please check permissions and disk space.");
                 e.to_string()
             })?;
+            let locked = database::is_database_locked();
+            
+            if let Some(window) = app.get_webview_window("main") {
+                let _ = window.set_title(env.window_title());
+                
+                // メインウィンドウは非表示で作成される（tauri.conf.json）
+                // `--hidden`付き（ログイン時の自動起動）の場合はトレイに常駐したまま表示しない
+                // ただしデータベースがロック中の場合は、パスフレーズを入力できるよう表示する
+                if !autostart::started_hidden() || locked {
+                    let _ = window.show();
+                    let _ = window.set_focus();
+                }
+            }
+            
+            // 同期サービスはロック解除後に再開する（encryption::unlock_database）
+            if !locked {
+                // Markdownフォルダ同期が有効な場合は監視を再開
+                markdown_sync::resume_from_config(app.handle());
+                
+                // LAN同期が有効な場合はサービスを再開
+                lan_sync::resume_from_config(app.handle());
+            }
             
             // システムトレイ初期化（失敗時もアプリは継続）
             if let Err(e) = tray::init_system_tray(app.handle()) {
@@ -132,6 +167,11 @@ pub fn run() {
                     }
                 }
                 
+                // 選択範囲の取り込みホットキー登録
+                if let Err(e) = capture::register_capture_hotkey(app_handle.clone()).await {
+                    eprintln!("Failed to register capture hotkey: {e}");
+                }
+                
                 // パレット用ホットキー登録
                 match global_hotkey::register_palette_hotkeys(app_handle.clone()).await {
                     Ok(()) => {
@@ -160,14 +200,22 @@ pub fn run() {
             search_prompts_fast,
             update_prompt,
             delete_prompt,
+            set_prompt_secret,
             get_app_info,
             pin_prompt,
             unpin_prompt,
             get_pinned_prompts,
             copy_pinned_prompt,
+            get_changes_since,
             shortcuts::register_global_shortcuts,
             shortcuts::unregister_global_shortcuts,
             shortcuts::get_shortcut_status,
+            shortcuts::retry_shortcut_registration,
+            shortcuts::pause_hotkeys,
+            shortcuts::resume_hotkeys,
+            shortcuts::get_hotkey_pause_state,
+            shortcuts::get_launcher_shortcut,
+            shortcuts::set_launcher_shortcut,
             shortcuts::hide_main_window,
             tray::toggle_main_window,
             tray::quit_app,
@@ -177,6 +225,11 @@ pub fn run() {
             global_hotkey::register_palette_hotkeys,
             global_hotkey::unregister_palette_hotkeys,
             global_hotkey::get_palette_hotkey_status,
+            global_hotkey::get_palette_hotkeys,
+            global_hotkey::set_palette_hotkeys,
+            global_hotkey::reset_palette_hotkeys,
+            global_hotkey::set_palette_chord,
+            global_hotkey::get_paste_history,
             hotkey_test::test_hotkey_combinations,
             hotkey_test::cleanup_test_hotkeys,
             commands::environment::get_current_environment,
@@ -189,7 +242,49 @@ pub fn run() {
             updater::list_backups,
             updater::cleanup_old_backups,
             updater::delete_backup,
-            updater::get_update_config
+            updater::get_update_config,
+            importer::preview_conversation_import,
+            importer::import_conversation_prompts,
+            markdown_sync::enable_markdown_sync,
+            markdown_sync::disable_markdown_sync,
+            markdown_sync::get_markdown_sync_status,
+            markdown_sync::sync_markdown_now,
+            markdown_sync::resolve_markdown_sync_conflict,
+            api_sync::configure_api_sync,
+            api_sync::sync_with_api,
+            api_sync::get_api_sync_status,
+            lan_sync::enable_lan_sync,
+            lan_sync::disable_lan_sync,
+            lan_sync::get_lan_sync_status,
+            lan_sync::start_lan_pairing,
+            lan_sync::pair_lan_peer,
+            lan_sync::sync_lan_now,
+            lan_sync::forget_lan_peer,
+            encryption::get_database_encryption_status,
+            encryption::enable_database_encryption,
+            encryption::disable_database_encryption,
+            encryption::change_database_passphrase,
+            encryption::unlock_database,
+            encryption::lock_database,
+            vault::get_vault_status,
+            vault::setup_vault,
+            vault::unlock_vault,
+            vault::lock_vault,
+            vault::set_vault_idle_timeout,
+            clipboard::get_clipboard_config,
+            clipboard::set_clipboard_config,
+            paste::get_paste_config,
+            paste::set_paste_config,
+            paste::get_paste_backends,
+            paste_guard::confirm_paste,
+            capture::create_prompt_from_clipboard,
+            starter::remove_starter_prompts,
+            autostart::enable_autostart,
+            autostart::disable_autostart,
+            autostart::get_autostart_status,
+            settings::get_settings,
+            settings::update_settings,
+            settings::reset_settings
         ])
         .run(tauri::generate_context!())
         .unwrap_or_else(|err| {
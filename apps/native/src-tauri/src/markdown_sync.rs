@@ -0,0 +1,1047 @@
+/*!
+ * Markdownフォルダとの双方向同期
+ *
+ * プロンプトライブラリを1プロンプト1ファイルの `.md` としてフォルダにミラーし、
+ * フォルダ側の編集（git pull やエディタでの変更）をSQLiteに反映する
+ *
+ * ファイル形式:
+ * ```text
+ * ---
+ * id: "<uuid>"
+ * title: "タイトル"
+ * tags: ["tag1", "tag2"]
+ * pinned: 3
+ * quick_access_key: "qa"
+ * updated_at: "2025-01-01T00:00:00Z"
+ * ---
+ * 本文
+ * ```
+ *
+ * 競合検出:
+ * - 前回同期時の内容ハッシュと `updated_at` をフォルダ内の状態ファイルに保存
+ * - アプリ側・フォルダ側の両方が変更されていれば競合として報告し、どちらも上書きしない
+ * - 読み込めないファイル（フロントマターの編集途中など）は削除として扱わず、直されるまでそのプロンプトを同期しない
+ */
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+use crate::database::{
+    delete_prompt as db_delete_prompt, get_all_prompts as db_get_all_prompts,
+    pin_prompt as db_pin_prompt, unpin_prompt as db_unpin_prompt, upsert_prompt as db_upsert_prompt,
+    CreatePromptRequest, Prompt, MAX_PIN_POSITION,
+};
+use crate::environment::Environment;
+
+/// フォルダ監視のポーリング間隔
+const WATCH_INTERVAL: Duration = Duration::from_secs(3);
+
+/// 同期状態ファイル名（同期フォルダ直下に保存）
+const STATE_FILENAME: &str = ".prompalette-sync.json";
+
+/// 同期設定ファイル名（アプリデータディレクトリに保存）
+const CONFIG_FILENAME: &str = "markdown_sync.json";
+
+/// 本文の最大長（create_promptの制限と同じ）
+const MAX_CONTENT_LENGTH: usize = 100_000;
+
+/// 同期状態ファイルのフォーマットバージョン
+const STATE_VERSION: u32 = 1;
+
+/// 実行中の監視タスク
+static WATCH_TASK: Mutex<Option<tauri::async_runtime::JoinHandle<()>>> = Mutex::new(None);
+
+/// 同期処理の排他ロック（手動同期と監視タスクの同時実行防止）
+static SYNC_LOCK: OnceLock<tokio::sync::Mutex<()>> = OnceLock::new();
+
+/// 直近の同期結果
+static LAST_REPORT: Mutex<Option<SyncReport>> = Mutex::new(None);
+
+/// 同期設定（永続化）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MarkdownSyncConfig {
+    pub enabled: bool,
+    pub directory: Option<String>,
+}
+
+impl MarkdownSyncConfig {
+    fn config_path() -> Result<PathBuf, String> {
+        let env = Environment::current();
+        let data_dir = env.data_dir().map_err(|e| e.to_string())?;
+        Ok(data_dir.join(CONFIG_FILENAME))
+    }
+
+    /// 設定ファイルから読み込み（存在しない場合はデフォルト）
+    pub fn load() -> Result<Self, String> {
+        let path = Self::config_path()?;
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(&path).map_err(|e| e.to_string())?;
+        serde_json::from_str(&content).map_err(|e| format!("Invalid sync config: {e}"))
+    }
+
+    /// 設定ファイルへ保存
+    pub fn save(&self) -> Result<(), String> {
+        let path = Self::config_path()?;
+        let content = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        std::fs::write(&path, content).map_err(|e| format!("Failed to save sync config: {e}"))
+    }
+}
+
+/// 1プロンプト分のMarkdownドキュメント
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyncDocument {
+    pub id: Option<String>,
+    pub title: Option<String>,
+    pub tags: Vec<String>,
+    pub pinned: Option<u8>,
+    pub quick_access_key: Option<String>,
+    pub content: String,
+}
+
+impl SyncDocument {
+    /// データベースのプロンプトから生成
+    pub fn from_prompt(prompt: &Prompt) -> Self {
+        let tags = prompt
+            .tags
+            .as_deref()
+            .and_then(|tags| serde_json::from_str::<Vec<String>>(tags).ok())
+            .unwrap_or_default();
+
+        Self {
+            id: Some(prompt.id.clone()),
+            title: prompt.title.clone(),
+            tags,
+            pinned: prompt.pinned_position,
+            quick_access_key: prompt.quick_access_key.clone(),
+            content: prompt.content.clone(),
+        }
+    }
+
+    /// 同期対象フィールドの内容ハッシュ（idとupdated_atは含まない）
+    pub fn content_hash(&self) -> String {
+        let canonical = serde_json::json!([
+            self.title,
+            self.tags,
+            self.pinned,
+            self.quick_access_key,
+            self.content,
+        ]);
+        let digest = Sha256::digest(canonical.to_string().as_bytes());
+        digest.iter().map(|byte| format!("{byte:02x}")).collect()
+    }
+
+    /// Markdown（フロントマター付き）として出力
+    pub fn render(&self, updated_at: Option<chrono::DateTime<chrono::Utc>>) -> String {
+        // 値はJSONでエンコードする（JSONの文字列・配列はYAMLとしても有効）
+        let json = |value: serde_json::Value| value.to_string();
+
+        let mut out = String::from("---\n");
+        if let Some(id) = &self.id {
+            out.push_str(&format!("id: {}\n", json(id.as_str().into())));
+        }
+        out.push_str(&format!("title: {}\n", json(serde_json::json!(self.title))));
+        out.push_str(&format!("tags: {}\n", json(serde_json::json!(self.tags))));
+        out.push_str(&format!("pinned: {}\n", json(serde_json::json!(self.pinned))));
+        out.push_str(&format!(
+            "quick_access_key: {}\n",
+            json(serde_json::json!(self.quick_access_key))
+        ));
+        if let Some(updated_at) = updated_at {
+            out.push_str(&format!("updated_at: {}\n", json(updated_at.to_rfc3339().into())));
+        }
+        out.push_str("---\n");
+        out.push_str(&self.content);
+        out.push('\n');
+        out
+    }
+
+    /// Markdownを解析（手編集されたフロントマターもある程度許容する）
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let text = text.strip_prefix('\u{feff}').unwrap_or(text).replace("\r\n", "\n");
+
+        let mut doc = Self {
+            id: None,
+            title: None,
+            tags: Vec::new(),
+            pinned: None,
+            quick_access_key: None,
+            content: String::new(),
+        };
+
+        let Some(rest) = text.strip_prefix("---\n") else {
+            // フロントマターなし: 全体を本文として扱う
+            doc.content = strip_single_newline(&text).to_string();
+            return Ok(doc);
+        };
+
+        let (front_matter, body) = if let Some(body) = rest.strip_prefix("---\n") {
+            // 空のフロントマター
+            ("", body)
+        } else if let Some(end) = rest.find("\n---\n") {
+            (&rest[..end], &rest[end + 5..])
+        } else if let Some(front_matter) = rest.strip_suffix("\n---") {
+            (front_matter, "")
+        } else {
+            return Err("Unterminated front matter".to_string());
+        };
+
+        for line in front_matter.lines() {
+            let Some((key, raw)) = line.split_once(':') else {
+                continue;
+            };
+            let raw = raw.trim();
+            match key.trim() {
+                "id" => doc.id = parse_string(raw),
+                "title" => doc.title = parse_string(raw),
+                "tags" => doc.tags = parse_tags(raw),
+                "pinned" => {
+                    doc.pinned = match parse_string(raw) {
+                        Some(value) => {
+                            let position: u8 =
+                                value.parse().map_err(|_| format!("Invalid pinned value: {value}"))?;
+                            if !(1..=MAX_PIN_POSITION).contains(&position) {
+                                return Err(format!("Pin position must be between 1 and {MAX_PIN_POSITION}"));
+                            }
+                            Some(position)
+                        }
+                        None => None,
+                    }
+                }
+                "quick_access_key" => doc.quick_access_key = parse_string(raw),
+                _ => {} // updated_atなどは情報表示のみ
+            }
+        }
+
+        doc.content = strip_single_newline(body).to_string();
+        Ok(doc)
+    }
+}
+
+/// 末尾の改行を1つだけ取り除く（render時に付与したもの）
+fn strip_single_newline(text: &str) -> &str {
+    text.strip_suffix('\n').unwrap_or(text)
+}
+
+/// フロントマターの値を文字列として解釈（null/空はNone）
+fn parse_string(raw: &str) -> Option<String> {
+    let value = match serde_json::from_str::<serde_json::Value>(raw) {
+        Ok(serde_json::Value::Null) => return None,
+        Ok(serde_json::Value::String(value)) => value,
+        Ok(value) => value.to_string(),
+        // 引用符なしの手書き値
+        Err(_) => raw.trim_matches(|c| c == '\'' || c == '"').to_string(),
+    };
+    let value = value.trim();
+    if value.is_empty() || value == "~" || value == "null" {
+        None
+    } else {
+        Some(value.to_string())
+    }
+}
+
+/// タグ配列を解釈（JSON配列と `[a, b]` 形式の両方に対応）
+fn parse_tags(raw: &str) -> Vec<String> {
+    if let Ok(tags) = serde_json::from_str::<Vec<String>>(raw) {
+        return tags;
+    }
+    raw.trim_start_matches('[')
+        .trim_end_matches(']')
+        .split(',')
+        .map(|tag| tag.trim().trim_matches(|c| c == '\'' || c == '"').to_string())
+        .filter(|tag| !tag.is_empty())
+        .collect()
+}
+
+/// 前回同期時の記録
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SyncEntry {
+    /// 同期フォルダからの相対パス
+    pub path: String,
+    pub hash: String,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// 同期状態（同期フォルダ内に保存）
+///
+/// フォルダをgitで管理しても差分が出ないよう、IDの順に保存し、変更がない場合は書き込まない
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+struct SyncState {
+    version: u32,
+    entries: BTreeMap<String, SyncEntry>,
+}
+
+impl SyncState {
+    fn load(dir: &Path) -> Result<Self, String> {
+        let path = dir.join(STATE_FILENAME);
+        if !path.exists() {
+            return Ok(Self {
+                version: STATE_VERSION,
+                entries: BTreeMap::new(),
+            });
+        }
+        let content = std::fs::read_to_string(&path).map_err(|e| e.to_string())?;
+        serde_json::from_str(&content).map_err(|e| format!("Invalid sync state file: {e}"))
+    }
+
+    fn save(&self, dir: &Path) -> Result<(), String> {
+        let content = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        std::fs::write(dir.join(STATE_FILENAME), content)
+            .map_err(|e| format!("Failed to save sync state: {e}"))
+    }
+}
+
+/// 競合の種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictKind {
+    /// アプリとフォルダの両方で変更された
+    BothModified,
+    /// アプリで削除されたがフォルダで変更された
+    DeletedInApp,
+    /// フォルダで削除されたがアプリで変更された
+    DeletedInFolder,
+}
+
+/// 同期競合
+#[derive(Debug, Clone, Serialize)]
+pub struct SyncConflict {
+    pub id: String,
+    pub kind: ConflictKind,
+    pub path: Option<String>,
+    pub app_updated_at: Option<String>,
+}
+
+/// 同期結果
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SyncReport {
+    pub files_written: usize,
+    pub prompts_updated: usize,
+    pub files_deleted: usize,
+    pub prompts_deleted: usize,
+    pub conflicts: Vec<SyncConflict>,
+    /// アプリに反映できなかったファイル（ファイル名, 理由）。次回の同期で再試行する
+    pub failed: Vec<(String, String)>,
+    pub synced_at: String,
+}
+
+impl SyncReport {
+    fn has_changes(&self) -> bool {
+        self.files_written + self.prompts_updated + self.files_deleted + self.prompts_deleted > 0
+    }
+}
+
+/// 1プロンプトに対する同期アクション
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncAction {
+    Unchanged,
+    WriteFile,
+    ApplyToApp,
+    DeleteFile,
+    DeletePrompt,
+    Forget,
+    Conflict(ConflictKind),
+    /// 前回同期したファイルが読み込めない（直されるまで何もしない）
+    Skip,
+}
+
+/// アプリ側・フォルダ側・前回同期時の状態からアクションを決定
+///
+/// app: アプリ側の (内容ハッシュ, updated_at)
+/// file_hash: フォルダ側の内容ハッシュ
+/// entry: 前回同期時の記録
+pub fn plan_action(
+    app: Option<(&str, chrono::DateTime<chrono::Utc>)>,
+    file_hash: Option<&str>,
+    entry: Option<&SyncEntry>,
+) -> SyncAction {
+    let app_changed = |entry: &SyncEntry| {
+        app.is_some_and(|(hash, updated_at)| hash != entry.hash || updated_at > entry.updated_at)
+    };
+
+    match (app, file_hash, entry) {
+        (Some((app_hash, _)), Some(file_hash), _) if app_hash == file_hash => SyncAction::Unchanged,
+        // 基準となる同期記録がないまま内容が食い違っている
+        (Some(_), Some(_), None) => SyncAction::Conflict(ConflictKind::BothModified),
+        (Some(_), Some(file_hash), Some(entry)) => {
+            match (app_changed(entry), file_hash != entry.hash) {
+                (true, false) => SyncAction::WriteFile,
+                (false, true) => SyncAction::ApplyToApp,
+                (true, true) => SyncAction::Conflict(ConflictKind::BothModified),
+                (false, false) => SyncAction::Unchanged,
+            }
+        }
+        (Some(_), None, None) => SyncAction::WriteFile,
+        (Some(_), None, Some(entry)) => {
+            if app_changed(entry) {
+                SyncAction::Conflict(ConflictKind::DeletedInFolder)
+            } else {
+                SyncAction::DeletePrompt
+            }
+        }
+        (None, Some(_), None) => SyncAction::ApplyToApp,
+        (None, Some(file_hash), Some(entry)) => {
+            if file_hash != entry.hash {
+                SyncAction::Conflict(ConflictKind::DeletedInApp)
+            } else {
+                SyncAction::DeleteFile
+            }
+        }
+        (None, None, _) => SyncAction::Forget,
+    }
+}
+
+/// 前回同期したファイルが読み込めない場合は`Skip`、それ以外は`plan_action`で決定
+///
+/// invalid: 読み込めなかったファイル（ファイル名 → 理由）
+fn plan_for_folder(
+    app: Option<(&str, chrono::DateTime<chrono::Utc>)>,
+    file_hash: Option<&str>,
+    entry: Option<&SyncEntry>,
+    invalid: &BTreeMap<String, String>,
+) -> SyncAction {
+    if file_hash.is_none() && entry.is_some_and(|entry| invalid.contains_key(&entry.path)) {
+        return SyncAction::Skip;
+    }
+    plan_action(app, file_hash, entry)
+}
+
+/// フォルダ内のMarkdownファイル
+struct FolderFile {
+    path: String,
+    doc: SyncDocument,
+    hash: String,
+}
+
+/// タイトルからファイル名を生成（`<slug>-<idの先頭8文字>.md`）
+fn file_name_for(doc: &SyncDocument, id: &str) -> String {
+    let source = doc
+        .title
+        .as_deref()
+        .unwrap_or_else(|| doc.content.lines().next().unwrap_or_default());
+    let mut slug = String::new();
+    for c in source.chars().flat_map(char::to_lowercase) {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c);
+        } else if !slug.ends_with('-') && !slug.is_empty() {
+            slug.push('-');
+        }
+        if slug.len() >= 40 {
+            break;
+        }
+    }
+    let slug = slug.trim_end_matches('-');
+    let slug = if slug.is_empty() { "prompt" } else { slug };
+    let short_id: String = id.chars().filter(char::is_ascii_alphanumeric).take(8).collect();
+    format!("{slug}-{short_id}.md")
+}
+
+/// 同期フォルダ直下の`.md`ファイル名かどうか（状態ファイルは手で編集できるため検証する）
+fn is_sync_file_name(path: &str) -> bool {
+    let mut components = Path::new(path).components();
+    let single_component = matches!(
+        (components.next(), components.next()),
+        (Some(std::path::Component::Normal(name)), None) if name == path
+    );
+    single_component
+        && !path.contains(['/', '\\', ':'])
+        && path.strip_suffix(".md").is_some_and(|stem| !stem.is_empty())
+}
+
+/// 前回同期時のファイル名（不正な場合はタイトルから作り直す）
+fn entry_file_name(entry: Option<&SyncEntry>, prompt: &Prompt) -> String {
+    entry
+        .map(|entry| entry.path.clone())
+        .filter(|path| is_sync_file_name(path))
+        .unwrap_or_else(|| file_name_for(&SyncDocument::from_prompt(prompt), &prompt.id))
+}
+
+/// 同期フォルダの読み込み結果
+#[derive(Default)]
+struct FolderScan {
+    with_id: HashMap<String, FolderFile>,
+    /// IDがない・重複しているファイル（新規プロンプトとして扱う）
+    without_id: Vec<FolderFile>,
+    /// 存在するが読み込めないファイル（ファイル名 → 理由）
+    invalid: BTreeMap<String, String>,
+}
+
+/// 同期フォルダ内のMarkdownファイルを読み込む
+fn scan_folder(dir: &Path) -> Result<FolderScan, String> {
+    let mut scan = FolderScan::default();
+
+    let entries = std::fs::read_dir(dir).map_err(|e| format!("Failed to read sync folder: {e}"))?;
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext == "md"))
+        .collect();
+    paths.sort();
+
+    for path in paths {
+        let Some(name) = path.file_name().map(|name| name.to_string_lossy().to_string()) else {
+            continue;
+        };
+        let doc = std::fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read file: {e}"))
+            .and_then(|text| SyncDocument::parse(&text))
+            .and_then(|doc| {
+                if doc.content.trim().is_empty() || doc.content.len() > MAX_CONTENT_LENGTH {
+                    Err("Content is empty or too long".to_string())
+                } else {
+                    Ok(doc)
+                }
+            });
+        let doc = match doc {
+            Ok(doc) => doc,
+            Err(e) => {
+                scan.invalid.insert(name, e);
+                continue;
+            }
+        };
+
+        let hash = doc.content_hash();
+        let valid_id = doc
+            .id
+            .clone()
+            .filter(|id| uuid::Uuid::parse_str(id).is_ok() && !scan.with_id.contains_key(id));
+        let file = FolderFile { path: name, doc, hash };
+        match valid_id {
+            Some(id) => {
+                scan.with_id.insert(id, file);
+            }
+            None => scan.without_id.push(file),
+        }
+    }
+
+    Ok(scan)
+}
+
+/// フォルダ側の内容をアプリに反映
+async fn apply_to_app(id: &str, doc: &SyncDocument, current: Option<&Prompt>) -> Result<Prompt, String> {
+    let request = CreatePromptRequest {
+        title: doc.title.clone(),
+        content: doc.content.clone(),
+        tags: Some(doc.tags.clone()),
+        quick_access_key: doc.quick_access_key.clone(),
+        status: None,
+        visibility: None,
+        workspace_id: None,
+        is_sensitive: None,
+        paste_confirmation: None,
+    };
+    let mut prompt = db_upsert_prompt(id, request)
+        .await
+        .map_err(|e| format!("Failed to save prompt {id}: {e}"))?;
+
+    let current_pin = current.and_then(|prompt| prompt.pinned_position);
+    if doc.pinned != current_pin {
+        match doc.pinned {
+            Some(position) => db_pin_prompt(id, position).await,
+            None => match current_pin {
+                Some(position) => db_unpin_prompt(position).await,
+                None => Ok(()),
+            },
+        }
+        .map_err(|e| format!("Failed to update pin for {id}: {e}"))?;
+        prompt.pinned_position = doc.pinned;
+    }
+
+    Ok(prompt)
+}
+
+/// アプリ側の内容をファイルに書き出す
+fn write_file(dir: &Path, path: &str, prompt: &Prompt) -> Result<SyncEntry, String> {
+    if !is_sync_file_name(path) {
+        return Err(format!("Invalid sync file name: {path}"));
+    }
+    let doc = SyncDocument::from_prompt(prompt);
+    std::fs::write(dir.join(path), doc.render(Some(prompt.updated_at)))
+        .map_err(|e| format!("Failed to write {path}: {e}"))?;
+    Ok(SyncEntry {
+        path: path.to_string(),
+        hash: doc.content_hash(),
+        updated_at: prompt.updated_at,
+    })
+}
+
+/// 同期済みとして記録するエントリ
+fn synced_entry(path: &str, prompt: &Prompt) -> SyncEntry {
+    SyncEntry {
+        path: path.to_string(),
+        hash: SyncDocument::from_prompt(prompt).content_hash(),
+        updated_at: prompt.updated_at,
+    }
+}
+
+/// 同期フォルダとデータベースを1回同期する
+pub async fn sync_directory(dir: &Path) -> Result<SyncReport, String> {
+    let _guard = SYNC_LOCK.get_or_init(|| tokio::sync::Mutex::new(())).lock().await;
+
+    if !dir.is_dir() {
+        return Err(format!("Sync folder does not exist: {}", dir.display()));
+    }
+
+    let mut state = SyncState::load(dir)?;
+    let loaded_state = state.clone();
+    let FolderScan { with_id: mut files, without_id: new_files, invalid } = scan_folder(dir)?;
+    // シークレットプロンプトはフォルダに書き出さない（書き出し済みのファイルはアプリ側で削除された扱い）
+    let prompts: HashMap<String, Prompt> = db_get_all_prompts()
+        .await
+        .map_err(|e| format!("Failed to load prompts: {e}"))?
+        .into_iter()
+        .filter(|prompt| !prompt.is_secret)
+        .map(|prompt| (prompt.id.clone(), prompt))
+        .collect();
+
+    // 読み込めないファイルは毎回報告する（直されるまで、そのファイルのプロンプトは削除も上書きもしない）
+    let mut report = SyncReport {
+        failed: invalid.iter().map(|(path, reason)| (path.clone(), reason.clone())).collect(),
+        ..SyncReport::default()
+    };
+    let ids: BTreeSet<String> = prompts
+        .keys()
+        .chain(files.keys())
+        .chain(state.entries.keys())
+        .cloned()
+        .collect();
+
+    for id in ids {
+        let prompt = prompts.get(&id);
+        let file = files.remove(&id);
+        let entry = state.entries.get(&id).cloned();
+        let app_hash = prompt.map(|prompt| SyncDocument::from_prompt(prompt).content_hash());
+        let app = prompt.zip(app_hash.as_deref()).map(|(prompt, hash)| (hash, prompt.updated_at));
+
+        match plan_for_folder(app, file.as_ref().map(|file| file.hash.as_str()), entry.as_ref(), &invalid) {
+            SyncAction::Unchanged => {
+                if let (Some(prompt), Some(file)) = (prompt, &file) {
+                    state.entries.insert(id, synced_entry(&file.path, prompt));
+                }
+            }
+            SyncAction::WriteFile => {
+                let Some(prompt) = prompt else { continue };
+                let path = match &file {
+                    Some(file) => file.path.clone(),
+                    None => entry_file_name(entry.as_ref(), prompt),
+                };
+                // 読み込めないファイルを上書きしない
+                if invalid.contains_key(&path) {
+                    continue;
+                }
+                state.entries.insert(id, write_file(dir, &path, prompt)?);
+                report.files_written += 1;
+            }
+            SyncAction::ApplyToApp => {
+                let Some(file) = file else { continue };
+                // 1ファイルの失敗（シークレットの上書き拒否など）で他のファイルの同期を止めない
+                match apply_to_app(&id, &file.doc, prompt).await {
+                    Ok(updated) => {
+                        state.entries.insert(id, synced_entry(&file.path, &updated));
+                        report.prompts_updated += 1;
+                    }
+                    Err(e) => report.failed.push((file.path, e)),
+                }
+            }
+            SyncAction::DeleteFile => {
+                if let Some(file) = file {
+                    std::fs::remove_file(dir.join(&file.path))
+                        .map_err(|e| format!("Failed to delete {}: {e}", file.path))?;
+                    report.files_deleted += 1;
+                }
+                state.entries.remove(&id);
+            }
+            SyncAction::DeletePrompt => {
+                db_delete_prompt(&id)
+                    .await
+                    .map_err(|e| format!("Failed to delete prompt {id}: {e}"))?;
+                state.entries.remove(&id);
+                report.prompts_deleted += 1;
+            }
+            SyncAction::Forget => {
+                state.entries.remove(&id);
+            }
+            SyncAction::Skip => {}
+            SyncAction::Conflict(kind) => {
+                report.conflicts.push(SyncConflict {
+                    id,
+                    kind,
+                    path: file.map(|file| file.path).or(entry.map(|entry| entry.path)),
+                    app_updated_at: prompt.map(|prompt| prompt.updated_at.to_rfc3339()),
+                });
+            }
+        }
+    }
+
+    // IDのないファイルは新規プロンプトとして作成し、IDを書き戻す
+    for file in new_files {
+        let id = uuid::Uuid::new_v4().to_string();
+        let prompt = match apply_to_app(&id, &file.doc, None).await {
+            Ok(prompt) => prompt,
+            Err(e) => {
+                report.failed.push((file.path, e));
+                continue;
+            }
+        };
+        state.entries.insert(id, write_file(dir, &file.path, &prompt)?);
+        report.prompts_updated += 1;
+    }
+
+    state.version = STATE_VERSION;
+    if state != loaded_state {
+        state.save(dir)?;
+    }
+
+    report.synced_at = chrono::Utc::now().to_rfc3339();
+    if let Ok(mut last) = LAST_REPORT.lock() {
+        *last = Some(report.clone());
+    }
+    Ok(report)
+}
+
+/// 競合を指定した側の内容で解決する
+///
+/// keep: "app"（アプリ側を採用）または "folder"（フォルダ側を採用）
+pub async fn resolve_conflict(dir: &Path, id: &str, keep: &str) -> Result<(), String> {
+    let _guard = SYNC_LOCK.get_or_init(|| tokio::sync::Mutex::new(())).lock().await;
+
+    let mut state = SyncState::load(dir)?;
+    let mut scan = scan_folder(dir)?;
+    let file = scan.with_id.remove(id);
+    // 読み込めないファイルをフォルダ側の削除として扱わない
+    if let Some(entry) = state.entries.get(id).filter(|entry| scan.invalid.contains_key(&entry.path)) {
+        return Err(format!("Fix the invalid sync file {} before resolving the conflict", entry.path));
+    }
+    let prompt = crate::database::get_prompt(id)
+        .await
+        .map_err(|e| format!("Failed to load prompt {id}: {e}"))?
+        .filter(|prompt| !prompt.is_secret);
+
+    match (keep, prompt, file) {
+        ("app", Some(prompt), file) => {
+            let path = match file {
+                Some(file) => file.path,
+                None => entry_file_name(state.entries.get(id), &prompt),
+            };
+            state.entries.insert(id.to_string(), write_file(dir, &path, &prompt)?);
+        }
+        ("app", None, file) => {
+            if let Some(file) = file {
+                std::fs::remove_file(dir.join(&file.path))
+                    .map_err(|e| format!("Failed to delete {}: {e}", file.path))?;
+            }
+            state.entries.remove(id);
+        }
+        ("folder", prompt, Some(file)) => {
+            let updated = apply_to_app(id, &file.doc, prompt.as_ref()).await?;
+            state.entries.insert(id.to_string(), synced_entry(&file.path, &updated));
+        }
+        ("folder", prompt, None) => {
+            if prompt.is_some() {
+                db_delete_prompt(id)
+                    .await
+                    .map_err(|e| format!("Failed to delete prompt {id}: {e}"))?;
+            }
+            state.entries.remove(id);
+        }
+        (other, _, _) => return Err(format!("Unknown conflict resolution: {other}")),
+    }
+
+    state.save(dir)
+}
+
+/// 同期結果をフロントエンドに通知
+fn emit_report(app_handle: &AppHandle, report: &SyncReport) {
+    if report.has_changes() {
+        crate::tray::refresh_tray(app_handle);
+        if let Err(e) = app_handle.emit("markdown-sync-completed", report) {
+            eprintln!("Failed to emit markdown sync event: {e}");
+        }
+    }
+    if !report.conflicts.is_empty() {
+        if let Err(e) = app_handle.emit("markdown-sync-conflict", &report.conflicts) {
+            eprintln!("Failed to emit markdown sync conflict event: {e}");
+        }
+    }
+}
+
+/// フォルダ監視タスクを開始（既存のタスクは停止）
+fn start_watching(app_handle: AppHandle, dir: PathBuf) {
+    stop_watching();
+
+    let task = tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(WATCH_INTERVAL);
+        let mut last_conflicts: Vec<String> = Vec::new();
+        loop {
+            interval.tick().await;
+            match sync_directory(&dir).await {
+                Ok(report) => {
+                    // 同じ競合を毎回通知しないよう、変化があった時のみ通知
+                    let conflicts: Vec<String> = report.conflicts.iter().map(|c| c.id.clone()).collect();
+                    if report.has_changes() || conflicts != last_conflicts {
+                        emit_report(&app_handle, &report);
+                    }
+                    last_conflicts = conflicts;
+                }
+                Err(e) => {
+                    eprintln!("Markdown sync failed: {e}");
+                    let _ = app_handle.emit("markdown-sync-failed", e);
+                }
+            }
+        }
+    });
+
+    if let Ok(mut current) = WATCH_TASK.lock() {
+        *current = Some(task);
+    }
+}
+
+/// フォルダ監視タスクを停止（データベースのロック時にも使用）
+pub(crate) fn stop_watching() {
+    if let Ok(mut current) = WATCH_TASK.lock() {
+        if let Some(task) = current.take() {
+            task.abort();
+        }
+    }
+}
+
+/// 保存済み設定に基づいて起動時に同期を再開
+pub fn resume_from_config(app_handle: &AppHandle) {
+    match MarkdownSyncConfig::load() {
+        Ok(MarkdownSyncConfig {
+            enabled: true,
+            directory: Some(directory),
+        }) => {
+            println!("Resuming markdown sync for {directory}");
+            start_watching(app_handle.clone(), PathBuf::from(directory));
+        }
+        Ok(_) => {}
+        Err(e) => eprintln!("Failed to load markdown sync config: {e}"),
+    }
+}
+
+/// Markdownフォルダ同期の有効化
+///
+/// directory: 同期先フォルダ（存在しない場合は作成）
+#[tauri::command]
+pub async fn enable_markdown_sync(app_handle: AppHandle, directory: String) -> Result<SyncReport, String> {
+    let dir = PathBuf::from(&directory);
+    if !dir.is_absolute() {
+        return Err("Sync folder must be an absolute path".to_string());
+    }
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create sync folder: {e}"))?;
+
+    // 初回同期に成功してから設定を保存する
+    let report = sync_directory(&dir).await?;
+    MarkdownSyncConfig {
+        enabled: true,
+        directory: Some(directory),
+    }
+    .save()?;
+
+    emit_report(&app_handle, &report);
+    start_watching(app_handle, dir);
+    Ok(report)
+}
+
+/// Markdownフォルダ同期の無効化（フォルダ内のファイルは残す）
+#[tauri::command]
+pub async fn disable_markdown_sync() -> Result<(), String> {
+    stop_watching();
+    let mut config = MarkdownSyncConfig::load()?;
+    config.enabled = false;
+    config.save()
+}
+
+/// Markdownフォルダ同期の状態を取得
+#[tauri::command]
+pub async fn get_markdown_sync_status() -> Result<serde_json::Value, String> {
+    let config = MarkdownSyncConfig::load()?;
+    let watching = WATCH_TASK.lock().map(|task| task.is_some()).unwrap_or(false);
+    let last_report = LAST_REPORT.lock().ok().and_then(|report| report.clone());
+
+    Ok(serde_json::json!({
+        "enabled": config.enabled,
+        "directory": config.directory,
+        "watching": watching,
+        "last_report": last_report,
+    }))
+}
+
+/// 即時同期
+#[tauri::command]
+pub async fn sync_markdown_now(app_handle: AppHandle) -> Result<SyncReport, String> {
+    let config = MarkdownSyncConfig::load()?;
+    let directory = config
+        .directory
+        .filter(|_| config.enabled)
+        .ok_or("Markdown sync is not enabled")?;
+
+    let report = sync_directory(Path::new(&directory)).await?;
+    emit_report(&app_handle, &report);
+    Ok(report)
+}
+
+/// 同期競合の解決
+///
+/// id: 競合しているプロンプトのID
+/// keep: "app" または "folder"
+#[tauri::command]
+pub async fn resolve_markdown_sync_conflict(id: String, keep: String) -> Result<(), String> {
+    let config = MarkdownSyncConfig::load()?;
+    let directory = config
+        .directory
+        .filter(|_| config.enabled)
+        .ok_or("Markdown sync is not enabled")?;
+
+    resolve_conflict(Path::new(&directory), &id, &keep).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn doc() -> SyncDocument {
+        SyncDocument {
+            id: Some("0b7f6f5e-8a0c-4f4e-9b1d-2f1c3e4d5a6b".to_string()),
+            title: Some("Code review".to_string()),
+            tags: vec!["review".to_string(), "git".to_string()],
+            pinned: Some(3),
+            quick_access_key: Some("cr".to_string()),
+            content: "Review this diff:\n\n---\nnot front matter".to_string(),
+        }
+    }
+
+    fn entry(hash: &str) -> SyncEntry {
+        SyncEntry {
+            path: "a.md".to_string(),
+            hash: hash.to_string(),
+            updated_at: chrono::DateTime::from_timestamp(1_000, 0).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_render_and_parse_roundtrip() {
+        let original = doc();
+        let rendered = original.render(Some(chrono::Utc::now()));
+        let parsed = SyncDocument::parse(&rendered).unwrap();
+
+        assert_eq!(parsed, original);
+        assert_eq!(parsed.content_hash(), original.content_hash());
+    }
+
+    #[test]
+    fn test_parse_hand_written_front_matter() {
+        let text = "---\ntitle: My prompt\ntags: [a, 'b']\npinned: ~\n---\nHello\n";
+        let parsed = SyncDocument::parse(text).unwrap();
+
+        assert_eq!(parsed.id, None);
+        assert_eq!(parsed.title.as_deref(), Some("My prompt"));
+        assert_eq!(parsed.tags, vec!["a", "b"]);
+        assert_eq!(parsed.pinned, None);
+        assert_eq!(parsed.content, "Hello");
+    }
+
+    #[test]
+    fn test_parse_rejects_invalid_pin() {
+        assert!(SyncDocument::parse("---\npinned: 37\n---\nx\n").is_err());
+        assert!(SyncDocument::parse("---\ntitle: x\n").is_err());
+    }
+
+    #[test]
+    fn test_file_name_for() {
+        let d = doc();
+        assert_eq!(file_name_for(&d, d.id.as_ref().unwrap()), "code-review-0b7f6f5e.md");
+    }
+
+    #[test]
+    fn test_state_roundtrip_is_stable() {
+        let dir = std::env::temp_dir().join(format!("prompalette-md-sync-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let mut state = SyncState::load(&dir).unwrap();
+        for id in ["c", "a", "b"] {
+            state.entries.insert(id.to_string(), entry(id));
+        }
+        state.save(&dir).unwrap();
+        let saved = std::fs::read_to_string(dir.join(STATE_FILENAME)).unwrap();
+        let loaded = SyncState::load(&dir).unwrap();
+        loaded.save(&dir).unwrap();
+
+        assert_eq!(loaded, state);
+        assert_eq!(std::fs::read_to_string(dir.join(STATE_FILENAME)).unwrap(), saved);
+        let positions: Vec<usize> = ["\"a\"", "\"b\"", "\"c\""].iter().map(|key| saved.find(key).unwrap()).collect();
+        assert!(positions.windows(2).all(|pair| pair[0] < pair[1]));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_is_sync_file_name() {
+        assert!(is_sync_file_name("code-review-0b7f6f5e.md"));
+        assert!(!is_sync_file_name("../outside.md"));
+        assert!(!is_sync_file_name("/etc/cron.d/x.md"));
+        assert!(!is_sync_file_name("nested/prompt.md"));
+        assert!(!is_sync_file_name("..\\outside.md"));
+        assert!(!is_sync_file_name("C:prompt.md"));
+        assert!(!is_sync_file_name("prompt.txt"));
+        assert!(!is_sync_file_name(".md"));
+        assert!(!is_sync_file_name(""));
+    }
+
+    #[test]
+    fn test_invalid_tracked_file_does_not_delete_prompt() {
+        let dir = std::env::temp_dir().join(format!("prompalette-md-sync-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let tracked = doc();
+        std::fs::write(dir.join("a.md"), tracked.render(None)).unwrap();
+        let base = entry(&tracked.content_hash());
+        let synced = scan_folder(&dir).unwrap();
+        assert!(synced.with_id.contains_key(tracked.id.as_ref().unwrap()));
+
+        // フロントマターの編集途中で壊れた
+        std::fs::write(dir.join("a.md"), "---\ntitle: Code review\nBroken").unwrap();
+        let scan = scan_folder(&dir).unwrap();
+        assert!(scan.with_id.is_empty());
+        assert!(scan.invalid.contains_key("a.md"));
+
+        let app = Some((base.hash.as_str(), base.updated_at));
+        assert_eq!(plan_for_folder(app, None, Some(&base), &scan.invalid), SyncAction::Skip);
+        assert_eq!(plan_for_folder(None, None, Some(&base), &scan.invalid), SyncAction::Skip);
+        // ファイル自体を削除した場合は従来どおり
+        assert_eq!(plan_for_folder(app, None, Some(&base), &BTreeMap::new()), SyncAction::DeletePrompt);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_plan_action() {
+        let old = chrono::DateTime::from_timestamp(1_000, 0).unwrap();
+        let new = chrono::DateTime::from_timestamp(2_000, 0).unwrap();
+        let base = entry("h0");
+
+        assert_eq!(plan_action(Some(("h1", new)), Some("h1"), Some(&base)), SyncAction::Unchanged);
+        assert_eq!(plan_action(Some(("h1", new)), Some("h0"), Some(&base)), SyncAction::WriteFile);
+        assert_eq!(plan_action(Some(("h0", old)), Some("h2"), Some(&base)), SyncAction::ApplyToApp);
+        assert_eq!(
+            plan_action(Some(("h1", new)), Some("h2"), Some(&base)),
+            SyncAction::Conflict(ConflictKind::BothModified)
+        );
+        assert_eq!(plan_action(Some(("h1", new)), None, None), SyncAction::WriteFile);
+        assert_eq!(plan_action(Some(("h0", old)), None, Some(&base)), SyncAction::DeletePrompt);
+        assert_eq!(
+            plan_action(Some(("h1", new)), None, Some(&base)),
+            SyncAction::Conflict(ConflictKind::DeletedInFolder)
+        );
+        assert_eq!(plan_action(None, Some("h2"), None), SyncAction::ApplyToApp);
+        assert_eq!(plan_action(None, Some("h0"), Some(&base)), SyncAction::DeleteFile);
+        assert_eq!(
+            plan_action(None, Some("h2"), Some(&base)),
+            SyncAction::Conflict(ConflictKind::DeletedInApp)
+        );
+        assert_eq!(plan_action(None, None, Some(&base)), SyncAction::Forget);
+    }
+}
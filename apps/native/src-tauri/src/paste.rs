@@ -0,0 +1,637 @@
+/*!
+ * ペースト方式の切り替え
+ *
+ * パレットホットキーでプロンプトをアクティブアプリケーションに送る方法を選択できるようにする
+ *
+ * - 方式
+ *   - `keystroke`: クリップボードに書き込み、Cmd+V（macOS）/ Ctrl+Vを送信
+ *   - `terminal_keystroke`: クリップボードに書き込み、Ctrl+Shift+Vを送信（ターミナル向け、macOSはCmd+V）
+ *   - `type_text`: クリップボードを使わず本文を直接入力
+ * - 送信手段（上から順に試し、失敗したら次を使う）
+ *   - macOS: CGEvent → AppleScript（キー送信のみ）
+ *   - Windows: enigo
+ *   - Linux（Wayland）: `wtype` → `ydotool` → enigo（XWaylandがある場合）
+ *   - Linux（X11）: enigo → `xdotool` → `ydotool`
+ *
+ * 外部ヘルパーはPATH上にある場合のみ使用し、本文は引数ではなく標準入力で渡す
+ *
+ * 本文の直接入力は、ホットキーの修飾キーを離すまで待ってから始める（押したままだと入力した文字と組み合わさる）。
+ * 途中まで入力した後に失敗した場合は、二重に入力しないよう次の送信手段を試さない
+ *
+ * 選択範囲の取り込み（captureモジュール）で使うコピーのキーストロークも同じ送信手段で送る
+ */
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::Duration;
+use tauri::AppHandle;
+
+use crate::database::PasteConfirmation;
+
+/// ペースト方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PasteMethod {
+    /// クリップボード + Cmd/Ctrl+V
+    #[default]
+    Keystroke,
+    /// クリップボード + Ctrl+Shift+V（ターミナル向け）
+    TerminalKeystroke,
+    /// 本文を直接入力
+    TypeText,
+}
+
+impl PasteMethod {
+    /// クリップボードを経由するかどうか
+    pub fn uses_clipboard(self) -> bool {
+        self != Self::TypeText
+    }
+}
+
+/// キー入力の送信手段
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PasteDriver {
+    /// CGEvent（macOS）/ enigo（Windows・Linux）
+    Native,
+    /// AppleScript（macOS、キー送信のみ）
+    AppleScript,
+    /// `wtype`（Wayland）
+    Wtype,
+    /// `xdotool`（X11）
+    Xdotool,
+    /// `ydotool`（uinput経由、`ydotoold`が必要）
+    Ydotool,
+}
+
+impl PasteDriver {
+    /// 外部コマンド名（外部ヘルパーの場合）
+    fn helper_command(self) -> Option<&'static str> {
+        match self {
+            Self::Native => None,
+            Self::AppleScript => Some("osascript"),
+            Self::Wtype => Some("wtype"),
+            Self::Xdotool => Some("xdotool"),
+            Self::Ydotool => Some("ydotool"),
+        }
+    }
+
+    fn supports(self, method: PasteMethod) -> bool {
+        !(self == Self::AppleScript && method == PasteMethod::TypeText)
+    }
+}
+
+/// ホットキーの修飾キーを離すまで待つ時間
+pub(crate) const KEY_RELEASE_DELAY: Duration = Duration::from_millis(200);
+
+/// ペースト前に確認するプロンプトの大きさ（文字数）
+const DEFAULT_CONFIRM_THRESHOLD: usize = 5_000;
+const MIN_CONFIRM_THRESHOLD: usize = 100;
+const MAX_CONFIRM_THRESHOLD: usize = 100_000;
+
+/// ペースト設定（アプリケーション設定の`paste`セクション）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PasteConfig {
+    #[serde(default)]
+    pub method: PasteMethod,
+    /// 送信手段を固定する場合に指定（省略時は自動検出した順に試す）
+    #[serde(default)]
+    pub driver: Option<PasteDriver>,
+    /// ホットキーでペーストする前に確認する文字数（超える場合に確認、`null`の場合は確認しない）
+    ///
+    /// プロンプトごとの`paste_confirmation`で常に確認・確認しないに変更できる
+    #[serde(default = "default_confirm_threshold")]
+    pub confirm_threshold: Option<usize>,
+}
+
+fn default_confirm_threshold() -> Option<usize> {
+    Some(DEFAULT_CONFIRM_THRESHOLD)
+}
+
+impl Default for PasteConfig {
+    fn default() -> Self {
+        Self {
+            method: PasteMethod::default(),
+            driver: None,
+            confirm_threshold: default_confirm_threshold(),
+        }
+    }
+}
+
+impl PasteConfig {
+    pub(crate) fn validate(&self) -> Result<(), String> {
+        if let Some(driver) = self.driver {
+            if !driver.supports(self.method) {
+                return Err(format!("{driver:?} cannot be used with {:?}", self.method));
+            }
+        }
+        match self.confirm_threshold {
+            Some(threshold) if !(MIN_CONFIRM_THRESHOLD..=MAX_CONFIRM_THRESHOLD).contains(&threshold) => Err(format!(
+                "Paste confirmation threshold must be between {MIN_CONFIRM_THRESHOLD} and {MAX_CONFIRM_THRESHOLD} characters"
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// ホットキーでペーストする前に確認が必要か
+    pub(crate) fn needs_confirmation(&self, text: &str, confirmation: PasteConfirmation) -> bool {
+        match confirmation {
+            PasteConfirmation::Always => true,
+            PasteConfirmation::Never => false,
+            PasteConfirmation::Default => self
+                .confirm_threshold
+                .is_some_and(|threshold| text.chars().count() > threshold),
+        }
+    }
+}
+
+/// 実行環境
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Platform {
+    MacOs,
+    Windows,
+    /// Linuxなど（`wayland`: Waylandセッション、`x11`: X11またはXWaylandが使える）
+    Unix { wayland: bool, x11: bool },
+}
+
+impl Platform {
+    fn current() -> Self {
+        if cfg!(target_os = "macos") {
+            Self::MacOs
+        } else if cfg!(target_os = "windows") {
+            Self::Windows
+        } else {
+            let non_empty = |name: &str| std::env::var_os(name).is_some_and(|value| !value.is_empty());
+            Self::Unix {
+                wayland: non_empty("WAYLAND_DISPLAY")
+                    || std::env::var("XDG_SESSION_TYPE").is_ok_and(|session| session == "wayland"),
+                x11: non_empty("DISPLAY"),
+            }
+        }
+    }
+
+    /// 試す順序（利用可否は考慮しない）
+    fn driver_order(self) -> Vec<PasteDriver> {
+        use PasteDriver::*;
+        match self {
+            Self::MacOs => vec![Native, AppleScript],
+            Self::Windows => vec![Native],
+            Self::Unix { wayland: true, x11 } => {
+                let mut order = vec![Wtype, Ydotool];
+                if x11 {
+                    order.push(Native);
+                }
+                order
+            }
+            Self::Unix { wayland: false, .. } => vec![Native, Xdotool, Ydotool],
+        }
+    }
+}
+
+/// PATHからコマンドを探す
+fn find_in_path(command: &str, path_var: Option<&std::ffi::OsStr>) -> Option<PathBuf> {
+    std::env::split_paths(path_var?)
+        .map(|dir| dir.join(command))
+        .find(|candidate| is_executable(candidate))
+}
+
+fn is_executable(path: &Path) -> bool {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        path.metadata()
+            .is_ok_and(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+    }
+    #[cfg(not(unix))]
+    {
+        path.is_file()
+    }
+}
+
+/// 設定と実行環境から、実際に試す送信手段を順に決める
+fn plan_drivers(
+    config: &PasteConfig,
+    platform: Platform,
+    is_available: impl Fn(PasteDriver) -> bool,
+) -> Vec<PasteDriver> {
+    let candidates = match config.driver {
+        Some(driver) => vec![driver],
+        None => platform.driver_order(),
+    };
+    candidates
+        .into_iter()
+        .filter(|driver| driver.supports(config.method) && is_available(*driver))
+        .collect()
+}
+
+fn is_driver_available(driver: PasteDriver) -> bool {
+    match driver.helper_command() {
+        Some(command) => find_in_path(command, std::env::var_os("PATH").as_deref()).is_some(),
+        None => true,
+    }
+}
+
+/// 外部ヘルパーに渡す引数（`type_text`の本文は標準入力で渡す）
+fn helper_args(driver: PasteDriver, method: PasteMethod) -> Vec<&'static str> {
+    use PasteMethod::*;
+    match (driver, method) {
+        (PasteDriver::AppleScript, _) => vec![
+            "-e",
+            r#"tell application "System Events" to keystroke "v" using command down"#,
+        ],
+        (PasteDriver::Wtype, Keystroke) => vec!["-M", "ctrl", "v", "-m", "ctrl"],
+        (PasteDriver::Wtype, TerminalKeystroke) => vec!["-M", "ctrl", "-M", "shift", "v", "-m", "shift", "-m", "ctrl"],
+        (PasteDriver::Wtype, TypeText) => vec!["-"],
+        (PasteDriver::Xdotool, Keystroke) => vec!["key", "--clearmodifiers", "ctrl+v"],
+        (PasteDriver::Xdotool, TerminalKeystroke) => vec!["key", "--clearmodifiers", "ctrl+shift+v"],
+        (PasteDriver::Xdotool, TypeText) => vec!["type", "--clearmodifiers", "--file", "-"],
+        // Linuxのキーコード: 29=左Ctrl, 42=左Shift, 47=V
+        (PasteDriver::Ydotool, Keystroke) => vec!["key", "29:1", "47:1", "47:0", "29:0"],
+        (PasteDriver::Ydotool, TerminalKeystroke) => vec!["key", "29:1", "42:1", "47:1", "47:0", "42:0", "29:0"],
+        (PasteDriver::Ydotool, TypeText) => vec!["type", "--file", "-"],
+        (PasteDriver::Native, _) => vec![],
+    }
+}
+
+/// 選択範囲のコピーで外部ヘルパーに渡す引数
+fn copy_helper_args(driver: PasteDriver, terminal: bool) -> Vec<&'static str> {
+    match (driver, terminal) {
+        (PasteDriver::AppleScript, _) => vec![
+            "-e",
+            r#"tell application "System Events" to keystroke "c" using command down"#,
+        ],
+        (PasteDriver::Wtype, false) => vec!["-M", "ctrl", "c", "-m", "ctrl"],
+        (PasteDriver::Wtype, true) => vec!["-M", "ctrl", "-M", "shift", "c", "-m", "shift", "-m", "ctrl"],
+        (PasteDriver::Xdotool, false) => vec!["key", "--clearmodifiers", "ctrl+c"],
+        (PasteDriver::Xdotool, true) => vec!["key", "--clearmodifiers", "ctrl+shift+c"],
+        // Linuxのキーコード: 29=左Ctrl, 42=左Shift, 46=C
+        (PasteDriver::Ydotool, false) => vec!["key", "29:1", "46:1", "46:0", "29:0"],
+        (PasteDriver::Ydotool, true) => vec!["key", "29:1", "42:1", "46:1", "46:0", "42:0", "29:0"],
+        (PasteDriver::Native, _) => vec![],
+    }
+}
+
+/// 外部ヘルパーを実行（`input`は標準入力で渡す）
+fn run_helper(driver: PasteDriver, args: &[&str], input: Option<&str>) -> Result<(), String> {
+    let command = driver.helper_command().ok_or("Not an external helper")?;
+    let mut child = Command::new(command)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to run {command}: {e}"))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        if let Some(input) = input {
+            stdin
+                .write_all(input.as_bytes())
+                .map_err(|e| format!("Failed to send text to {command}: {e}"))?;
+        }
+    }
+    let output = child
+        .wait_with_output()
+        .map_err(|e| format!("Failed to run {command}: {e}"))?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(format!(
+            "{command} failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
+
+/// CGEvent（macOS）またはenigoで修飾キー付きのキーを送信
+///
+/// macOSはCmd+キー、それ以外はCtrl+キー（`terminal`の場合はCtrl+Shift+キー）
+fn run_native_keystroke(key: char, terminal: bool) -> Result<(), String> {
+    #[cfg(target_os = "macos")]
+    {
+        use core_graphics::event::{CGEvent, CGEventFlags, CGEventTapLocation};
+        use core_graphics::event_source::{CGEventSource, CGEventSourceStateID};
+
+        let _ = terminal;
+        // キーコード: 8=C, 9=V
+        let keycode = match key {
+            'c' => 8,
+            'v' => 9,
+            _ => return Err(format!("Unsupported key: {key}")),
+        };
+
+        // CGEventで直接キーイベントを送信（標準Cmd+Vと同じ、ターミナルもCmd+V）
+        let source = CGEventSource::new(CGEventSourceStateID::HIDSystemState)
+            .map_err(|()| "Failed to create event source".to_string())?;
+
+        let key_down = CGEvent::new_keyboard_event(source.clone(), keycode, true)
+            .map_err(|()| "Failed to create key down event".to_string())?;
+        let key_up = CGEvent::new_keyboard_event(source, keycode, false)
+            .map_err(|()| "Failed to create key up event".to_string())?;
+
+        key_down.set_flags(CGEventFlags::CGEventFlagCommand);
+        key_up.set_flags(CGEventFlags::CGEventFlagCommand);
+
+        key_down.post(CGEventTapLocation::HID);
+        key_up.post(CGEventTapLocation::HID);
+        Ok(())
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    {
+        use enigo::{Direction, Enigo, Key, Keyboard, Settings};
+
+        let mut enigo = Enigo::new(&Settings::default()).map_err(|e| format!("Failed to create Enigo: {e}"))?;
+        let modifiers: &[Key] = if terminal { &[Key::Control, Key::Shift] } else { &[Key::Control] };
+        for modifier in modifiers {
+            enigo
+                .key(*modifier, Direction::Press)
+                .map_err(|e| format!("Failed to press {modifier:?}: {e}"))?;
+        }
+        let clicked = enigo
+            .key(Key::Unicode(key), Direction::Click)
+            .map_err(|e| format!("Failed to click {}: {e}", key.to_ascii_uppercase()));
+        for modifier in modifiers.iter().rev() {
+            let _ = enigo.key(*modifier, Direction::Release);
+        }
+        clicked
+    }
+}
+
+/// 送信の失敗
+#[derive(Debug)]
+enum SendError {
+    /// 何も送信していない（次の送信手段を試せる）
+    NotSent(String),
+    /// 途中まで送信した可能性がある（次の送信手段を試すと二重に入力される）
+    Partial(String),
+}
+
+impl SendError {
+    fn message(&self) -> &str {
+        match self {
+            Self::NotSent(message) | Self::Partial(message) => message,
+        }
+    }
+}
+
+/// キーを送信・本文を入力（ネイティブ）
+fn run_native(method: PasteMethod, text: &str) -> Result<(), SendError> {
+    match method {
+        PasteMethod::TypeText => {
+            use enigo::{Direction, Enigo, Key, Keyboard, Settings};
+
+            let mut enigo =
+                Enigo::new(&Settings::default()).map_err(|e| SendError::NotSent(format!("Failed to create Enigo: {e}")))?;
+            // 離し損ねた修飾キーが入力と組み合わさらないよう、明示的に離す
+            for modifier in [Key::Control, Key::Shift, Key::Alt, Key::Meta] {
+                let _ = enigo.key(modifier, Direction::Release);
+            }
+            enigo
+                .text(text)
+                .map_err(|e| SendError::Partial(format!("Failed to type text: {e}")))
+        }
+        PasteMethod::Keystroke => run_native_keystroke('v', false).map_err(SendError::NotSent),
+        PasteMethod::TerminalKeystroke => run_native_keystroke('v', true).map_err(SendError::NotSent),
+    }
+}
+
+fn run_driver(driver: PasteDriver, method: PasteMethod, text: &str) -> Result<(), SendError> {
+    let args = helper_args(driver, method);
+    match (driver, method) {
+        (PasteDriver::Native, _) => run_native(method, text),
+        (_, PasteMethod::TypeText) => {
+            // 空の入力で実行し、本文を送る前に使えるか（接続できるか）確かめる
+            run_helper(driver, &args, Some("")).map_err(SendError::NotSent)?;
+            run_helper(driver, &args, Some(text)).map_err(SendError::Partial)
+        }
+        _ => run_helper(driver, &args, None).map_err(SendError::NotSent),
+    }
+}
+
+fn run_copy(driver: PasteDriver, terminal: bool) -> Result<(), String> {
+    match driver {
+        PasteDriver::Native => run_native_keystroke('c', terminal),
+        _ => run_helper(driver, &copy_helper_args(driver, terminal), None),
+    }
+}
+
+/// 順に試し、最初に成功した送信手段を返す
+///
+/// 途中まで送信した可能性がある失敗の場合は、次の送信手段を試さずに終了する
+fn send_with_fallback(
+    drivers: &[PasteDriver],
+    send: impl Fn(PasteDriver) -> Result<(), SendError>,
+) -> Result<PasteDriver, String> {
+    let mut errors = Vec::new();
+    for &driver in drivers {
+        match send(driver) {
+            Ok(()) => return Ok(driver),
+            Err(e) => {
+                errors.push(format!("{driver:?}: {}", e.message()));
+                if matches!(e, SendError::Partial(_)) {
+                    break;
+                }
+            }
+        }
+    }
+    if errors.is_empty() {
+        Err("No paste backend is available".to_string())
+    } else {
+        Err(errors.join("; "))
+    }
+}
+
+/// プロンプトを設定した方式でアクティブアプリケーションに送る
+///
+/// 送信できなかった場合もクリップボードにはプロンプトを残す（手動でペーストできるように）
+/// キー送信・外部ヘルパーの完了を待つため、非同期処理からは`spawn_blocking`で呼ぶ
+pub fn paste_prompt(app_handle: &AppHandle, text: &str, sensitive: bool) -> Result<PasteDriver, String> {
+    let config = crate::settings::load_or_default().paste;
+    let drivers = plan_drivers(&config, Platform::current(), is_driver_available);
+
+    if config.method.uses_clipboard() {
+        crate::clipboard::begin_paste(app_handle, text, sensitive)?;
+        let result = send_with_fallback(&drivers, |driver| run_driver(driver, config.method, text));
+        crate::clipboard::finish_paste(app_handle, result.is_ok());
+        return result;
+    }
+
+    std::thread::sleep(KEY_RELEASE_DELAY);
+    let result = send_with_fallback(&drivers, |driver| run_driver(driver, config.method, text));
+    if result.is_err() {
+        crate::clipboard::write_prompt_text(app_handle, text, sensitive)?;
+    }
+    result
+}
+
+/// 選択範囲をコピーするキーストロークを送る（選択範囲の取り込み用）
+///
+/// Ctrl+C（ペースト方式が`terminal_keystroke`の場合はCtrl+Shift+C、macOSはCmd+C）。
+/// 本文を入力する方式を設定していても、キーを送信できる手段を使う
+pub fn send_copy_keystroke() -> Result<PasteDriver, String> {
+    let config = crate::settings::load_or_default().paste;
+    let terminal = config.method == PasteMethod::TerminalKeystroke;
+    let keystroke = PasteConfig { method: PasteMethod::Keystroke, ..config };
+    let drivers = plan_drivers(&keystroke, Platform::current(), is_driver_available);
+    send_with_fallback(&drivers, |driver| run_copy(driver, terminal).map_err(SendError::NotSent))
+}
+
+/// ペースト設定を取得
+#[tauri::command]
+pub async fn get_paste_config() -> Result<PasteConfig, String> {
+    crate::settings::load().map(|settings| settings.paste)
+}
+
+/// ペースト設定を保存
+#[tauri::command]
+pub async fn set_paste_config(app_handle: AppHandle, config: PasteConfig) -> Result<(), String> {
+    crate::settings::update_with(&app_handle, |settings| {
+        settings.paste = config;
+        Ok(())
+    })
+    .map(|_| ())
+}
+
+/// 現在の設定で試す送信手段と利用可否を取得
+#[tauri::command]
+pub async fn get_paste_backends() -> Result<serde_json::Value, String> {
+    let config = crate::settings::load()?.paste;
+    let platform = Platform::current();
+    let order = plan_drivers(&config, platform, |_| true);
+    let backends: Vec<_> = order
+        .into_iter()
+        .map(|driver| {
+            serde_json::json!({
+                "driver": driver,
+                "available": is_driver_available(driver),
+            })
+        })
+        .collect();
+    Ok(serde_json::json!({
+        "method": config.method,
+        "backends": backends,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_driver_order_depends_on_session() {
+        use PasteDriver::*;
+        let config = PasteConfig::default();
+        let all = |_: PasteDriver| true;
+
+        assert_eq!(plan_drivers(&config, Platform::MacOs, all), vec![Native, AppleScript]);
+        assert_eq!(
+            plan_drivers(&config, Platform::Unix { wayland: true, x11: true }, all),
+            vec![Wtype, Ydotool, Native]
+        );
+        assert_eq!(
+            plan_drivers(&config, Platform::Unix { wayland: true, x11: false }, all),
+            vec![Wtype, Ydotool]
+        );
+        assert_eq!(
+            plan_drivers(&config, Platform::Unix { wayland: false, x11: true }, all),
+            vec![Native, Xdotool, Ydotool]
+        );
+
+        // 見つからないヘルパーは飛ばす
+        let without_wtype = |driver: PasteDriver| driver != Wtype;
+        assert_eq!(
+            plan_drivers(&config, Platform::Unix { wayland: true, x11: true }, without_wtype),
+            vec![Ydotool, Native]
+        );
+
+        // AppleScriptは直接入力に使えない
+        let typing = PasteConfig { method: PasteMethod::TypeText, ..PasteConfig::default() };
+        assert_eq!(plan_drivers(&typing, Platform::MacOs, all), vec![Native]);
+
+        // 固定した送信手段のみ使う
+        let forced = PasteConfig { driver: Some(Xdotool), ..PasteConfig::default() };
+        assert_eq!(plan_drivers(&forced, Platform::Unix { wayland: true, x11: true }, all), vec![Xdotool]);
+        let invalid = PasteConfig { method: PasteMethod::TypeText, driver: Some(AppleScript), ..PasteConfig::default() };
+        assert!(invalid.validate().is_err());
+    }
+
+    #[test]
+    fn test_paste_confirmation_threshold() {
+        let config = PasteConfig::default();
+        let large = "あ".repeat(DEFAULT_CONFIRM_THRESHOLD + 1);
+        assert!(config.needs_confirmation(&large, PasteConfirmation::Default));
+        assert!(!config.needs_confirmation("short", PasteConfirmation::Default));
+        // プロンプトごとの設定はしきい値より優先
+        assert!(!config.needs_confirmation(&large, PasteConfirmation::Never));
+        assert!(config.needs_confirmation("short", PasteConfirmation::Always));
+
+        let disabled = PasteConfig { confirm_threshold: None, ..PasteConfig::default() };
+        assert!(!disabled.needs_confirmation(&large, PasteConfirmation::Default));
+        assert!(PasteConfig { confirm_threshold: Some(10), ..PasteConfig::default() }.validate().is_err());
+
+        // 既存の設定ファイルにない場合はデフォルト
+        let saved: PasteConfig = serde_json::from_str(r#"{"method":"keystroke"}"#).unwrap();
+        assert_eq!(saved.confirm_threshold, Some(DEFAULT_CONFIRM_THRESHOLD));
+    }
+
+    #[test]
+    fn test_helper_args_cover_every_method() {
+        use PasteDriver::*;
+        for driver in [AppleScript, Wtype, Xdotool, Ydotool] {
+            for method in [PasteMethod::Keystroke, PasteMethod::TerminalKeystroke, PasteMethod::TypeText] {
+                assert!(!helper_args(driver, method).is_empty());
+            }
+        }
+        assert_eq!(helper_args(Xdotool, PasteMethod::TerminalKeystroke), vec!["key", "--clearmodifiers", "ctrl+shift+v"]);
+        assert_eq!(helper_args(Wtype, PasteMethod::TypeText), vec!["-"]);
+        for driver in [AppleScript, Wtype, Xdotool, Ydotool] {
+            assert!(!copy_helper_args(driver, false).is_empty());
+        }
+        assert_eq!(copy_helper_args(Xdotool, true), vec!["key", "--clearmodifiers", "ctrl+shift+c"]);
+    }
+
+    #[test]
+    fn test_fallback_stops_after_partial_send() {
+        use PasteDriver::*;
+        let tried = std::cell::RefCell::new(Vec::new());
+        let send = |failure: fn(String) -> SendError| {
+            let tried = &tried;
+            move |driver: PasteDriver| {
+                tried.borrow_mut().push(driver);
+                if driver == Native {
+                    Ok(())
+                } else {
+                    Err(failure(format!("{driver:?} failed")))
+                }
+            }
+        };
+
+        // 何も送っていなければ次の送信手段を試す
+        assert_eq!(send_with_fallback(&[Wtype, Ydotool, Native], send(SendError::NotSent)), Ok(Native));
+        assert_eq!(*tried.borrow(), vec![Wtype, Ydotool, Native]);
+
+        // 途中まで入力した可能性があれば二重に入力しない
+        tried.borrow_mut().clear();
+        assert!(send_with_fallback(&[Wtype, Ydotool, Native], send(SendError::Partial)).is_err());
+        assert_eq!(*tried.borrow(), vec![Wtype]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_find_in_path_requires_executable() {
+        use std::os::unix::fs::PermissionsExt;
+        let dir = std::env::temp_dir().join(format!("prompalette-paste-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let helper = dir.join("wtype");
+        std::fs::write(&helper, "#!/bin/sh\n").unwrap();
+
+        let path_var = std::env::join_paths([dir.clone()]).unwrap();
+        assert_eq!(find_in_path("wtype", Some(&path_var)), None);
+        std::fs::set_permissions(&helper, std::fs::Permissions::from_mode(0o755)).unwrap();
+        assert_eq!(find_in_path("wtype", Some(&path_var)), Some(helper));
+        assert_eq!(find_in_path("xdotool", Some(&path_var)), None);
+        assert_eq!(find_in_path("wtype", None), None);
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
@@ -0,0 +1,161 @@
+/*!
+ * 大きなプロンプトのペースト前の確認
+ *
+ * ホットキーでのペーストはフォーカス中のウィンドウにそのまま入力されるため、
+ * ペースト設定の`confirm_threshold`を超えるプロンプト（またはプロンプトの`paste_confirmation`が`always`）は
+ * 送信前にユーザーの承認を待つ
+ *
+ * 1. `paste-confirmation-required`を通知し、メインウィンドウを前面に表示（確認ダイアログはフロントエンド）
+ * 2. `confirm_paste`コマンドで承認・拒否されるまで待つ（一定時間で取り消し）
+ * 3. `paste-confirmation-closed`を通知する。承認された場合はメインウィンドウを隠して
+ *    元のアプリケーションにフォーカスを戻してからペーストする（拒否された場合は表示したウィンドウのみ隠す）
+ */
+use serde::Serialize;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::sync::oneshot;
+
+/// 承認を待つ時間（過ぎたらペーストしない）
+const CONFIRM_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// メインウィンドウを隠してから元のアプリケーションにフォーカスが戻るまで待つ時間
+const FOCUS_RETURN_DELAY: Duration = Duration::from_millis(150);
+
+/// 確認の依頼（`paste-confirmation-required`で通知）
+#[derive(Debug, Clone, Serialize)]
+pub struct PasteConfirmationRequest {
+    pub request_id: String,
+    /// パレット位置（パレットホットキーでペーストする場合）
+    pub position: Option<u8>,
+    /// ペースト元（`palette` / `repeat_last` / `cycle_recent`）
+    pub source: &'static str,
+    pub prompt_id: String,
+    /// 本文の文字数（内容は含まない）
+    pub chars: usize,
+    pub threshold: Option<usize>,
+}
+
+/// 承認待ちの確認（ペーストは1つずつ処理するため1件のみ）
+static PENDING: Mutex<Option<(String, oneshot::Sender<bool>)>> = Mutex::new(None);
+
+/// 承認待ちとして登録（前の確認が残っていれば拒否として扱う）
+fn register_pending(request_id: &str) -> oneshot::Receiver<bool> {
+    let (sender, receiver) = oneshot::channel();
+    if let Ok(mut pending) = PENDING.lock() {
+        *pending = Some((request_id.to_string(), sender));
+    }
+    receiver
+}
+
+/// 承認待ちの確認に応答
+fn resolve_pending(request_id: &str, approved: bool) -> Result<(), String> {
+    let mut pending = PENDING.lock().map_err(|_| "Paste confirmation state is poisoned".to_string())?;
+    match pending.take() {
+        Some((id, sender)) if id == request_id => {
+            let _ = sender.send(approved);
+            Ok(())
+        }
+        other => {
+            *pending = other;
+            Err("No pending paste confirmation".to_string())
+        }
+    }
+}
+
+/// 承認待ちの確認があるかどうか
+fn has_pending() -> bool {
+    PENDING.lock().map(|pending| pending.is_some()).unwrap_or(false)
+}
+
+/// 確認を依頼し、承認された場合はtrue
+pub async fn request_confirmation(app_handle: &AppHandle, request: PasteConfirmationRequest) -> bool {
+    let request_id = request.request_id.clone();
+    let receiver = register_pending(&request_id);
+    let _ = app_handle.emit("paste-confirmation-required", &request);
+    let window_shown = show_main_window(app_handle);
+
+    let approved = matches!(tokio::time::timeout(CONFIRM_TIMEOUT, receiver).await, Ok(Ok(true)));
+    // タイムアウトした場合の後始末（応答済みの場合は何もしない）
+    let _ = resolve_pending(&request_id, false);
+    let _ = app_handle.emit(
+        "paste-confirmation-closed",
+        serde_json::json!({ "request_id": request_id, "approved": approved }),
+    );
+
+    if approved {
+        // ペーストがメインウィンドウに入力されないよう、元から表示していた場合も隠す
+        return_focus(app_handle);
+        tokio::time::sleep(FOCUS_RETURN_DELAY).await;
+    } else if window_shown && !has_pending() {
+        // 次の確認ダイアログを表示中の場合は隠さない
+        return_focus(app_handle);
+    }
+    approved
+}
+
+/**
+ * 確認ダイアログのためにメインウィンドウを前面に表示
+ *
+ * 表示していなかった場合はtrue（既に表示していた場合も前面に出してフォーカスする）
+ */
+fn show_main_window(app_handle: &AppHandle) -> bool {
+    let Some(window) = app_handle.get_webview_window("main") else {
+        return false;
+    };
+    let was_visible = window.is_visible().unwrap_or(false) && !window.is_minimized().unwrap_or(false);
+    if let Err(e) = window.show() {
+        eprintln!("Failed to show window: {e}");
+        return false;
+    }
+    if let Err(e) = window.unminimize() {
+        eprintln!("Failed to unminimize window: {e}");
+    }
+    if let Err(e) = window.set_focus() {
+        eprintln!("Failed to focus window: {e}");
+    }
+    !was_visible
+}
+
+/// メインウィンドウを隠し、元のアプリケーションにフォーカスを戻す
+fn return_focus(app_handle: &AppHandle) {
+    if let Some(window) = app_handle.get_webview_window("main") {
+        if let Err(e) = window.hide() {
+            eprintln!("Failed to hide window: {e}");
+        }
+    }
+    // macOSはウィンドウを隠すだけではアプリがアクティブなままのため、アプリごと隠す
+    #[cfg(target_os = "macos")]
+    if let Err(e) = app_handle.hide() {
+        eprintln!("Failed to hide app: {e}");
+    }
+}
+
+/// ペーストの確認に応答（フロントエンドの確認ダイアログから呼ぶ）
+#[tauri::command]
+pub async fn confirm_paste(request_id: String, approved: bool) -> Result<(), String> {
+    resolve_pending(&request_id, approved)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_pending_matches_request() {
+        let mut receiver = register_pending("request-1");
+        assert!(resolve_pending("request-2", true).is_err());
+        assert!(receiver.try_recv().is_err());
+
+        resolve_pending("request-1", true).unwrap();
+        assert_eq!(receiver.try_recv(), Ok(true));
+        // 応答済みの確認には応答できない
+        assert!(resolve_pending("request-1", false).is_err());
+
+        // 新しい確認を登録すると前の確認は拒否として扱う
+        let mut stale = register_pending("request-3");
+        let _current = register_pending("request-4");
+        assert!(stale.try_recv().is_err());
+        assert!(resolve_pending("request-3", true).is_err());
+    }
+}
@@ -0,0 +1,424 @@
+/*!
+ * アプリケーション設定
+ *
+ * ユーザーが変更できる設定を1つのファイル（settings.json）にまとめて管理する
+ *
+ * - 型付き: 各機能の設定構造体をセクションとして持つ
+ * - バージョン管理: `version`が古い場合は読み込み時に順にマイグレーションして保存
+ *   - v0 → v1: 機能ごとの設定ファイル（clipboard.json、paste.json、palette_hotkeys.json）を取り込み
+ *     （settings.jsonがない、または`version`がない場合はv0とみなす）
+ *   - 新しいバージョンのアプリで保存された設定は読み込まない
+ * - 変更: 検証してから反映・保存し、フロントエンドに`settings-changed`を通知してトレイを更新
+ *
+ * 鍵やペアリング情報など設定以外の状態（encryption.json、vault.json、lan_sync.json）は対象外
+ */
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter};
+
+use crate::capture::CaptureConfig;
+use crate::clipboard::ClipboardConfig;
+use crate::environment::Environment;
+use crate::global_hotkey::PaletteHotkeyConfig;
+use crate::paste::PasteConfig;
+use crate::shortcuts::LauncherConfig;
+
+/// 設定ファイル名（アプリデータディレクトリに保存）
+const SETTINGS_FILENAME: &str = "settings.json";
+
+/// 現在の設定バージョン
+pub const SETTINGS_VERSION: u32 = 1;
+
+/// v0で使っていた機能ごとの設定ファイル（セクション名, ファイル名）
+const LEGACY_FILES: &[(&str, &str)] = &[
+    ("clipboard", "clipboard.json"),
+    ("paste", "paste.json"),
+    ("palette", "palette_hotkeys.json"),
+];
+
+/// 設定の変更を直列化するロック
+static UPDATE_LOCK: Mutex<()> = Mutex::new(());
+
+/// アプリケーション設定
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AppSettings {
+    pub version: u32,
+    /// クイックランチャーのショートカット
+    pub launcher: LauncherConfig,
+    /// 選択範囲の取り込みホットキー
+    pub capture: CaptureConfig,
+    /// パレットホットキー
+    pub palette: PaletteHotkeyConfig,
+    /// クリップボード
+    pub clipboard: ClipboardConfig,
+    /// ペースト方式
+    pub paste: PasteConfig,
+}
+
+impl Default for AppSettings {
+    fn default() -> Self {
+        Self {
+            version: SETTINGS_VERSION,
+            launcher: LauncherConfig::default(),
+            capture: CaptureConfig::default(),
+            palette: PaletteHotkeyConfig::default(),
+            clipboard: ClipboardConfig::default(),
+            paste: PasteConfig::default(),
+        }
+    }
+}
+
+impl AppSettings {
+    /// 全セクションを検証
+    pub fn validate(&self) -> Result<(), String> {
+        self.launcher.validate()?;
+        self.capture.validate()?;
+        self.palette.validate()?;
+        self.validate_global_bindings()?;
+        self.clipboard.validate()?;
+        self.paste.validate()
+    }
+
+    /// 登録するグローバルショートカット（所有者ID, ショートカット）
+    fn global_bindings(&self) -> Vec<(String, String)> {
+        let mut bindings = vec![("launcher".to_string(), self.launcher.shortcut.clone())];
+        if let Some(shortcut) = self.capture.binding() {
+            bindings.push((crate::capture::CAPTURE_OWNER.to_string(), shortcut.to_string()));
+        }
+        bindings.extend(
+            self.palette
+                .bindings()
+                .into_iter()
+                .map(|entry| (crate::global_hotkey::palette_owner_id(entry.position), entry.hotkey)),
+        );
+        bindings
+    }
+
+    /// ランチャー・取り込み・パレットホットキーの間の重複
+    fn validate_global_bindings(&self) -> Result<(), String> {
+        let mut owners: HashMap<u32, String> = HashMap::new();
+        for (owner, shortcut) in self.global_bindings() {
+            let id = crate::shortcuts::parse_shortcut(&shortcut)?.id();
+            if let Some(other) = owners.insert(id, owner.clone()) {
+                return Err(format!("Shortcut '{shortcut}' is used by both {other} and {owner}"));
+            }
+        }
+        Ok(())
+    }
+
+    /// 変更されたセクション名
+    fn changed_sections(&self, other: &Self) -> Vec<&'static str> {
+        let mut changed = Vec::new();
+        if self.launcher != other.launcher {
+            changed.push("launcher");
+        }
+        if self.capture != other.capture {
+            changed.push("capture");
+        }
+        if self.palette != other.palette {
+            changed.push("palette");
+        }
+        if self.clipboard != other.clipboard {
+            changed.push("clipboard");
+        }
+        if self.paste != other.paste {
+            changed.push("paste");
+        }
+        changed
+    }
+}
+
+fn data_dir() -> Result<PathBuf, String> {
+    Environment::current().data_dir().map_err(|e| e.to_string())
+}
+
+/// 設定を読み込む（存在しない・古い場合はマイグレーションして保存）
+pub fn load() -> Result<AppSettings, String> {
+    let dir = data_dir()?;
+    let path = dir.join(SETTINGS_FILENAME);
+    let value = if path.exists() {
+        let content = std::fs::read_to_string(&path).map_err(|e| e.to_string())?;
+        serde_json::from_str(&content).map_err(|e| format!("Invalid settings file: {e}"))?
+    } else {
+        serde_json::json!({ "version": 0 })
+    };
+
+    let version = settings_version(&value);
+    let settings: AppSettings = serde_json::from_value(migrate(value, &dir)?)
+        .map_err(|e| format!("Invalid settings file: {e}"))?;
+    if version < SETTINGS_VERSION {
+        // 保存できない場合も今回は移行後の設定を使い、旧ファイルは残す
+        match save(&settings) {
+            Ok(()) => remove_legacy_files(&dir),
+            Err(e) => eprintln!("Failed to save migrated settings: {e}"),
+        }
+    }
+    Ok(settings)
+}
+
+/// 設定のバージョン（ない場合はv0）
+fn settings_version(value: &serde_json::Value) -> u32 {
+    value
+        .get("version")
+        .and_then(serde_json::Value::as_u64)
+        .map_or(0, |version| u32::try_from(version).unwrap_or(u32::MAX))
+}
+
+/// 古いバージョンの設定を現在のバージョンまで順に変換（新しいバージョンの設定はエラー）
+fn migrate(mut value: serde_json::Value, legacy_dir: &Path) -> Result<serde_json::Value, String> {
+    let mut version = settings_version(&value);
+    if version > SETTINGS_VERSION {
+        return Err(format!(
+            "Settings were saved by a newer version of the app (version {version})"
+        ));
+    }
+    let object = value.as_object_mut().ok_or("Settings must be a JSON object")?;
+
+    while version < SETTINGS_VERSION {
+        match version {
+            0 => import_legacy_files(object, legacy_dir),
+            _ => unreachable!("no migration from settings version {version}"),
+        }
+        version += 1;
+        object.insert("version".to_string(), version.into());
+    }
+    Ok(value)
+}
+
+/// v0 → v1: 機能ごとの設定ファイルをセクションとして取り込む（読めないファイルはデフォルト）
+fn import_legacy_files(object: &mut serde_json::Map<String, serde_json::Value>, legacy_dir: &Path) {
+    for (section, filename) in LEGACY_FILES {
+        let path = legacy_dir.join(filename);
+        if object.contains_key(*section) || !path.exists() {
+            continue;
+        }
+        let legacy = std::fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|content| serde_json::from_str(&content).map_err(|e| e.to_string()));
+        match legacy {
+            Ok(legacy) => {
+                object.insert(section.to_string(), legacy);
+            }
+            Err(e) => eprintln!("Ignoring invalid legacy settings file {filename}: {e}"),
+        }
+    }
+}
+
+fn remove_legacy_files(dir: &Path) {
+    for (_, filename) in LEGACY_FILES {
+        let path = dir.join(filename);
+        if path.exists() {
+            if let Err(e) = std::fs::remove_file(&path) {
+                eprintln!("Failed to remove legacy settings file {filename}: {e}");
+            }
+        }
+    }
+}
+
+/// 読み込みに失敗した場合はデフォルト
+pub fn load_or_default() -> AppSettings {
+    load().unwrap_or_else(|e| {
+        eprintln!("Failed to load settings: {e}");
+        AppSettings::default()
+    })
+}
+
+fn save(settings: &AppSettings) -> Result<(), String> {
+    let path = data_dir()?.join(SETTINGS_FILENAME);
+    let content = serde_json::to_string_pretty(settings).map_err(|e| e.to_string())?;
+    // 書き込み途中で終了しても壊れないよう、一時ファイルに書いてから置き換える
+    let tmp_path = path.with_extension("json.tmp");
+    std::fs::write(&tmp_path, content).map_err(|e| format!("Failed to save settings: {e}"))?;
+    std::fs::rename(&tmp_path, &path).map_err(|e| format!("Failed to save settings: {e}"))
+}
+
+/// JSONオブジェクトを再帰的にマージ（オブジェクト以外の値は置き換え）
+fn merge(target: &mut serde_json::Value, patch: serde_json::Value) {
+    match (target, patch) {
+        (serde_json::Value::Object(target), serde_json::Value::Object(patch)) => {
+            for (key, value) in patch {
+                merge(target.entry(key).or_insert(serde_json::Value::Null), value);
+            }
+        }
+        (target, patch) => *target = patch,
+    }
+}
+
+/// 設定を変更して反映・保存
+///
+/// ショートカットが変わった場合は再登録し、保存に失敗した場合は元に戻す
+pub fn update_with(
+    app_handle: &AppHandle,
+    change: impl FnOnce(&mut AppSettings) -> Result<(), String>,
+) -> Result<AppSettings, String> {
+    let _guard = UPDATE_LOCK.lock().map_err(|_| "Settings lock is poisoned".to_string())?;
+    let current = load()?;
+    let mut next = current.clone();
+    change(&mut next)?;
+    next.version = SETTINGS_VERSION;
+    next.validate()?;
+
+    let changed = current.changed_sections(&next);
+    if changed.is_empty() {
+        return Ok(next);
+    }
+
+    apply_shortcuts(app_handle, &current, &next, &changed)?;
+    if let Err(e) = save(&next) {
+        if let Err(restore_err) = apply_shortcuts(app_handle, &next, &current, &changed) {
+            eprintln!("Failed to restore shortcuts: {restore_err}");
+        }
+        return Err(e);
+    }
+
+    let _ = app_handle.emit(
+        "settings-changed",
+        serde_json::json!({
+            "settings": &next,
+            "changed": changed,
+        }),
+    );
+    crate::tray::refresh_tray(app_handle);
+    Ok(next)
+}
+
+/// ショートカットを持つセクションと、その設定を登録する処理（登録順）
+type ApplyShortcut = fn(&AppHandle, &AppSettings) -> Result<(), String>;
+const SHORTCUT_SECTIONS: &[(&str, ApplyShortcut)] = &[
+    ("launcher", |app_handle, settings| {
+        crate::shortcuts::apply_launcher_shortcut(app_handle, &settings.launcher.shortcut)
+    }),
+    ("capture", |app_handle, settings| {
+        crate::capture::apply_capture_shortcut(app_handle, &settings.capture)
+    }),
+    ("palette", |app_handle, settings| {
+        crate::global_hotkey::apply_palette_hotkeys(app_handle, &settings.palette.bindings())
+    }),
+];
+
+/// 変更されたショートカットを登録し直す（失敗した場合は変更前に戻す）
+///
+/// ホットキーの一時停止中は登録せず、再開時に保存済みの設定で登録する
+fn apply_shortcuts(
+    app_handle: &AppHandle,
+    from: &AppSettings,
+    to: &AppSettings,
+    changed: &[&str],
+) -> Result<(), String> {
+    if crate::shortcuts::is_paused() {
+        return Ok(());
+    }
+    let mut applied: Vec<(&str, ApplyShortcut)> = Vec::new();
+    for &(section, apply) in SHORTCUT_SECTIONS {
+        if !changed.contains(&section) {
+            continue;
+        }
+        if let Err(e) = apply(app_handle, to) {
+            for (section, restore) in applied.into_iter().rev() {
+                if let Err(restore_err) = restore(app_handle, from) {
+                    eprintln!("Failed to restore {section} shortcut: {restore_err}");
+                }
+            }
+            return Err(e);
+        }
+        applied.push((section, apply));
+    }
+    Ok(())
+}
+
+/// 設定を取得
+#[tauri::command]
+pub async fn get_settings() -> Result<AppSettings, String> {
+    load()
+}
+
+/// 設定を部分的に更新（指定したキーのみ変更）
+///
+/// 例: `{ "clipboard": { "restore_after_paste": false } }`
+#[tauri::command]
+pub async fn update_settings(app_handle: AppHandle, patch: serde_json::Value) -> Result<AppSettings, String> {
+    if !patch.is_object() {
+        return Err("Settings patch must be a JSON object".to_string());
+    }
+    update_with(&app_handle, |settings| {
+        let mut value = serde_json::to_value(&*settings).map_err(|e| e.to_string())?;
+        merge(&mut value, patch);
+        *settings = serde_json::from_value(value).map_err(|e| format!("Invalid settings: {e}"))?;
+        Ok(())
+    })
+}
+
+/// 設定をデフォルトに戻す
+#[tauri::command]
+pub async fn reset_settings(app_handle: AppHandle) -> Result<AppSettings, String> {
+    update_with(&app_handle, |settings| {
+        *settings = AppSettings::default();
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_migrate_from_legacy_files() {
+        let dir = std::env::temp_dir().join(format!("prompalette-settings-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("clipboard.json"), r#"{"restore_after_paste":false}"#).unwrap();
+        std::fs::write(dir.join("paste.json"), r#"{"method":"type_text"}"#).unwrap();
+        std::fs::write(dir.join("palette_hotkeys.json"), "not json").unwrap();
+
+        // versionがない設定はv0として移行する
+        let migrated = migrate(serde_json::json!({}), &dir).unwrap();
+        let settings: AppSettings = serde_json::from_value(migrated).unwrap();
+        assert_eq!(settings.version, SETTINGS_VERSION);
+        assert!(!settings.clipboard.restore_after_paste);
+        assert_eq!(settings.clipboard.restore_delay_ms, ClipboardConfig::default().restore_delay_ms);
+        assert_eq!(settings.paste.method, crate::paste::PasteMethod::TypeText);
+        // 読めないファイルはデフォルト
+        assert_eq!(settings.palette, PaletteHotkeyConfig::default());
+
+        // 現在のバージョンの設定は旧ファイルを取り込まない
+        let current = migrate(serde_json::json!({ "version": SETTINGS_VERSION }), &dir).unwrap();
+        let settings: AppSettings = serde_json::from_value(current).unwrap();
+        assert!(settings.clipboard.restore_after_paste);
+
+        assert!(migrate(serde_json::json!({ "version": SETTINGS_VERSION + 1 }), &dir).is_err());
+
+        remove_legacy_files(&dir);
+        assert!(!dir.join("clipboard.json").exists());
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_merge_patch_and_validation() {
+        let mut value = serde_json::to_value(AppSettings::default()).unwrap();
+        merge(&mut value, serde_json::json!({ "clipboard": { "restore_delay_ms": 50 } }));
+        let patched: AppSettings = serde_json::from_value(value).unwrap();
+
+        // 指定していないキーはそのまま
+        assert!(patched.clipboard.restore_after_paste);
+        assert_eq!(patched.changed_sections(&AppSettings::default()), vec!["clipboard"]);
+        assert!(patched.validate().is_err());
+        assert!(AppSettings::default().validate().is_ok());
+
+        // ランチャーとパレットホットキーの重複
+        let mut conflicting = AppSettings::default();
+        conflicting.launcher.shortcut = "Control+CommandOrControl+5".to_string();
+        assert!(conflicting.validate().is_err());
+        conflicting.launcher.shortcut = "Alt+Space".to_string();
+        assert!(conflicting.validate().is_ok());
+        conflicting.launcher.shortcut = "Space".to_string();
+        assert!(conflicting.validate().is_err());
+
+        // 取り込みホットキーとの重複（無効にした場合は対象外）
+        conflicting.launcher.shortcut = crate::capture::CaptureConfig::default().shortcut;
+        assert!(conflicting.validate().is_err());
+        conflicting.capture.enabled = false;
+        assert!(conflicting.validate().is_ok());
+    }
+}
@@ -2,14 +2,20 @@
  * グローバルショートカット管理モジュール
  * 
  * `PromPaletteのキーボードショートカット機能を提供`
- * - Ctrl+Shift+P: クイックランチャー表示
+ * - Ctrl+Shift+P（設定で変更可能）: クイックランチャー表示
+ * - Ctrl+Alt+Shift+C（設定で変更可能）: 選択範囲の取り込み（captureモジュール）
+ * - 一時停止: 画面共有やゲーム中などにアプリの全ショートカットを解除し、再開時に登録し直す
  * 
  * パフォーマンス目標:
  * - ホットキー検出から UI表示まで <200ms
  * - 確実な動作保証（全OS対応）
  */
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::{Mutex, OnceLock};
 use tauri::{AppHandle, Emitter, Manager};
-use tauri_plugin_global_shortcut::GlobalShortcutExt;
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
 
 use crate::config;
 
@@ -19,20 +25,120 @@ pub struct ShortcutError {
     pub error: String,
 }
 
+/// クイックランチャーのショートカット設定（アプリケーション設定の`launcher`セクション）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LauncherConfig {
+    pub shortcut: String,
+}
+
+impl Default for LauncherConfig {
+    fn default() -> Self {
+        Self { shortcut: config::shortcuts::QUICK_LAUNCHER.to_string() }
+    }
+}
+
+impl LauncherConfig {
+    pub(crate) fn validate(&self) -> Result<(), String> {
+        validate_global_shortcut(&self.shortcut)
+    }
+}
+
+pub(crate) fn parse_shortcut(shortcut: &str) -> Result<Shortcut, String> {
+    Shortcut::from_str(shortcut).map_err(|e| format!("Invalid shortcut '{shortcut}': {e}"))
+}
+
+/// グローバルに登録するショートカットを検証
+pub(crate) fn validate_global_shortcut(shortcut: &str) -> Result<(), String> {
+    // 修飾キーなしのショートカットは通常の入力を奪うため禁止
+    if parse_shortcut(shortcut)?.mods.is_empty() {
+        return Err(format!("Shortcut '{shortcut}' needs at least one modifier key"));
+    }
+    Ok(())
+}
+
+/// 現在登録中のクイックランチャーのショートカット
+static ACTIVE_LAUNCHER: Mutex<Option<String>> = Mutex::new(None);
+
 /**
  * グローバルショートカットを登録
  * 
  * アプリケーション起動時に呼び出され、以下のショートカットを登録:
- * - Ctrl+Shift+P (Cmd+Shift+P on macOS): クイックランチャー表示
+ * - 設定したショートカット（デフォルトはCtrl+Shift+P、macOSはCmd+Shift+P）: クイックランチャー表示
  * 
  * @param app Tauriアプリハンドル
  * @returns 登録成功時はOk(()), 失敗時はエラーメッセージ
  */
 #[tauri::command]
 pub async fn register_global_shortcuts(app: AppHandle) -> Result<(), ShortcutError> {
+    if is_paused() {
+        return Ok(()); // 再開時に登録する
+    }
+    let launcher = crate::settings::load_or_default().launcher;
+    apply_launcher_shortcut(&app, &launcher.shortcut).map_err(|error| {
+        eprintln!("{error}");
+        ShortcutError { error }
+    })
+}
+
+/**
+ * クイックランチャーのショートカットを差し替え
+ * 
+ * 現在のランチャーのショートカットのみ解除して新しいショートカットを登録する
+ * （パレットホットキーなど他のショートカットには触れない）。
+ * 登録に失敗した場合は以前のショートカットを登録し直す
+ */
+pub(crate) fn apply_launcher_shortcut(app: &AppHandle, shortcut: &str) -> Result<(), String> {
+    replace_shortcut(app, &ACTIVE_LAUNCHER, Some(shortcut), register_launcher_shortcut)
+}
+
+/**
+ * 単独のショートカット（ランチャー、取り込みなど）を差し替え
+ * 
+ * `active`に記録した現在のショートカットのみ解除し、`shortcut`を`register`で登録する
+ * （`None`の場合は解除のみ）。登録に失敗した場合は以前のショートカットを登録し直す
+ */
+pub(crate) fn replace_shortcut(
+    app: &AppHandle,
+    active: &Mutex<Option<String>>,
+    shortcut: Option<&str>,
+    register: fn(&AppHandle, &str) -> Result<(), String>,
+) -> Result<(), String> {
+    let mut active = active.lock().map_err(|_| "Shortcut state is poisoned".to_string())?;
+    let previous = active.take();
+    if let Some(previous) = &previous {
+        if let Err(e) = app.global_shortcut().unregister(previous.as_str()) {
+            eprintln!("Failed to unregister global shortcut '{previous}': {e}");
+        }
+    }
+    let Some(shortcut) = shortcut else {
+        return Ok(());
+    };
+
+    match register(app, shortcut) {
+        Ok(()) => {
+            *active = Some(shortcut.to_string());
+            Ok(())
+        }
+        Err(e) => {
+            if let Some(previous) = previous {
+                match register(app, &previous) {
+                    Ok(()) => *active = Some(previous),
+                    Err(restore_err) => eprintln!("Failed to restore global shortcut '{previous}': {restore_err}"),
+                }
+            }
+            Err(e)
+        }
+    }
+}
+
+fn register_launcher_shortcut(app: &AppHandle, shortcut: &str) -> Result<(), String> {
     // ショートカット登録とイベントハンドラーを同時に設定
-    app.global_shortcut()
-        .on_shortcut(config::shortcuts::QUICK_LAUNCHER, move |app_handle, _shortcut, _event| {
+    let result = app.global_shortcut()
+        .on_shortcut(shortcut, move |app_handle, _shortcut, event| {
+            // キーダウンのみ処理（キーアップで二重に表示しない）
+            if event.state == ShortcutState::Released {
+                return;
+            }
             // メインウィンドウを表示してフォーカス
             if let Some(window) = app_handle.get_webview_window("main") {
                 // ウィンドウを最前面に表示
@@ -52,30 +158,34 @@ pub async fn register_global_shortcuts(app: AppHandle) -> Result<(), ShortcutErr
                 }
             }
         })
-        .map_err(|e| {
-            eprintln!("Failed to register global shortcut '{}': {}", config::shortcuts::QUICK_LAUNCHER, e);
-            ShortcutError {
-                error: format!("ショートカットキー '{}' の登録に失敗しました: {}", config::shortcuts::QUICK_LAUNCHER, e)
-            }
-        })?;
-
-    Ok(())
+        .map_err(|e| format!("ショートカットキー '{shortcut}' の登録に失敗しました: {e}"));
+    record_registration("launcher", shortcut, &result);
+    result
 }
 
 /**
  * グローバルショートカットの登録解除
  * 
  * アプリケーション終了時やショートカット設定変更時に使用
+ * （クイックランチャーのショートカットのみ解除し、パレットホットキーは残す）
  * 
  * @param app Tauriアプリハンドル
  * @returns 解除成功時はOk(()), 失敗時はエラーメッセージ
  */
 #[tauri::command]
 pub async fn unregister_global_shortcuts(app: AppHandle) -> Result<(), ShortcutError> {
+    let shortcut = match ACTIVE_LAUNCHER.lock() {
+        Ok(mut active) => active.take(),
+        Err(_) => return Err(ShortcutError { error: "Launcher shortcut state is poisoned".to_string() }),
+    };
+    let Some(shortcut) = shortcut else {
+        return Ok(());
+    };
+
     app.global_shortcut()
-        .unregister_all()
+        .unregister(shortcut.as_str())
         .map_err(|e| {
-            eprintln!("Failed to unregister global shortcuts: {e}");
+            eprintln!("Failed to unregister global shortcut '{shortcut}': {e}");
             ShortcutError {
                 error: format!("ショートカットキーの解除に失敗しました: {e}")
             }
@@ -84,25 +194,328 @@ pub async fn unregister_global_shortcuts(app: AppHandle) -> Result<(), ShortcutE
     Ok(())
 }
 
+/**
+ * クイックランチャーのショートカットを取得
+ */
+#[tauri::command]
+pub async fn get_launcher_shortcut() -> Result<LauncherConfig, ShortcutError> {
+    crate::settings::load()
+        .map(|settings| settings.launcher)
+        .map_err(|error| ShortcutError { error })
+}
+
+/**
+ * クイックランチャーのショートカットを変更
+ * 
+ * 他のショートカットと重複する場合や登録できない場合は以前のショートカットのまま
+ */
+#[tauri::command]
+pub async fn set_launcher_shortcut(app: AppHandle, shortcut: String) -> Result<(), ShortcutError> {
+    crate::settings::update_with(&app, |settings| {
+        settings.launcher = LauncherConfig { shortcut };
+        Ok(())
+    })
+    .map(|_| ())
+    .map_err(|error| ShortcutError { error })
+}
+
+/// よく使われるショートカットとの衝突のヒント
+const KNOWN_CONFLICTS: &[(&str, &str)] = &[
+    ("CommandOrControl+Shift+P", "VS CodeやJetBrains IDEのコマンドパレットと同じ組み合わせです"),
+    ("CommandOrControl+Space", "Spotlightや入力ソースの切り替えと同じ組み合わせです"),
+    ("Control+Space", "入力ソースの切り替えやエディタの補完と同じ組み合わせです"),
+    ("Alt+Space", "ウィンドウメニューやランチャー（Alfred、PowerToys Run）と同じ組み合わせです"),
+    ("Control+Alt+T", "Linuxの端末の起動と同じ組み合わせです"),
+];
+
+/// 登録に失敗した場合の汎用ヒント
+const REGISTRATION_FAILED_HINT: &str =
+    "他のアプリケーションまたはOSが同じ組み合わせを使用している可能性があります。別の組み合わせを設定してください";
+
+/// ショートカットごとの最後の登録エラー（キー: 所有者ID、値: (ショートカット, エラー)）
+static REGISTRATION_ERRORS: OnceLock<Mutex<HashMap<String, (String, String)>>> = OnceLock::new();
+
+/// 登録結果を記録（成功した場合はエラーを消去）
+pub(crate) fn record_registration(owner: &str, shortcut: &str, result: &Result<(), String>) {
+    let errors = REGISTRATION_ERRORS.get_or_init(|| Mutex::new(HashMap::new()));
+    let Ok(mut errors) = errors.lock() else {
+        return;
+    };
+    match result {
+        Ok(()) => {
+            errors.remove(owner);
+        }
+        Err(e) => {
+            errors.insert(owner.to_string(), (shortcut.to_string(), e.clone()));
+        }
+    }
+}
+
+/// 現在のショートカットに対する最後の登録エラー
+fn last_registration_error(owner: &str, shortcut: &str) -> Option<String> {
+    let errors = REGISTRATION_ERRORS.get()?.lock().ok()?;
+    errors
+        .get(owner)
+        .filter(|(failed, _)| failed == shortcut)
+        .map(|(_, error)| error.clone())
+}
+
+/// 衝突のヒント（既知の組み合わせ、または登録できていない場合）
+fn conflict_hint(shortcut: &str, registered: bool) -> Option<String> {
+    let id = Shortcut::from_str(shortcut).ok()?.id();
+    KNOWN_CONFLICTS
+        .iter()
+        .find(|(known, _)| Shortcut::from_str(known).is_ok_and(|known| known.id() == id))
+        .map(|(_, hint)| hint.to_string())
+        .or_else(|| (!registered).then(|| REGISTRATION_FAILED_HINT.to_string()))
+}
+
+/// ショートカット1つ分の登録状況
+#[derive(Debug, Clone, Serialize)]
+pub struct ShortcutStatus {
+    /// 所有者ID（`launcher`、`capture`、`palette:1`など）
+    pub id: String,
+    /// 種類（`launcher` / `capture` / `palette` / `palette_leader` / `repeat_last` / `cycle_recent`）
+    pub kind: &'static str,
+    /// パレット位置（パレットホットキーのみ）
+    pub position: Option<u8>,
+    pub shortcut: String,
+    pub registered: bool,
+    pub last_error: Option<String>,
+    pub conflict_hint: Option<String>,
+}
+
+/// アプリが所有する全ショートカットの登録状況
+///
+/// プロンプト単位のグローバルショートカットはない（`quick_access_key`はアプリ内でのみ使用）
+fn collect_shortcut_status(app: &AppHandle) -> Vec<ShortcutStatus> {
+    let settings = crate::settings::load_or_default();
+    let launcher = (
+        "launcher".to_string(),
+        "launcher",
+        None,
+        settings.launcher.shortcut,
+    );
+    let capture = settings.capture.enabled.then(|| {
+        (crate::capture::CAPTURE_OWNER.to_string(), "capture", None, settings.capture.shortcut)
+    });
+    let palette = settings.palette.bindings().into_iter().map(|entry| {
+        let kind = crate::global_hotkey::binding_kind(entry.position);
+        let position = (kind == "palette").then_some(entry.position);
+        (crate::global_hotkey::palette_owner_id(entry.position), kind, position, entry.hotkey)
+    });
+
+    std::iter::once(launcher)
+        .chain(capture)
+        .chain(palette)
+        .map(|(id, kind, position, shortcut)| {
+            let registered = app.global_shortcut().is_registered(shortcut.as_str());
+            ShortcutStatus {
+                last_error: (!registered).then(|| last_registration_error(&id, &shortcut)).flatten(),
+                conflict_hint: conflict_hint(&shortcut, registered),
+                id,
+                kind,
+                position,
+                shortcut,
+                registered,
+            }
+        })
+        .collect()
+}
+
+fn shortcut_status_json(app: &AppHandle) -> serde_json::Value {
+    let shortcuts = collect_shortcut_status(app);
+    let launcher = shortcuts.iter().find(|status| status.kind == "launcher");
+    serde_json::json!({
+        "quick_launcher": {
+            "shortcut": launcher.map(|status| status.shortcut.clone()),
+            "registered": launcher.is_some_and(|status| status.registered),
+        },
+        "all_registered": shortcuts.iter().all(|status| status.registered),
+        "paused": is_paused(),
+        "shortcuts": shortcuts,
+    })
+}
+
 /**
  * 登録済みショートカットの確認
  * 
- * デバッグ・診断用途で現在登録されているショートカットを確認
+ * アプリが所有する全ショートカット（クイックランチャー、取り込み、パレットホットキー）について、
+ * 実際の登録状況・最後の登録エラー・衝突のヒントを返す
  * 
  * @param app Tauriアプリハンドル  
  * @returns 登録状況の情報
  */
 #[tauri::command]
-pub async fn get_shortcut_status(_app: AppHandle) -> Result<serde_json::Value, ShortcutError> {
-    // 登録状況確認（Tauriプラグインに依存するため簡易実装）
-    let status = serde_json::json!({
-        "quick_launcher": {
-            "shortcut": config::shortcuts::QUICK_LAUNCHER,
-            "registered": true // 実際の確認ロジックは将来実装
+pub async fn get_shortcut_status(app: AppHandle) -> Result<serde_json::Value, ShortcutError> {
+    Ok(shortcut_status_json(&app))
+}
+
+/**
+ * 登録できていないショートカットを登録し直す
+ * 
+ * 起動時に他のアプリケーションが使用していたショートカットなどを、後から再試行する
+ * （一時停止中は何もしない）
+ * 
+ * @param app Tauriアプリハンドル
+ * @returns 再試行後の登録状況
+ */
+#[tauri::command]
+pub async fn retry_shortcut_registration(app: AppHandle) -> Result<serde_json::Value, ShortcutError> {
+    let settings = crate::settings::load().map_err(|error| ShortcutError { error })?;
+    if is_paused() {
+        return Ok(shortcut_status_json(&app));
+    }
+
+    if !app.global_shortcut().is_registered(settings.launcher.shortcut.as_str()) {
+        if let Err(e) = apply_launcher_shortcut(&app, &settings.launcher.shortcut) {
+            eprintln!("Retrying launcher shortcut failed: {e}");
         }
-    });
-    
-    Ok(status)
+    }
+    if settings.capture.enabled && !app.global_shortcut().is_registered(settings.capture.shortcut.as_str()) {
+        if let Err(e) = crate::capture::apply_capture_shortcut(&app, &settings.capture) {
+            eprintln!("Retrying capture shortcut failed: {e}");
+        }
+    }
+    for e in crate::global_hotkey::register_missing_palette_hotkeys(&app, &settings.palette.bindings()) {
+        eprintln!("Retrying palette hotkey failed: {e}");
+    }
+
+    Ok(shortcut_status_json(&app))
+}
+
+/// 一時停止の最長時間（分）
+const MAX_PAUSE_MINUTES: u32 = 24 * 60;
+
+/// ホットキーの一時停止状態
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct HotkeyPauseState {
+    pub paused: bool,
+    /// 自動で再開する時刻（時間を指定して一時停止した場合）
+    pub resume_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+static PAUSE_STATE: Mutex<HotkeyPauseState> = Mutex::new(HotkeyPauseState { paused: false, resume_at: None });
+
+/// 自動再開のタスク
+static RESUME_TIMER: Mutex<Option<tauri::async_runtime::JoinHandle<()>>> = Mutex::new(None);
+
+/// ホットキーを一時停止中か
+pub(crate) fn is_paused() -> bool {
+    PAUSE_STATE.lock().map(|state| state.paused).unwrap_or(false)
+}
+
+pub(crate) fn pause_state() -> HotkeyPauseState {
+    PAUSE_STATE.lock().map(|state| state.clone()).unwrap_or_default()
+}
+
+fn cancel_resume_timer() {
+    if let Some(timer) = RESUME_TIMER.lock().ok().and_then(|mut timer| timer.take()) {
+        timer.abort();
+    }
+}
+
+/// 状態を更新してトレイとフロントエンドに反映
+fn set_pause_state(app: &AppHandle, state: HotkeyPauseState) {
+    if let Ok(mut current) = PAUSE_STATE.lock() {
+        *current = state.clone();
+    }
+    crate::tray::refresh_tray(app);
+    let _ = app.emit("hotkeys-pause-changed", &state);
+}
+
+/**
+ * アプリの全ショートカット（ランチャー、取り込み、パレットホットキー）を一時停止
+ * 
+ * `minutes`を指定した場合はその時間の後に自動で再開する。
+ * 一時停止中に変更した設定は再開時に反映する
+ */
+pub(crate) fn pause_app_shortcuts(app: &AppHandle, minutes: Option<u32>) -> Result<HotkeyPauseState, String> {
+    if let Some(minutes) = minutes {
+        if !(1..=MAX_PAUSE_MINUTES).contains(&minutes) {
+            return Err(format!("Pause duration must be between 1 and {MAX_PAUSE_MINUTES} minutes"));
+        }
+    }
+    cancel_resume_timer();
+
+    if let Err(e) = replace_shortcut(app, &ACTIVE_LAUNCHER, None, register_launcher_shortcut) {
+        eprintln!("Failed to unregister launcher shortcut: {e}");
+    }
+    if let Err(e) = crate::capture::unregister_capture_shortcut(app) {
+        eprintln!("Failed to unregister capture shortcut: {e}");
+    }
+    if let Err(e) = crate::global_hotkey::apply_palette_hotkeys(app, &[]) {
+        eprintln!("Failed to unregister palette hotkeys: {e}");
+    }
+
+    let resume_at = minutes.map(|minutes| chrono::Utc::now() + chrono::Duration::minutes(i64::from(minutes)));
+    if let Some(minutes) = minutes {
+        let handle = app.clone();
+        let timer = tauri::async_runtime::spawn(async move {
+            tokio::time::sleep(std::time::Duration::from_secs(u64::from(minutes) * 60)).await;
+            if let Err(e) = resume_app_shortcuts(&handle) {
+                eprintln!("Failed to resume hotkeys: {e}");
+            }
+        });
+        if let Ok(mut current) = RESUME_TIMER.lock() {
+            *current = Some(timer);
+        }
+    }
+
+    let state = HotkeyPauseState { paused: true, resume_at };
+    set_pause_state(app, state.clone());
+    Ok(state)
+}
+
+/**
+ * 一時停止したショートカットを保存済みの設定で登録し直す
+ * 
+ * 登録できないショートカットがあっても残りは登録する（状況は`get_shortcut_status`で確認できる）
+ */
+pub(crate) fn resume_app_shortcuts(app: &AppHandle) -> Result<HotkeyPauseState, String> {
+    cancel_resume_timer();
+    if !is_paused() {
+        return Ok(pause_state());
+    }
+    set_pause_state(app, HotkeyPauseState::default());
+
+    let settings = crate::settings::load()?;
+    if let Err(e) = apply_launcher_shortcut(app, &settings.launcher.shortcut) {
+        eprintln!("Failed to resume launcher shortcut: {e}");
+    }
+    if let Err(e) = crate::capture::apply_capture_shortcut(app, &settings.capture) {
+        eprintln!("Failed to resume capture shortcut: {e}");
+    }
+    for e in crate::global_hotkey::register_missing_palette_hotkeys(app, &settings.palette.bindings()) {
+        eprintln!("Failed to resume palette hotkey: {e}");
+    }
+    Ok(pause_state())
+}
+
+/**
+ * ホットキーを一時停止
+ * 
+ * @param minutes 自動で再開するまでの時間（分、省略時は再開するまで停止）
+ */
+#[tauri::command]
+pub async fn pause_hotkeys(app: AppHandle, minutes: Option<u32>) -> Result<HotkeyPauseState, ShortcutError> {
+    pause_app_shortcuts(&app, minutes).map_err(|error| ShortcutError { error })
+}
+
+/**
+ * 一時停止したホットキーを再開
+ */
+#[tauri::command]
+pub async fn resume_hotkeys(app: AppHandle) -> Result<HotkeyPauseState, ShortcutError> {
+    resume_app_shortcuts(&app).map_err(|error| ShortcutError { error })
+}
+
+/**
+ * ホットキーの一時停止状態を取得
+ */
+#[tauri::command]
+pub async fn get_hotkey_pause_state() -> Result<HotkeyPauseState, ShortcutError> {
+    Ok(pause_state())
 }
 
 /**
@@ -136,6 +549,26 @@ mod tests {
     fn test_shortcut_constants() {
         // ショートカット文字列の妥当性確認
         assert_eq!(config::shortcuts::QUICK_LAUNCHER, "CommandOrControl+Shift+P");
+        assert!(LauncherConfig::default().validate().is_ok());
+    }
+
+    #[test]
+    fn test_conflict_hint() {
+        // 既知の組み合わせは登録済みでもヒントを返す（修飾キーの順序は問わない）
+        assert!(conflict_hint("Shift+CommandOrControl+P", true).is_some());
+        assert_eq!(conflict_hint("Alt+Shift+9", false).as_deref(), Some(REGISTRATION_FAILED_HINT));
+        assert_eq!(conflict_hint("Alt+Shift+9", true), None);
+    }
+
+    #[test]
+    fn test_registration_errors_follow_current_shortcut() {
+        let owner = "test:errors";
+        record_registration(owner, "Alt+Shift+8", &Err("taken".to_string()));
+        assert_eq!(last_registration_error(owner, "Alt+Shift+8").as_deref(), Some("taken"));
+        // 別のショートカットに変更した後は古いエラーを返さない
+        assert_eq!(last_registration_error(owner, "Alt+Shift+7"), None);
+        record_registration(owner, "Alt+Shift+8", &Ok(()));
+        assert_eq!(last_registration_error(owner, "Alt+Shift+8"), None);
     }
     
     // 注意: グローバルショートカットの実際のテストは統合テストで実装
@@ -0,0 +1,114 @@
+/*!
+ * スターターライブラリ
+ *
+ * 初回起動時に、よく使うプロンプトのセット（一部はピン留め）を導入する
+ *
+ * - プロンプトはMarkdownフォルダ同期と同じ形式（フロントマター付きの`.md`）でアプリに埋め込む
+ * - 1つのトランザクションで導入する（途中で失敗した場合は1件も導入しない）
+ * - `starter`タグを付け、`remove_starter_prompts`コマンドでまとめて削除できるようにする
+ * - 導入するのは初回起動時の1回のみ（削除した後に再度導入することはない。app_stateモジュールで記録）
+ *   導入に失敗した場合は、次回の起動で再試行する
+ */
+use tauri::AppHandle;
+
+use crate::database::{CreatePromptRequest, PromptFilter};
+use crate::markdown_sync::SyncDocument;
+
+/// スターターライブラリのプロンプトに付けるタグ
+pub const STARTER_TAG: &str = "starter";
+
+/// 埋め込んだプロンプト（ファイル名, 内容）
+const STARTER_FILES: &[(&str, &str)] = &[
+    ("summarize.md", include_str!("../starter/summarize.md")),
+    ("explain-code.md", include_str!("../starter/explain-code.md")),
+    ("review-code.md", include_str!("../starter/review-code.md")),
+    ("proofread.md", include_str!("../starter/proofread.md")),
+    ("translate.md", include_str!("../starter/translate.md")),
+    ("write-tests.md", include_str!("../starter/write-tests.md")),
+    ("commit-message.md", include_str!("../starter/commit-message.md")),
+    ("brainstorm.md", include_str!("../starter/brainstorm.md")),
+];
+
+/// 埋め込んだプロンプトを作成リクエストとピン留め位置に変換
+fn starter_prompts() -> Result<Vec<(CreatePromptRequest, Option<u8>)>, String> {
+    STARTER_FILES
+        .iter()
+        .map(|(name, text)| {
+            let doc = SyncDocument::parse(text).map_err(|e| format!("Invalid starter prompt {name}: {e}"))?;
+            let mut tags = doc.tags;
+            tags.push(STARTER_TAG.to_string());
+            let request = CreatePromptRequest {
+                title: doc.title,
+                content: doc.content,
+                tags: Some(tags),
+                quick_access_key: doc.quick_access_key,
+                status: None,
+                visibility: None,
+                workspace_id: None,
+                is_sensitive: None,
+                paste_confirmation: None,
+            };
+            Ok((request, doc.pinned))
+        })
+        .collect()
+}
+
+/// スターターライブラリを導入し、導入した件数を返す（初回起動時に呼ぶ）
+pub async fn install_starter_library() -> Result<usize, String> {
+    let installed = crate::database::install_prompts(starter_prompts()?)
+        .await
+        .map_err(|e| format!("Failed to install starter prompts: {e}"))?;
+    if !installed.skipped_pins.is_empty() {
+        println!("Starter prompts were not pinned to taken positions {:?}", installed.skipped_pins);
+    }
+    Ok(installed.prompts.len())
+}
+
+/**
+ * スターターライブラリのプロンプト（`starter`タグ付き）をまとめて削除
+ *
+ * 編集したプロンプトも、タグが残っていれば削除する。削除した件数を返す
+ */
+#[tauri::command]
+pub async fn remove_starter_prompts(app_handle: AppHandle) -> Result<usize, String> {
+    let filter = PromptFilter { tag_id: Some(STARTER_TAG.to_string()), ..PromptFilter::default() };
+    let prompts = crate::database::list_prompts(&filter)
+        .await
+        .map_err(|e| format!("Failed to list starter prompts: {e}"))?;
+
+    let mut removed = 0;
+    for prompt in prompts {
+        if crate::database::delete_prompt(&prompt.id)
+            .await
+            .map_err(|e| format!("Failed to delete starter prompt: {e}"))?
+        {
+            removed += 1;
+        }
+    }
+    crate::tray::refresh_tray(&app_handle);
+    Ok(removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_starter_prompts_are_valid() {
+        let prompts = starter_prompts().unwrap();
+        assert_eq!(prompts.len(), STARTER_FILES.len());
+
+        let mut positions = HashSet::new();
+        for (request, position) in &prompts {
+            assert!(request.title.as_deref().is_some_and(|title| !title.is_empty() && title.len() <= 200));
+            assert!(!request.content.trim().is_empty());
+            assert!(request.content.len() <= 100_000);
+            assert!(request.tags.as_ref().is_some_and(|tags| tags.iter().any(|tag| tag == STARTER_TAG)));
+            if let Some(position) = position {
+                assert!(positions.insert(*position), "position {position} is pinned twice");
+            }
+        }
+        assert!(!positions.is_empty());
+    }
+}
@@ -1,14 +1,262 @@
 /*!
  * システムトレイ（メニューバー）の実装
  * Alfred/Clipyライクなバックグラウンド動作を提供
+ * 
+ * ホットキーの一時停止・再開もトレイから切り替えられる（状態はメニューとツールチップに表示）
+ * 
+ * メニューにはピン留めプロンプト（位置1-10）と最近ペーストしたプロンプトを表示し、
+ * クリックでクリップボードにコピーする（ピン留め・ペースト履歴の変更時にメニューを作り直す）。
+ * クリップボードのテキストから新しいプロンプトを作る項目もある
  */
 
+use std::sync::atomic::{AtomicU64, Ordering};
 use tauri::{
     tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent},
-    menu::{Menu, MenuItem},
+    menu::{CheckMenuItem, Menu, MenuItem, PredefinedMenuItem, Submenu},
     AppHandle, Manager, Emitter,
 };
 
+use crate::database::Prompt;
+
+/// トレイアイコンのID（メニュー・ツールチップの更新に使用）
+const TRAY_ID: &str = "main";
+
+/// トレイメニューから一時停止する場合の時間（分）
+const TRAY_PAUSE_MINUTES: u32 = 60;
+
+/// トレイメニューに表示するピン留め位置の上限
+const TRAY_PINNED_LIMIT: u8 = 10;
+
+/// メニュー項目のラベルの最大文字数（ヒントを除く）
+const MENU_LABEL_CHARS: usize = 40;
+
+/// ピン留めプロンプトの項目IDの接頭辞（後ろにピン留め位置）
+const PINNED_ITEM_PREFIX: &str = "pinned:";
+
+/// 最近のプロンプトの項目IDの接頭辞（後ろにプロンプトID）
+const RECENT_ITEM_PREFIX: &str = "recent:";
+
+/// メニューの作り直しの世代（古い内容で上書きしないようにする）
+static MENU_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// メニューのプロンプト項目（項目IDに埋め込む）
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum MenuPrompt {
+    /// ピン留め位置
+    Pinned(u8),
+    /// 最近ペーストしたプロンプトのID
+    Recent(String),
+}
+
+impl MenuPrompt {
+    fn item_id(&self) -> String {
+        match self {
+            Self::Pinned(position) => format!("{PINNED_ITEM_PREFIX}{position}"),
+            Self::Recent(prompt_id) => format!("{RECENT_ITEM_PREFIX}{prompt_id}"),
+        }
+    }
+
+    fn from_item_id(item_id: &str) -> Option<Self> {
+        if let Some(position) = item_id.strip_prefix(PINNED_ITEM_PREFIX) {
+            return position.parse().ok().map(Self::Pinned);
+        }
+        item_id.strip_prefix(RECENT_ITEM_PREFIX).map(|prompt_id| Self::Recent(prompt_id.to_string()))
+    }
+}
+
+/// メニューに表示するプロンプト
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct TrayPromptItem {
+    id: String,
+    label: String,
+}
+
+/// メニューに表示するプロンプトの一覧（データベースから読み込む）
+#[derive(Debug, Clone, Default)]
+struct TrayMenuData {
+    pinned: Vec<TrayPromptItem>,
+    recent: Vec<TrayPromptItem>,
+}
+
+/// メニューに表示するプロンプトを読み込む（読み込めない場合は空）
+async fn load_menu_data() -> TrayMenuData {
+    let palette = crate::settings::load_or_default().palette;
+    let pinned = match crate::database::get_pinned_prompts().await {
+        Ok(prompts) => prompts
+            .iter()
+            .filter_map(|prompt| {
+                let position = prompt.pinned_position.filter(|position| *position <= TRAY_PINNED_LIMIT)?;
+                Some(TrayPromptItem {
+                    id: MenuPrompt::Pinned(position).item_id(),
+                    label: pinned_label(position, &prompt_label(prompt), palette.hotkey_hint(position).as_deref()),
+                })
+            })
+            .collect(),
+        Err(e) => {
+            eprintln!("Failed to load pinned prompts for tray menu: {e}");
+            Vec::new()
+        }
+    };
+
+    let mut recent = Vec::new();
+    for entry in crate::global_hotkey::recent_pastes() {
+        match crate::database::get_prompt_redacted(&entry.prompt_id).await {
+            Ok(Some(prompt)) => recent.push(TrayPromptItem {
+                id: MenuPrompt::Recent(prompt.id.clone()).item_id(),
+                label: escape_label(&prompt_label(&prompt)),
+            }),
+            Ok(None) => {} // 削除されたプロンプト
+            Err(e) => eprintln!("Failed to load recent prompt for tray menu: {e}"),
+        }
+    }
+    TrayMenuData { pinned, recent }
+}
+
+/// プロンプトの表示名（タイトル、なければ本文の最初の行。シークレット・機密の本文は表示しない）
+fn prompt_label(prompt: &Prompt) -> String {
+    let title = prompt.title.as_deref().map(str::trim).filter(|title| !title.is_empty());
+    let text = match title {
+        Some(title) => title,
+        None if prompt.is_secret => return "Secret prompt".to_string(),
+        None if prompt.is_sensitive => return "Sensitive prompt".to_string(),
+        None => prompt
+            .content
+            .lines()
+            .map(str::trim)
+            .find(|line| !line.is_empty())
+            .unwrap_or("Untitled"),
+    };
+    if text.chars().count() <= MENU_LABEL_CHARS {
+        return text.to_string();
+    }
+    let mut label: String = text.chars().take(MENU_LABEL_CHARS - 1).collect();
+    label.push('…');
+    label
+}
+
+/// ピン留めプロンプトの項目のラベル（位置とホットキーのヒント付き）
+fn pinned_label(position: u8, label: &str, hint: Option<&str>) -> String {
+    let label = escape_label(label);
+    match hint {
+        Some(hint) => format!("{position}. {label}  ({})", escape_label(&display_hotkey(hint))),
+        None => format!("{position}. {label}"),
+    }
+}
+
+/// ホットキーの表示用の表記（`CommandOrControl`を実際のキー名に）
+fn display_hotkey(hotkey: &str) -> String {
+    let command = if cfg!(target_os = "macos") { "Cmd" } else { "Ctrl" };
+    hotkey.replace("CommandOrControl", command).replace("Control", "Ctrl")
+}
+
+/// メニューのラベルでアクセスキーとして扱われる`&`をエスケープ
+fn escape_label(label: &str) -> String {
+    label.replace('&', "&&")
+}
+
+/// 現在の状態でトレイメニューを作成
+fn build_tray_menu(app: &AppHandle, data: &TrayMenuData) -> tauri::Result<Menu<tauri::Wry>> {
+    let pause = crate::shortcuts::pause_state();
+    let menu = Menu::new(app)?;
+
+    menu.append(&MenuItem::with_id(app, "show", "Show PromPalette", true, None::<&str>)?)?;
+    menu.append(&MenuItem::with_id(app, "hide", "Hide PromPalette", true, None::<&str>)?)?;
+    menu.append(&MenuItem::with_id(app, "new_from_clipboard", "New Prompt from Clipboard", true, None::<&str>)?)?;
+    menu.append(&PredefinedMenuItem::separator(app)?)?;
+
+    if data.pinned.is_empty() {
+        menu.append(&MenuItem::with_id(app, "no_pinned", "No Pinned Prompts", false, None::<&str>)?)?;
+    }
+    for item in &data.pinned {
+        menu.append(&MenuItem::with_id(app, &item.id, &item.label, true, None::<&str>)?)?;
+    }
+    let recent = Submenu::with_id(app, "recent", "Recent", !data.recent.is_empty())?;
+    for item in &data.recent {
+        recent.append(&MenuItem::with_id(app, &item.id, &item.label, true, None::<&str>)?)?;
+    }
+    menu.append(&recent)?;
+    menu.append(&PredefinedMenuItem::separator(app)?)?;
+
+    menu.append(&CheckMenuItem::with_id(app, "pause_hotkeys", "Pause Hotkeys", true, pause.paused, None::<&str>)?)?;
+    menu.append(&MenuItem::with_id(
+        app,
+        "pause_hotkeys_for_hour",
+        "Pause Hotkeys for 1 Hour",
+        !pause.paused,
+        None::<&str>,
+    )?)?;
+    menu.append(&PredefinedMenuItem::separator(app)?)?;
+    menu.append(&MenuItem::with_id(app, "quit", "Quit PromPalette", true, None::<&str>)?)?;
+    Ok(menu)
+}
+
+/// 現在の状態のツールチップ
+fn tray_tooltip() -> String {
+    let pause = crate::shortcuts::pause_state();
+    match (pause.paused, pause.resume_at) {
+        (false, _) => "PromPalette - AI Prompt Manager".to_string(),
+        (true, None) => "PromPalette - Hotkeys paused".to_string(),
+        (true, Some(resume_at)) => format!(
+            "PromPalette - Hotkeys paused until {}",
+            resume_at.with_timezone(&chrono::Local).format("%H:%M")
+        ),
+    }
+}
+
+/**
+ * トレイメニューとツールチップを現在の状態で作り直す
+ * 
+ * プロンプトの読み込みはバックグラウンドで行う（続けて呼ばれた場合は最後の内容のみ反映）
+ */
+pub fn refresh_tray(app: &AppHandle) {
+    if app.tray_by_id(TRAY_ID).is_none() {
+        return; // トレイ未初期化（またはトレイ非対応環境）
+    }
+    let generation = MENU_GENERATION.fetch_add(1, Ordering::SeqCst) + 1;
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let data = load_menu_data().await;
+        if MENU_GENERATION.load(Ordering::SeqCst) != generation {
+            return; // より新しい作り直しがある
+        }
+        let Some(tray) = app.tray_by_id(TRAY_ID) else {
+            return;
+        };
+        match build_tray_menu(&app, &data) {
+            Ok(menu) => {
+                if let Err(e) = tray.set_menu(Some(menu)) {
+                    eprintln!("Failed to update tray menu: {e}");
+                }
+            }
+            Err(e) => eprintln!("Failed to build tray menu: {e}"),
+        }
+        if let Err(e) = tray.set_tooltip(Some(tray_tooltip())) {
+            eprintln!("Failed to update tray tooltip: {e}");
+        }
+    });
+}
+
+/// メニューのプロンプトをクリップボードにコピー（`copy_pinned_prompt`コマンドと同じ処理）
+fn copy_menu_prompt(app: &AppHandle, target: MenuPrompt) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let (result, payload) = match &target {
+            MenuPrompt::Pinned(position) => (
+                crate::commands::copy_pinned_prompt(app.clone(), *position).await.map(|_| ()),
+                serde_json::json!({ "position": position }),
+            ),
+            MenuPrompt::Recent(prompt_id) => (
+                crate::commands::copy_prompt_by_id(&app, prompt_id).await,
+                serde_json::json!({ "prompt_id": prompt_id }),
+            ),
+        };
+        let _ = match result {
+            Ok(()) => app.emit("tray-prompt-copied", payload),
+            Err(e) => app.emit("tray-copy-failed", serde_json::json!({ "target": payload, "error": e.error })),
+        };
+    });
+}
+
 /// システムトレイアイコンとメニューを初期化
 /// 
 /// # Arguments
@@ -19,19 +267,15 @@ use tauri::{
 pub fn init_system_tray(app: &AppHandle) -> Result<(), Box<dyn std::error::Error>> {
     println!("Initializing system tray...");
 
-    // システムトレイメニューを作成
-    let show_item = MenuItem::with_id(app, "show", "Show PromPalette", true, None::<&str>)?;
-    let hide_item = MenuItem::with_id(app, "hide", "Hide PromPalette", true, None::<&str>)?;
-    let quit_item = MenuItem::with_id(app, "quit", "Quit PromPalette", true, None::<&str>)?;
-
-    let menu = Menu::with_items(app, &[&show_item, &hide_item, &quit_item])?;
+    // システムトレイメニューを作成（プロンプトは作成後に読み込んで反映）
+    let menu = build_tray_menu(app, &TrayMenuData::default())?;
 
     // システムトレイアイコンを作成
-    let _tray = TrayIconBuilder::new()
+    let _tray = TrayIconBuilder::with_id(TRAY_ID)
         .menu(&menu)
         .show_menu_on_left_click(false)
         .icon(app.default_window_icon().ok_or("No default window icon found")?.clone())
-        .tooltip("PromPalette - AI Prompt Manager")
+        .tooltip(tray_tooltip())
         .on_tray_icon_event(|tray, event| {
             match event {
                 TrayIconEvent::Click {
@@ -67,15 +311,47 @@ pub fn init_system_tray(app: &AppHandle) -> Result<(), Box<dyn std::error::Error
                         let _ = window.hide();
                     }
                 }
+                "new_from_clipboard" => {
+                    // 結果は`prompt-captured` / `capture-failed`で通知される
+                    let app = app.clone();
+                    tauri::async_runtime::spawn(async move {
+                        if let Err(e) = crate::capture::create_prompt_from_clipboard(app).await {
+                            eprintln!("Failed to create prompt from clipboard: {e}");
+                        }
+                    });
+                }
+                "pause_hotkeys" => {
+                    // 一時停止・再開を切り替え（メニューのチェック状態は作り直して反映）
+                    let result = if crate::shortcuts::is_paused() {
+                        crate::shortcuts::resume_app_shortcuts(app)
+                    } else {
+                        crate::shortcuts::pause_app_shortcuts(app, None)
+                    };
+                    if let Err(e) = result {
+                        eprintln!("Failed to toggle hotkey pause: {e}");
+                        refresh_tray(app);
+                    }
+                }
+                "pause_hotkeys_for_hour" => {
+                    if let Err(e) = crate::shortcuts::pause_app_shortcuts(app, Some(TRAY_PAUSE_MINUTES)) {
+                        eprintln!("Failed to pause hotkeys: {e}");
+                    }
+                }
                 "quit" => {
                     // アプリケーションを完全に終了
                     app.exit(0);
                 }
-                _ => {}
+                id => {
+                    if let Some(target) = MenuPrompt::from_item_id(id) {
+                        copy_menu_prompt(app, target);
+                    }
+                }
             }
         })
         .build(app)?;
 
+    refresh_tray(app);
+
     println!("System tray initialized successfully");
     Ok(())
 }
@@ -87,8 +363,10 @@ pub fn handle_window_close_requested(app_handle: &AppHandle, window_label: &str)
         // ウィンドウを隠すだけで、アプリケーションは終了しない
         let _ = window.hide();
         
-        // 初回クローズ時にユーザーに通知
-        let _ = app_handle.emit("app-minimized-to-tray", ());
+        // 初回クローズ時にユーザーに通知（表示済みかどうかは再起動後も保持）
+        if crate::app_state::take_tray_tip() {
+            let _ = app_handle.emit("app-minimized-to-tray", ());
+        }
         
         println!("Window '{}' hidden to system tray", window_label);
     }
@@ -117,4 +395,67 @@ pub fn toggle_main_window(app_handle: AppHandle) -> Result<bool, String> {
 #[tauri::command]
 pub fn quit_app(app_handle: AppHandle) {
     app_handle.exit(0);
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::{PasteConfirmation, PromptStatus, PromptVisibility};
+
+    fn prompt(title: Option<&str>, content: &str) -> Prompt {
+        Prompt {
+            id: "prompt-1".to_string(),
+            title: title.map(str::to_string),
+            content: content.to_string(),
+            tags: None,
+            quick_access_key: None,
+            created_at: chrono::Utc::now(),
+            updated_at: chrono::Utc::now(),
+            pinned_position: Some(1),
+            pinned_at: None,
+            status: PromptStatus::default(),
+            visibility: PromptVisibility::default(),
+            workspace_id: None,
+            is_secret: false,
+            is_sensitive: false,
+            paste_confirmation: PasteConfirmation::default(),
+        }
+    }
+
+    #[test]
+    fn test_prompt_label() {
+        assert_eq!(prompt_label(&prompt(Some(" Review "), "body")), "Review");
+        assert_eq!(prompt_label(&prompt(None, "\n  First line\nsecond")), "First line");
+        assert_eq!(prompt_label(&prompt(Some(""), "")), "Untitled");
+
+        let long = prompt_label(&prompt(None, &"x".repeat(100)));
+        assert_eq!(long.chars().count(), MENU_LABEL_CHARS);
+        assert!(long.ends_with('…'));
+
+        // シークレット・機密の本文は表示しない
+        let secret = Prompt { is_secret: true, ..prompt(None, "api key") };
+        assert_eq!(prompt_label(&secret), "Secret prompt");
+        let sensitive = Prompt { is_sensitive: true, ..prompt(None, "password") };
+        assert_eq!(prompt_label(&sensitive), "Sensitive prompt");
+        let titled = Prompt { is_secret: true, ..prompt(Some("Deploy token"), "token") };
+        assert_eq!(prompt_label(&titled), "Deploy token");
+    }
+
+    #[test]
+    fn test_pinned_label() {
+        let command = if cfg!(target_os = "macos") { "Cmd" } else { "Ctrl" };
+        assert_eq!(
+            pinned_label(1, "Q&A", Some("CommandOrControl+Control+1")),
+            format!("1. Q&&A  ({command}+Ctrl+1)")
+        );
+        assert_eq!(pinned_label(2, "Summary", None), "2. Summary");
+    }
+
+    #[test]
+    fn test_menu_prompt_item_id() {
+        for target in [MenuPrompt::Pinned(10), MenuPrompt::Recent("prompt-1".to_string())] {
+            assert_eq!(MenuPrompt::from_item_id(&target.item_id()), Some(target));
+        }
+        assert_eq!(MenuPrompt::from_item_id("pinned:x"), None);
+        assert_eq!(MenuPrompt::from_item_id("quit"), None);
+    }
+}
@@ -186,7 +186,8 @@ pub async fn create_backup(_app: AppHandle) -> Result<BackupResult, UpdateError>
     let backup_filename = format!("prompalette_backup_{}.db", timestamp);
     let backup_path = backup_dir.join(&backup_filename);
     
-    // データベースファイルをコピー
+    // データベースファイルをコピー（暗号化が有効な場合は暗号化されたまま保存される）
+    crate::database::checkpoint_wal().await;
     match std::fs::copy(&db_path, &backup_path) {
         Ok(_) => Ok(BackupResult {
             success: true,
@@ -280,24 +281,22 @@ pub async fn download_and_apply_update(app: AppHandle) -> Result<UpdateStatus, S
 }
 
 /// バックアップからの復元
+///
+/// 接続中のデータベースを閉じてから置き換え、開き直す
 #[tauri::command]
 pub async fn restore_from_backup(backup_path: String) -> Result<bool, String> {
-    let env = Environment::current();
-    let data_dir = env.data_dir().map_err(|e| e.to_string())?;
-    let db_path = data_dir.join(env.database_filename());
-    
     let backup_path = PathBuf::from(backup_path);
     
     // バックアップファイルの存在確認
     if !backup_path.exists() {
         return Err("Backup file does not exist".to_string());
     }
+    let pool = crate::database::try_get_db_pool()?;
+    crate::encryption::ensure_backup_matches_encryption(&pool, &backup_path).await?;
     
     // データベースファイルを復元
-    match std::fs::copy(&backup_path, &db_path) {
-        Ok(_) => Ok(true),
-        Err(e) => Err(format!("Failed to restore database: {}", e)),
-    }
+    crate::database::replace_database_file(&backup_path).await?;
+    Ok(true)
 }
 
 /// バックアップ情報の詳細構造
@@ -396,7 +395,8 @@ pub async fn create_manual_backup(name: Option<String>) -> Result<BackupResult,
     };
     let backup_path = backup_dir.join(&backup_filename);
     
-    // データベースファイルをコピー
+    // データベースファイルをコピー（暗号化が有効な場合は暗号化されたまま保存される）
+    crate::database::checkpoint_wal().await;
     match std::fs::copy(&db_path, &backup_path) {
         Ok(_) => Ok(BackupResult {
             success: true,
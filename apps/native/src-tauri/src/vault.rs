@@ -0,0 +1,453 @@
+/*!
+ * シークレットプロンプトの保管庫
+ *
+ * データベース全体の暗号化とは別に、個別のプロンプトを「シークレット」として保護する
+ *
+ * - 本文: 保管庫の鍵（パスフレーズからArgon2idで導出）でChaCha20-Poly1305により暗号化して保存
+ *   （関連データにプロンプトIDを使い、別のプロンプトへの付け替えを検出）
+ * - 検索: シークレットプロンプトの本文は検索対象外
+ * - 読み取り: 保管庫のロック解除中のみ本文を返す
+ * - 自動ロック: 最後に使用してから一定時間（既定5分）で鍵を破棄
+ * - 同期: API同期・LAN同期・Markdown同期の対象外
+ */
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+use zeroize::Zeroizing;
+
+use crate::encryption::{self, KdfParams};
+use crate::environment::Environment;
+
+/// 保管庫設定ファイル名（アプリデータディレクトリに保存）
+const CONFIG_FILENAME: &str = "vault.json";
+
+/// 暗号化された本文の接頭辞
+const CIPHERTEXT_PREFIX: &str = "vault:v1:";
+
+/// ノンス長
+const NONCE_LEN: usize = 12;
+
+/// パスフレーズ確認用の平文と関連データ
+const VERIFIER_PLAINTEXT: &[u8] = b"prompalette-vault";
+const VERIFIER_AAD: &[u8] = b"vault-verifier";
+
+/// 自動ロックまでの時間（秒）
+const DEFAULT_IDLE_TIMEOUT_SECS: u64 = 300;
+const MIN_IDLE_TIMEOUT_SECS: u64 = 30;
+const MAX_IDLE_TIMEOUT_SECS: u64 = 24 * 60 * 60;
+
+/// 自動ロックの確認間隔
+const IDLE_CHECK_INTERVAL: Duration = Duration::from_secs(10);
+
+/// 保管庫がロックされている場合のエラー
+pub const VAULT_LOCKED_ERROR: &str = "Vault is locked. Unlock it to use secret prompts.";
+
+/// 保管庫設定（永続化）
+///
+/// 鍵そのものは保存しない
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VaultConfig {
+    /// Argon2のソルト（Base64、未設定なら保管庫は未作成）
+    pub salt: Option<String>,
+    #[serde(default)]
+    pub kdf: KdfParams,
+    /// パスフレーズ確認用の暗号文
+    pub verifier: Option<String>,
+    #[serde(default = "default_idle_timeout_secs")]
+    pub idle_timeout_secs: u64,
+}
+
+fn default_idle_timeout_secs() -> u64 {
+    DEFAULT_IDLE_TIMEOUT_SECS
+}
+
+impl Default for VaultConfig {
+    fn default() -> Self {
+        Self {
+            salt: None,
+            kdf: KdfParams::default(),
+            verifier: None,
+            idle_timeout_secs: DEFAULT_IDLE_TIMEOUT_SECS,
+        }
+    }
+}
+
+impl VaultConfig {
+    fn config_path() -> Result<PathBuf, String> {
+        let env = Environment::current();
+        let data_dir = env.data_dir().map_err(|e| e.to_string())?;
+        Ok(data_dir.join(CONFIG_FILENAME))
+    }
+
+    /// 設定ファイルから読み込み（存在しない場合はデフォルト）
+    pub fn load() -> Result<Self, String> {
+        let path = Self::config_path()?;
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(&path).map_err(|e| e.to_string())?;
+        serde_json::from_str(&content).map_err(|e| format!("Invalid vault config: {e}"))
+    }
+
+    /// 設定ファイルへ保存
+    pub fn save(&self) -> Result<(), String> {
+        let content = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        std::fs::write(Self::config_path()?, content).map_err(|e| format!("Failed to save vault config: {e}"))
+    }
+
+    /// 保管庫が作成済みかどうか
+    pub fn is_set_up(&self) -> bool {
+        self.salt.is_some() && self.verifier.is_some()
+    }
+
+    fn salt(&self) -> Result<Vec<u8>, String> {
+        use base64::{engine::general_purpose, Engine as _};
+        let salt = self.salt.as_deref().ok_or("Vault is not set up")?;
+        general_purpose::STANDARD
+            .decode(salt)
+            .map_err(|_| "Invalid salt in vault config".to_string())
+    }
+
+    fn idle_timeout(&self) -> Duration {
+        Duration::from_secs(self.idle_timeout_secs.clamp(MIN_IDLE_TIMEOUT_SECS, MAX_IDLE_TIMEOUT_SECS))
+    }
+}
+
+/// 保管庫の鍵（破棄時にゼロクリア）
+struct VaultKey(Zeroizing<[u8; 32]>);
+
+impl VaultKey {
+    fn cipher(&self) -> ChaCha20Poly1305 {
+        ChaCha20Poly1305::new(Key::from_slice(self.0.as_ref()))
+    }
+
+    /// 暗号化して`vault:v1:<Base64(ノンス || 暗号文)>`形式で返す
+    fn seal(&self, plaintext: &[u8], aad: &[u8]) -> Result<String, String> {
+        use base64::{engine::general_purpose, Engine as _};
+        use rand::RngCore;
+        let mut nonce = [0u8; NONCE_LEN];
+        rand::rngs::OsRng.fill_bytes(&mut nonce);
+        let ciphertext = self
+            .cipher()
+            .encrypt(Nonce::from_slice(&nonce), Payload { msg: plaintext, aad })
+            .map_err(|_| "Failed to encrypt secret".to_string())?;
+        let mut sealed = nonce.to_vec();
+        sealed.extend_from_slice(&ciphertext);
+        Ok(format!("{CIPHERTEXT_PREFIX}{}", general_purpose::STANDARD.encode(sealed)))
+    }
+
+    /// `seal`の出力を復号
+    fn open(&self, sealed: &str, aad: &[u8]) -> Result<Zeroizing<Vec<u8>>, String> {
+        use base64::{engine::general_purpose, Engine as _};
+        let encoded = sealed.strip_prefix(CIPHERTEXT_PREFIX).ok_or("Secret is not encrypted")?;
+        let raw = general_purpose::STANDARD
+            .decode(encoded)
+            .map_err(|_| "Invalid secret ciphertext".to_string())?;
+        if raw.len() < NONCE_LEN {
+            return Err("Invalid secret ciphertext".to_string());
+        }
+        let (nonce, ciphertext) = raw.split_at(NONCE_LEN);
+        self.cipher()
+            .decrypt(Nonce::from_slice(nonce), Payload { msg: ciphertext, aad })
+            .map(Zeroizing::new)
+            .map_err(|_| "Failed to decrypt secret".to_string())
+    }
+
+    fn seal_text(&self, prompt_id: &str, text: &str) -> Result<String, String> {
+        self.seal(text.as_bytes(), prompt_id.as_bytes())
+    }
+
+    fn open_text(&self, prompt_id: &str, sealed: &str) -> Result<String, String> {
+        let plaintext = self.open(sealed, prompt_id.as_bytes())?;
+        String::from_utf8(plaintext.to_vec()).map_err(|_| "Secret is not valid UTF-8".to_string())
+    }
+}
+
+/// ロック解除中の保管庫
+struct UnlockedVault {
+    key: VaultKey,
+    last_used: Instant,
+    idle_timeout: Duration,
+}
+
+impl UnlockedVault {
+    fn is_expired(&self, now: Instant) -> bool {
+        now.duration_since(self.last_used) >= self.idle_timeout
+    }
+}
+
+/// ロック解除中の鍵（ロック中は`None`）
+static VAULT: Mutex<Option<UnlockedVault>> = Mutex::new(None);
+
+/// 自動ロックの監視タスク
+static IDLE_TASK: Mutex<Option<tauri::async_runtime::JoinHandle<()>>> = Mutex::new(None);
+
+/// ロック解除中の鍵で処理する（アイドル時間を超えていればロックしてエラー）
+///
+/// `touch`: 最終使用時刻を更新するか（一覧表示などの受動的な利用では更新しない）
+fn use_key<T>(
+    vault: &mut Option<UnlockedVault>,
+    now: Instant,
+    touch: bool,
+    f: impl FnOnce(&VaultKey) -> Result<T, String>,
+) -> Result<T, String> {
+    match vault.as_mut() {
+        Some(unlocked) if !unlocked.is_expired(now) => {
+            if touch {
+                unlocked.last_used = now;
+            }
+            f(&unlocked.key)
+        }
+        Some(_) => {
+            *vault = None;
+            Err(VAULT_LOCKED_ERROR.to_string())
+        }
+        None => Err(VAULT_LOCKED_ERROR.to_string()),
+    }
+}
+
+/// 明示的な操作（表示・ペースト・保存）で鍵を使う（自動ロックまでの時間を延長する）
+fn with_key<T>(f: impl FnOnce(&VaultKey) -> Result<T, String>) -> Result<T, String> {
+    use_key(&mut VAULT.lock().unwrap(), Instant::now(), true, f)
+}
+
+/// 受動的に鍵を使う（画面を開いているだけで自動ロックされなくならないよう、延長しない）
+fn peek_key<T>(f: impl FnOnce(&VaultKey) -> Result<T, String>) -> Result<T, String> {
+    use_key(&mut VAULT.lock().unwrap(), Instant::now(), false, f)
+}
+
+/// 保管庫がロック解除中かどうか
+pub fn is_unlocked() -> bool {
+    VAULT
+        .lock()
+        .unwrap()
+        .as_ref()
+        .is_some_and(|unlocked| !unlocked.is_expired(Instant::now()))
+}
+
+/// シークレットプロンプトの本文を暗号化
+pub fn encrypt_content(prompt_id: &str, content: &str) -> Result<String, String> {
+    with_key(|key| key.seal_text(prompt_id, content))
+}
+
+/// シークレットプロンプトの本文を復号
+pub fn decrypt_content(prompt_id: &str, stored: &str) -> Result<String, String> {
+    with_key(|key| key.open_text(prompt_id, stored))
+}
+
+/// 一覧表示用の本文（ロック中は空、復号できない場合も空）
+///
+/// 自動ロックまでの時間は延長しない
+pub fn redacted_content(prompt_id: &str, stored: &str) -> String {
+    if !is_unlocked() {
+        return String::new();
+    }
+    peek_key(|key| key.open_text(prompt_id, stored)).unwrap_or_else(|e| {
+        eprintln!("Failed to decrypt secret prompt {prompt_id}: {e}");
+        String::new()
+    })
+}
+
+/// 鍵導出はCPU・メモリを使うため専用スレッドで実行
+async fn derive_key(passphrase: Zeroizing<String>, salt: Vec<u8>, params: KdfParams) -> Result<VaultKey, String> {
+    tokio::task::spawn_blocking(move || encryption::derive_key_bytes(&passphrase, &salt, params).map(VaultKey))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// 鍵を保持し、自動ロックの監視を開始
+fn install_key(app_handle: &AppHandle, key: VaultKey, idle_timeout: Duration) {
+    *VAULT.lock().unwrap() = Some(UnlockedVault {
+        key,
+        last_used: Instant::now(),
+        idle_timeout,
+    });
+
+    let app_handle = app_handle.clone();
+    let task = tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(IDLE_CHECK_INTERVAL).await;
+            let expired = {
+                let mut vault = VAULT.lock().unwrap();
+                match vault.as_ref() {
+                    Some(unlocked) if unlocked.is_expired(Instant::now()) => {
+                        *vault = None;
+                        true
+                    }
+                    Some(_) => false,
+                    // 手動でロックされた
+                    None => break,
+                }
+            };
+            if expired {
+                println!("Vault locked after idle timeout");
+                emit_locked(&app_handle);
+                break;
+            }
+        }
+    });
+    if let Some(previous) = IDLE_TASK.lock().unwrap().replace(task) {
+        previous.abort();
+    }
+}
+
+fn emit_locked(app_handle: &AppHandle) {
+    if let Err(e) = app_handle.emit("vault-locked", ()) {
+        eprintln!("Failed to emit vault-locked event: {e}");
+    }
+}
+
+/// 保管庫の状態を取得
+#[tauri::command]
+pub async fn get_vault_status() -> Result<serde_json::Value, String> {
+    let config = VaultConfig::load()?;
+    Ok(serde_json::json!({
+        "set_up": config.is_set_up(),
+        "unlocked": is_unlocked(),
+        "idle_timeout_secs": config.idle_timeout().as_secs(),
+    }))
+}
+
+/// 保管庫を作成（作成後はロック解除された状態）
+#[tauri::command]
+pub async fn setup_vault(app_handle: AppHandle, passphrase: String) -> Result<(), String> {
+    let passphrase = Zeroizing::new(passphrase);
+    encryption::validate_passphrase(&passphrase)?;
+    let mut config = VaultConfig::load()?;
+    if config.is_set_up() {
+        return Err("Vault is already set up".to_string());
+    }
+
+    use base64::{engine::general_purpose, Engine as _};
+    let salt = encryption::generate_salt();
+    let key = derive_key(passphrase, salt.clone(), config.kdf).await?;
+    config.salt = Some(general_purpose::STANDARD.encode(&salt));
+    config.verifier = Some(key.seal(VERIFIER_PLAINTEXT, VERIFIER_AAD)?);
+    config.save()?;
+
+    println!("Vault set up");
+    install_key(&app_handle, key, config.idle_timeout());
+    Ok(())
+}
+
+/// パスフレーズで保管庫のロックを解除
+#[tauri::command]
+pub async fn unlock_vault(app_handle: AppHandle, passphrase: String) -> Result<(), String> {
+    let config = VaultConfig::load()?;
+    let verifier = config.verifier.as_deref().ok_or("Vault is not set up")?;
+    let key = derive_key(Zeroizing::new(passphrase), config.salt()?, config.kdf).await?;
+    key.open(verifier, VERIFIER_AAD)
+        .map_err(|_| "Incorrect passphrase".to_string())?;
+
+    println!("Vault unlocked");
+    install_key(&app_handle, key, config.idle_timeout());
+    Ok(())
+}
+
+/// 保管庫をロック（鍵を破棄）
+#[tauri::command]
+pub async fn lock_vault(app_handle: AppHandle) -> Result<(), String> {
+    let was_unlocked = VAULT.lock().unwrap().take().is_some();
+    if let Some(task) = IDLE_TASK.lock().unwrap().take() {
+        task.abort();
+    }
+    if was_unlocked {
+        println!("Vault locked");
+        emit_locked(&app_handle);
+    }
+    Ok(())
+}
+
+/// 自動ロックまでの時間を変更
+#[tauri::command]
+pub async fn set_vault_idle_timeout(seconds: u64) -> Result<(), String> {
+    if !(MIN_IDLE_TIMEOUT_SECS..=MAX_IDLE_TIMEOUT_SECS).contains(&seconds) {
+        return Err(format!(
+            "Idle timeout must be between {MIN_IDLE_TIMEOUT_SECS} and {MAX_IDLE_TIMEOUT_SECS} seconds"
+        ));
+    }
+    let mut config = VaultConfig::load()?;
+    config.idle_timeout_secs = seconds;
+    config.save()?;
+    if let Some(unlocked) = VAULT.lock().unwrap().as_mut() {
+        unlocked.idle_timeout = Duration::from_secs(seconds);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_key(passphrase: &str) -> VaultKey {
+        let params = KdfParams { memory_kib: 1024, iterations: 1, parallelism: 1 };
+        VaultKey(encryption::derive_key_bytes(passphrase, b"0123456789abcdef", params).unwrap())
+    }
+
+    #[test]
+    fn test_secret_roundtrip_is_bound_to_prompt_id() {
+        let key = test_key("correct horse");
+        let sealed = key.seal_text("prompt-1", "API key: sk-123").unwrap();
+
+        assert!(sealed.starts_with(CIPHERTEXT_PREFIX));
+        assert!(!sealed.contains("sk-123"));
+        assert_eq!(key.open_text("prompt-1", &sealed).unwrap(), "API key: sk-123");
+        // 別のプロンプトに付け替えた暗号文は復号できない
+        assert!(key.open_text("prompt-2", &sealed).is_err());
+        // 同じ本文でもノンスが異なる
+        assert_ne!(sealed, key.seal_text("prompt-1", "API key: sk-123").unwrap());
+    }
+
+    #[test]
+    fn test_wrong_key_fails_verification() {
+        let key = test_key("correct horse");
+        let verifier = key.seal(VERIFIER_PLAINTEXT, VERIFIER_AAD).unwrap();
+
+        assert!(test_key("correct horse").open(&verifier, VERIFIER_AAD).is_ok());
+        assert!(test_key("battery staple").open(&verifier, VERIFIER_AAD).is_err());
+        assert!(key.open("not encrypted", VERIFIER_AAD).is_err());
+    }
+
+    #[test]
+    fn test_idle_expiry_and_timeout_bounds() {
+        let now = Instant::now();
+        let unlocked = UnlockedVault {
+            key: test_key("correct horse"),
+            last_used: now,
+            idle_timeout: Duration::from_secs(60),
+        };
+        assert!(!unlocked.is_expired(now + Duration::from_secs(59)));
+        assert!(unlocked.is_expired(now + Duration::from_secs(60)));
+
+        let config = VaultConfig { idle_timeout_secs: 1, ..VaultConfig::default() };
+        assert_eq!(config.idle_timeout(), Duration::from_secs(MIN_IDLE_TIMEOUT_SECS));
+        let config: VaultConfig = serde_json::from_str(r#"{"salt":null,"verifier":null}"#).unwrap();
+        assert_eq!(config.idle_timeout_secs, DEFAULT_IDLE_TIMEOUT_SECS);
+        assert!(!config.is_set_up());
+    }
+
+    #[test]
+    fn test_passive_use_does_not_extend_idle_timeout() {
+        let unlocked_at = Instant::now();
+        let mut vault = Some(UnlockedVault {
+            key: test_key("correct horse"),
+            last_used: unlocked_at,
+            idle_timeout: Duration::from_secs(60),
+        });
+        let later = unlocked_at + Duration::from_secs(30);
+
+        assert!(use_key(&mut vault, later, false, |_| Ok(())).is_ok());
+        assert_eq!(vault.as_ref().unwrap().last_used, unlocked_at);
+        assert!(use_key(&mut vault, later, true, |_| Ok(())).is_ok());
+        assert_eq!(vault.as_ref().unwrap().last_used, later);
+
+        // 期限切れならロックされる
+        let expired = later + Duration::from_secs(60);
+        assert_eq!(use_key(&mut vault, expired, false, |_| Ok(())).unwrap_err(), VAULT_LOCKED_ERROR);
+        assert!(vault.is_none());
+    }
+}
@@ -48,6 +48,16 @@ describe('API Layer', () => {
         expect(result).toEqual(mockPrompts)
       })
 
+      it('should pass the filter to the backend', async () => {
+        mockInvoke.mockResolvedValue({ success: true, data: [] })
+
+        await promptsApi.getAll(undefined, { status: 'active', workspaceId: 'wsp_team' })
+
+        expect(mockInvoke).toHaveBeenCalledWith('get_all_prompts', {
+          filter: { status: 'active', visibility: undefined, tagId: undefined, workspaceId: 'wsp_team' }
+        })
+      })
+
       it('should throw ApiError when command fails', async () => {
         mockInvoke.mockRejectedValue(new Error('Database error'))
 
@@ -204,6 +214,17 @@ describe('API Layer', () => {
         expect(result).toEqual(mockResults)
       })
 
+      it('should pass status, visibility, tag and workspace filters', async () => {
+        mockInvoke.mockResolvedValue({ success: true, data: [] })
+
+        await promptsApi.search({ q: 'review', status: 'draft', tagId: 'code', workspaceId: 'wsp_team' })
+
+        expect(mockInvoke).toHaveBeenCalledWith('search_prompts', {
+          query: 'review',
+          filter: { status: 'draft', visibility: undefined, tagId: 'code', workspaceId: 'wsp_team' }
+        })
+      })
+
       it('should handle undefined search query', async () => {
         const mockResults: any[] = []
         mockInvoke.mockResolvedValue({ success: true, data: mockResults })
@@ -5,6 +5,7 @@ import type {
   CreatePromptRequest, 
   UpdatePromptRequest, 
   SearchQuery,
+  PromptFilter,
   PinnedPrompt,
   PinPromptRequest,
   UnpinPromptRequest,
@@ -192,13 +193,25 @@ export const promptsApi = {
   /**
    * 全てのプロンプトを取得
    * @param signal - AbortSignal for request cancellation
+   * @param filter - 絞り込み条件（省略時は全件）
    * @returns プロンプトの配列
    */
-  async getAll(signal?: AbortSignal): Promise<Prompt[]> {
+  async getAll(signal?: AbortSignal, filter?: PromptFilter): Promise<Prompt[]> {
     if (isE2ETestEnvironment()) {
       return mockPromptsApi.getAll()
     }
-    const rawPrompts = await invokeCommand<RawPrompt[]>('get_all_prompts', undefined, signal)
+    const hasFilter = Boolean(filter?.status || filter?.visibility || filter?.tagId || filter?.workspaceId)
+    const args = hasFilter
+      ? {
+          filter: {
+            status: filter?.status,
+            visibility: filter?.visibility,
+            tagId: filter?.tagId,
+            workspaceId: filter?.workspaceId,
+          },
+        }
+      : undefined
+    const rawPrompts = await invokeCommand<RawPrompt[]>('get_all_prompts', args, signal)
     return rawPrompts.map(transformPromptFromDatabase)
   },
 
@@ -246,7 +259,10 @@ export const promptsApi = {
         title: request.title,
         content: request.content,
         tags: request.tags,
-        quickAccessKey: request.quickAccessKey
+        quickAccessKey: request.quickAccessKey,
+        status: request.status,
+        visibility: request.visibility,
+        workspaceId: request.workspaceId
       }
     })
     return rawPrompt ? transformPromptFromDatabase(rawPrompt) : null
@@ -285,7 +301,19 @@ export const promptsApi = {
       return mockPromptsApi.search(query)
     }
     const searchQuery = query.q || ''
-    const rawPrompts = await invokeCommand<RawPrompt[]>('search_prompts', { query: searchQuery })
+    const hasFilter = Boolean(query.status || query.visibility || query.tagId || query.workspaceId)
+    const args = hasFilter
+      ? {
+          query: searchQuery,
+          filter: {
+            status: query.status,
+            visibility: query.visibility,
+            tagId: query.tagId,
+            workspaceId: query.workspaceId,
+          },
+        }
+      : { query: searchQuery }
+    const rawPrompts = await invokeCommand<RawPrompt[]>('search_prompts', args)
     return rawPrompts.map(transformPromptFromDatabase)
   },
 }
@@ -1,3 +1,13 @@
+/**
+ * プロンプトの状態（APIサーバーの `status` と同じ値）
+ */
+export type PromptStatus = 'draft' | 'active' | 'archived'
+
+/**
+ * プロンプトの公開範囲（APIサーバーの `visibility` と同じ値）
+ */
+export type PromptVisibility = 'private' | 'public' | 'shared'
+
 /**
  * プロンプトのデータ構造を定義
  * PromPaletteで管理される個々のプロンプトの情報を表現
@@ -21,6 +31,16 @@ export interface Prompt {
   pinned_position?: number | null
   /** ピン留め日時（ISO 8601形式、ピン留めされていない場合はnull） */
   pinned_at?: string | null
+  /** 状態（デフォルト: active） */
+  status?: PromptStatus
+  /** 公開範囲（デフォルト: private） */
+  visibility?: PromptVisibility
+  /** 所属ワークスペースID（`wsp_`で始まる英数字、未割り当てはnull） */
+  workspace_id?: string | null
+  /** シークレット（保管庫のロック中は本文が空になる） */
+  is_secret?: boolean
+  /** 機密（ペースト後に一定時間でクリップボードから消去） */
+  is_sensitive?: boolean
 }
 
 /**
@@ -36,17 +56,32 @@ export interface CreatePromptRequest {
   tags?: string[]
   /** クイックアクセスキー（検索で一意特定するための任意キー、英数字のみ、2-20文字） */
   quickAccessKey?: string
+  /** 状態（省略時はactive） */
+  status?: PromptStatus
+  /** 公開範囲（省略時はprivate） */
+  visibility?: PromptVisibility
+  /** 所属ワークスペースID */
+  workspaceId?: string
+  /** 機密（ペースト後に一定時間でクリップボードから消去） */
+  isSensitive?: boolean
 }
 
 /**
  * プロンプト更新時のリクエストデータ
  * 既存プロンプトの一部または全部を更新する際に使用
  */
-export interface UpdatePromptRequest extends Partial<CreatePromptRequest> {
+export interface UpdatePromptRequest extends Partial<Omit<CreatePromptRequest, 'workspaceId'>> {
   /** 更新対象プロンプトの識別子（必須） */
   id: string
+  /** 所属ワークスペースID（省略時は変更しない、nullでワークスペースから外す） */
+  workspaceId?: string | null
 }
 
+/**
+ * 一覧取得時の絞り込み条件（バックエンドで絞り込む）
+ */
+export type PromptFilter = Pick<SearchQuery, 'status' | 'visibility' | 'tagId' | 'workspaceId'>
+
 /**
  * プロンプト検索時のクエリパラメータ
  * 検索API呼び出し時の条件指定に使用
@@ -56,6 +91,14 @@ export interface SearchQuery {
   q?: string
   /** タグによる絞り込み（AND条件） */
   tags?: string[]
+  /** タグIDによる絞り込み（完全一致、バックエンドで絞り込む） */
+  tagId?: string
+  /** 状態による絞り込み */
+  status?: PromptStatus
+  /** 公開範囲による絞り込み */
+  visibility?: PromptVisibility
+  /** ワークスペースによる絞り込み */
+  workspaceId?: string
   /** 取得件数の上限（デフォルト: 20） */
   limit?: number
   /** 検索結果のオフセット（ページネーション用） */